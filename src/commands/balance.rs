@@ -25,7 +25,11 @@ impl BalanceCommand {
     pub async fn execute(&self) -> Result<()> {
         // Load config to get the current network
         let config = ConfigManager::new()?.load()?;
-        let network = config.default_network.to_string().to_lowercase();
+        let network = config.network_name();
+        let native_symbol = config
+            .active_custom()
+            .map(|c| c.native_symbol.clone())
+            .unwrap_or_else(|| "RBTC".to_string());
 
         // Try to initialize eth client with graceful failure handling
         let eth_client_result = Helper::init_eth_client(&network).await;
@@ -66,7 +70,7 @@ impl BalanceCommand {
             // Check if it's the RBTC zero address
             if token == "0x0000000000000000000000000000000000000000" {
                 eth_client.get_balance(&address, &None).await
-                    .map(|balance| (balance, "RBTC".to_string()))
+                    .map(|balance| (balance, native_symbol.clone()))
             } else {
                 let token_address = Address::from_str(token)
                     .map_err(|_| anyhow!("Invalid token address format: {}", token))?;
@@ -87,7 +91,7 @@ impl BalanceCommand {
         } else {
             // Native RBTC balance
             eth_client.get_balance(&address, &None).await
-                .map(|balance| (balance, "RBTC".to_string()))
+                .map(|balance| (balance, native_symbol.clone()))
         };
 
         let (balance, token_name) = match balance_result {
@@ -110,7 +114,7 @@ impl BalanceCommand {
         table.add_header(&["Address", "Network", "Token", "Balance"]);
         table.add_row(&[
             &Helper::format_address(&address),
-            &config.default_network.to_string(),
+            &config.network_label(),
             &token_name,
             &balance_str,
         ]);
@@ -145,7 +149,7 @@ impl BalanceCommand {
         table.add_header(&["Address", "Network", "Status"]);
         table.add_row(&[
             &Helper::format_address(&address),
-            &config.default_network.to_string(),
+            &config.network_label(),
             "Offline - Balance unavailable",
         ]);
 
@@ -4,8 +4,10 @@ use colored::Colorize;
 use alloy::primitives::Address;
 use std::str::FromStr;
 
-use crate::types::contacts::Contact;
+use crate::types::contacts::{Contact, ContactGroup};
 use crate::utils::table::TableBuilder;
+use crate::utils::constants;
+use crate::utils::contact_io::{self, ConflictPolicy, ContactFormat, read_contacts, write_contacts};
 
 #[derive(Parser, Debug)]
 pub struct ContactsCommand {
@@ -62,15 +64,90 @@ pub enum ContactsAction {
         /// Search term
         query: String,
     },
+    /// Manage contact groups used as bulk-transfer recipient lists
+    Group {
+        #[command(subcommand)]
+        action: GroupAction,
+    },
     /// Save contacts to a file
     Save {
         /// File path to save contacts
         file: Option<String>,
+        /// File format; taken from the extension when omitted
+        #[arg(short, long, value_enum)]
+        format: Option<ContactFormat>,
     },
-    /// Load contacts from a file
+    /// Load contacts from a file, merging them with the saved ones
     Load {
         /// File path to load contacts from
         file: Option<String>,
+        /// File format; taken from the extension when omitted
+        #[arg(short, long, value_enum)]
+        format: Option<ContactFormat>,
+        /// What to do with contacts whose name or address is already saved
+        #[arg(long, value_enum, default_value_t = ConflictPolicy::Keep)]
+        on_conflict: ConflictPolicy,
+        /// Show what would change without saving
+        #[arg(long)]
+        dry_run: bool,
+    },
+}
+
+#[derive(Parser, Debug)]
+pub enum GroupAction {
+    /// Create a group from contacts, a tag, or both
+    Create {
+        /// Group name
+        name: String,
+        /// Contact names or addresses to include
+        #[arg(short, long)]
+        members: Vec<String>,
+        /// Include every contact with this tag
+        #[arg(short, long)]
+        tag: Option<String>,
+        /// Amount sent to each member by default
+        #[arg(short, long)]
+        amount: Option<String>,
+        /// Token address sent by default (RBTC when omitted)
+        #[arg(long)]
+        token: Option<String>,
+    },
+    /// List groups and their members
+    List,
+    /// Delete a group
+    Delete {
+        /// Group name
+        name: String,
+    },
+    /// Add a contact to a group
+    Add {
+        /// Group name
+        group: String,
+        /// Contact name or address
+        member: String,
+    },
+    /// Remove a contact from a group
+    Remove {
+        /// Group name
+        group: String,
+        /// Contact name or address
+        member: String,
+    },
+    /// Set the amount or token one member gets instead of the group default
+    Override {
+        /// Group name
+        group: String,
+        /// Contact name or address
+        member: String,
+        /// Amount for this member
+        #[arg(short, long)]
+        amount: Option<String>,
+        /// Token address for this member
+        #[arg(long)]
+        token: Option<String>,
+        /// Drop the member's override
+        #[arg(long)]
+        clear: bool,
     },
 }
 
@@ -106,8 +183,17 @@ impl ContactsCommand {
             }
             ContactsAction::Get { identifier } => self.get_contact(identifier).await?,
             ContactsAction::Search { query } => self.search_contacts(query).await?,
-            ContactsAction::Load { file } => self.load_contacts_from_file(file).await?,
-            ContactsAction::Save { file } => self.save_contacts_to_file(file).await?,
+            ContactsAction::Load {
+                file,
+                format,
+                on_conflict,
+                dry_run,
+            } => {
+                self.load_contacts_from_file(file, *format, *on_conflict, *dry_run)
+                    .await?
+            }
+            ContactsAction::Group { action } => self.group(action)?,
+            ContactsAction::Save { file, format } => self.save_contacts_to_file(file, *format).await?,
         }
         Ok(())
     }
@@ -277,10 +363,7 @@ impl ContactsCommand {
     }
 
     pub fn load_contacts(&self) -> Result<Vec<Contact>> {
-        let contacts_path = dirs::data_local_dir()
-            .ok_or_else(|| anyhow::anyhow!("Failed to get data directory"))?
-            .join("rsk-rust-cli")
-            .join("contacts.json");
+        let contacts_path = constants::data_dir().join("contacts.json");
 
         if !contacts_path.exists() {
             return Ok(Vec::new());
@@ -303,11 +386,7 @@ impl ContactsCommand {
     //     Ok(())
     // }
     pub fn save_contacts(&self, contacts: &[Contact]) -> Result<()> {
-        let contacts_dir = dirs::data_local_dir()
-            .ok_or_else(|| anyhow::anyhow!("Failed to get data directory"))?
-            .join("rsk-rust-cli");
-
-        std::fs::create_dir_all(&contacts_dir)?;
+        let contacts_dir = constants::data_dir();
 
         let contacts_path = contacts_dir.join("contacts.json");
         let content = serde_json::to_string_pretty(contacts)?;
@@ -315,7 +394,155 @@ impl ContactsCommand {
         Ok(())
     }
 
-    pub async fn save_contacts_to_file(&self, file: &Option<String>) -> Result<()> {
+    pub fn load_groups(&self) -> Result<Vec<ContactGroup>> {
+        let groups_path = constants::data_dir().join("contact_groups.json");
+
+        if !groups_path.exists() {
+            return Ok(Vec::new());
+        }
+
+        let content = std::fs::read_to_string(groups_path)?;
+        serde_json::from_str(&content)
+            .map_err(|e| anyhow::anyhow!("Failed to parse contact groups: {}", e))
+    }
+
+    pub fn save_groups(&self, groups: &[ContactGroup]) -> Result<()> {
+        let groups_path = constants::data_dir().join("contact_groups.json");
+        let content = serde_json::to_string_pretty(groups)?;
+        std::fs::write(groups_path, content)?;
+        Ok(())
+    }
+
+    /// Address of a contact given by name or address; plain addresses
+    /// need not be contacts
+    fn member_address(&self, contacts: &[Contact], member: &str) -> Result<Address> {
+        contacts
+            .iter()
+            .find(|c| c.name.eq_ignore_ascii_case(member))
+            .map(|c| c.address)
+            .or_else(|| Address::from_str(member).ok())
+            .ok_or_else(|| anyhow::anyhow!("No contact named '{}'", member))
+    }
+
+    fn group(&self, action: &GroupAction) -> Result<()> {
+        let contacts = self.load_contacts()?;
+        let mut groups = self.load_groups()?;
+        let find = |groups: &[ContactGroup], name: &str| {
+            groups
+                .iter()
+                .position(|g| g.name.eq_ignore_ascii_case(name))
+                .ok_or_else(|| anyhow::anyhow!("Group '{}' not found", name))
+        };
+
+        match action {
+            GroupAction::Create {
+                name,
+                members,
+                tag,
+                amount,
+                token,
+            } => {
+                if groups.iter().any(|g| g.name.eq_ignore_ascii_case(name)) {
+                    return Err(anyhow::anyhow!("Group '{}' already exists", name));
+                }
+                let mut group = ContactGroup::new(name.clone());
+                for member in members {
+                    let address = self.member_address(&contacts, member)?;
+                    if !group.members.contains(&address) {
+                        group.members.push(address);
+                    }
+                }
+                group.tag = tag.clone();
+                group.default_amount = amount.clone();
+                group.default_token = token.as_deref().map(parse_token).transpose()?;
+                group.validate()?;
+                println!(
+                    "{}: Group {} created with {} members",
+                    "Success".green().bold(),
+                    name,
+                    group.recipients(&contacts).len()
+                );
+                groups.push(group);
+            }
+            GroupAction::List => {
+                if groups.is_empty() {
+                    println!("{}: No groups found", "Info".yellow().bold());
+                    return Ok(());
+                }
+                let mut table = TableBuilder::new();
+                table.add_header(&["Group", "Member", "Address", "Amount", "Token"]);
+                for group in &groups {
+                    for recipient in group.recipients(&contacts) {
+                        table.add_row(&[
+                            &group.name,
+                            &recipient.name,
+                            &recipient.address.to_string(),
+                            recipient.amount.as_deref().unwrap_or("-"),
+                            recipient.token.as_deref().unwrap_or("RBTC"),
+                        ]);
+                    }
+                }
+                table.print();
+                return Ok(());
+            }
+            GroupAction::Delete { name } => {
+                let index = find(&groups, name)?;
+                groups.remove(index);
+                println!("{}: Group removed successfully", "Success".green().bold());
+            }
+            GroupAction::Add { group, member } => {
+                let index = find(&groups, group)?;
+                let address = self.member_address(&contacts, member)?;
+                if !groups[index].members.contains(&address) {
+                    groups[index].members.push(address);
+                }
+                println!("{}: {} added to {}", "Success".green().bold(), member, group);
+            }
+            GroupAction::Remove { group, member } => {
+                let index = find(&groups, group)?;
+                let address = self.member_address(&contacts, member)?;
+                let group = &mut groups[index];
+                group.members.retain(|m| *m != address);
+                group.overrides.remove(&address);
+                group.validate()?;
+                println!("{}: {} removed from {}", "Success".green().bold(), member, group.name);
+            }
+            GroupAction::Override {
+                group,
+                member,
+                amount,
+                token,
+                clear,
+            } => {
+                let index = find(&groups, group)?;
+                let address = self.member_address(&contacts, member)?;
+                let group = &mut groups[index];
+                if !group.recipients(&contacts).iter().any(|r| r.address == address) {
+                    return Err(anyhow::anyhow!("{} is not a member of {}", member, group.name));
+                }
+                if *clear {
+                    group.overrides.remove(&address);
+                } else {
+                    let entry = group.overrides.entry(address).or_default();
+                    if amount.is_some() {
+                        entry.amount = amount.clone();
+                    }
+                    if let Some(token) = token {
+                        entry.token = Some(parse_token(token)?);
+                    }
+                }
+                println!("{}: Override for {} saved", "Success".green().bold(), member);
+            }
+        }
+
+        self.save_groups(&groups)
+    }
+
+    pub async fn save_contacts_to_file(
+        &self,
+        file: &Option<String>,
+        format: Option<ContactFormat>,
+    ) -> Result<()> {
         let contacts = self.load_contacts()?;
 
         let file_path = match file {
@@ -325,19 +552,26 @@ impl ContactsCommand {
                 std::env::current_dir()?.join("contacts.json")
             }
         };
+        let format = resolve_format(&file_path, format)?;
 
-        let content = serde_json::to_string_pretty(&contacts)?;
-        std::fs::write(&file_path, content)?;
+        write_contacts(&file_path, format, &contacts)?;
 
         println!(
-            "{}: Contacts saved to {}",
+            "{}: {} contacts saved to {}",
             "Success".green().bold(),
+            contacts.len(),
             file_path.display()
         );
         Ok(())
     }
 
-    pub async fn load_contacts_from_file(&self, file: &Option<String>) -> Result<()> {
+    pub async fn load_contacts_from_file(
+        &self,
+        file: &Option<String>,
+        format: Option<ContactFormat>,
+        on_conflict: ConflictPolicy,
+        dry_run: bool,
+    ) -> Result<()> {
         let file_path = match file {
             Some(path) => std::path::PathBuf::from(path),
             None => {
@@ -345,14 +579,20 @@ impl ContactsCommand {
                 std::env::current_dir()?.join("contacts.json")
             }
         };
+        let format = resolve_format(&file_path, format)?;
 
-        let content = std::fs::read_to_string(&file_path)?;
-        let contacts: Vec<Contact> = serde_json::from_str(&content)?;
+        let parsed = read_contacts(&file_path, format)?;
+        let mut contacts = self.load_contacts()?;
+        let summary = contact_io::merge(&mut contacts, parsed.contacts, on_conflict);
+        summary.print(&parsed.invalid);
 
-        // Merge with existing contacts (optional - you might want to replace instead)
-        let mut existing_contacts = self.load_contacts().unwrap_or_default();
-        existing_contacts.extend(contacts);
-        self.save_contacts(&existing_contacts)?;
+        if dry_run {
+            println!("{}: Dry run, nothing saved", "Info".yellow().bold());
+            return Ok(());
+        }
+        if summary.changes() > 0 {
+            self.save_contacts(&contacts)?;
+        }
 
         println!(
             "{}: Contacts loaded from {}",
@@ -362,3 +602,24 @@ impl ContactsCommand {
         Ok(())
     }
 }
+
+/// Token addresses are stored checksummed; "rbtc" and the zero address mean RBTC
+fn parse_token(token: &str) -> Result<String> {
+    if token.eq_ignore_ascii_case("rbtc") {
+        return Ok(Address::ZERO.to_checksum(None));
+    }
+    Address::from_str(token)
+        .map(|address| address.to_checksum(None))
+        .map_err(|_| anyhow::anyhow!("Invalid token address: {}", token))
+}
+
+fn resolve_format(path: &std::path::Path, format: Option<ContactFormat>) -> Result<ContactFormat> {
+    format
+        .or_else(|| ContactFormat::from_path(path))
+        .ok_or_else(|| {
+            anyhow::anyhow!(
+                "Cannot tell the contacts format from '{}'; pass --format",
+                path.display()
+            )
+        })
+}
@@ -0,0 +1,537 @@
+use crate::types::transaction::TransactionStatus;
+use crate::types::wallet::WalletData;
+use crate::utils::block_resolver::{DateRange, DateZone, parse_date_range};
+use crate::utils::constants;
+use crate::utils::export::{ExportContext, StatementLine, format_amount};
+use crate::utils::prices::{CsvPriceSource, PriceSource};
+use crate::utils::table::TableBuilder;
+use crate::utils::tx_store::TransactionStore;
+use alloy::primitives::{Address, B256, I256, U256, utils::format_units};
+use anyhow::{Result, anyhow};
+use chrono::{DateTime, Utc};
+use clap::{Parser, ValueEnum};
+use console::style;
+use std::collections::{HashMap, VecDeque};
+use std::fs;
+use std::str::FromStr;
+
+/// How disposals are matched against earlier acquisitions
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum CostMethod {
+    /// First in, first out
+    #[default]
+    Fifo,
+    /// Last in, first out
+    Lifo,
+    /// Average cost of all units held
+    Average,
+}
+
+impl std::fmt::Display for CostMethod {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Fifo => write!(f, "FIFO"),
+            Self::Lifo => write!(f, "LIFO"),
+            Self::Average => write!(f, "average cost"),
+        }
+    }
+}
+
+/// Units acquired together; `acquired` is `None` for an average-cost pool
+#[derive(Debug, Clone, PartialEq)]
+pub struct Lot {
+    pub acquired: Option<DateTime<Utc>>,
+    pub quantity: U256,
+    pub cost: f64,
+}
+
+/// The part of a disposal matched against one lot
+#[derive(Debug, Clone, PartialEq)]
+pub struct MatchedLot {
+    pub acquired: Option<DateTime<Utc>>,
+    pub quantity: U256,
+    pub cost: f64,
+}
+
+/// Open lots of one asset
+#[derive(Debug, Clone, Default)]
+pub struct LotBook {
+    method: CostMethod,
+    lots: VecDeque<Lot>,
+}
+
+impl LotBook {
+    pub fn new(method: CostMethod) -> Self {
+        Self {
+            method,
+            lots: VecDeque::new(),
+        }
+    }
+
+    pub fn lots(&self) -> impl Iterator<Item = &Lot> {
+        self.lots.iter()
+    }
+
+    pub fn acquire(&mut self, time: DateTime<Utc>, quantity: U256, cost: f64) {
+        if quantity.is_zero() {
+            return;
+        }
+        match (self.method, self.lots.front_mut()) {
+            (CostMethod::Average, Some(pool)) => {
+                pool.quantity += quantity;
+                pool.cost += cost;
+            }
+            (CostMethod::Average, None) => self.lots.push_back(Lot {
+                acquired: None,
+                quantity,
+                cost,
+            }),
+            _ => self.lots.push_back(Lot {
+                acquired: Some(time),
+                quantity,
+                cost,
+            }),
+        }
+    }
+
+    /// Removes `quantity` from the open lots. Returns the matched parts and
+    /// the quantity no lot covered, which happens when earlier history is
+    /// missing.
+    pub fn dispose(&mut self, quantity: U256) -> (Vec<MatchedLot>, U256) {
+        let mut remaining = quantity;
+        let mut matched = Vec::new();
+        while !remaining.is_zero() {
+            let lot = match self.method {
+                CostMethod::Lifo => self.lots.back_mut(),
+                CostMethod::Fifo | CostMethod::Average => self.lots.front_mut(),
+            };
+            let Some(lot) = lot else {
+                break;
+            };
+
+            let taken = remaining.min(lot.quantity);
+            let cost = lot.cost * ratio(taken, lot.quantity);
+            matched.push(MatchedLot {
+                acquired: lot.acquired,
+                quantity: taken,
+                cost,
+            });
+            lot.quantity -= taken;
+            lot.cost -= cost;
+            remaining -= taken;
+
+            if lot.quantity.is_zero() {
+                match self.method {
+                    CostMethod::Lifo => self.lots.pop_back(),
+                    CostMethod::Fifo | CostMethod::Average => self.lots.pop_front(),
+                };
+            }
+        }
+        (matched, remaining)
+    }
+}
+
+fn ratio(part: U256, whole: U256) -> f64 {
+    if whole.is_zero() {
+        return 0.0;
+    }
+    f64::from(part) / f64::from(whole)
+}
+
+fn units(quantity: U256, decimals: u8) -> f64 {
+    format_units(quantity, decimals)
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or_default()
+}
+
+/// A realized gain or loss from disposing of part of a lot
+#[derive(Debug, Clone)]
+pub struct Realized {
+    pub time: DateTime<Utc>,
+    pub hash: B256,
+    pub asset: String,
+    pub decimals: u8,
+    pub quantity: U256,
+    pub acquired: Option<DateTime<Utc>>,
+    pub proceeds: f64,
+    pub cost: f64,
+    pub is_fee: bool,
+    /// No acquisition covered these units, so their cost basis is zero
+    pub unmatched: bool,
+    pub counterparty: Option<String>,
+}
+
+impl Realized {
+    pub fn gain(&self) -> f64 {
+        self.proceeds - self.cost
+    }
+}
+
+/// Result of walking a wallet's history with one lot matching method
+#[derive(Debug, Default)]
+pub struct CostBasisReport {
+    pub realized: Vec<Realized>,
+    /// Open lots per asset symbol at the end of the range
+    pub open_lots: Vec<(String, u8, Lot)>,
+    /// Asset and time of transfers that could not be valued
+    pub missing_prices: Vec<(String, DateTime<Utc>)>,
+}
+
+/// Walks the statement in chronological order, matching disposals against
+/// earlier acquisitions. Only disposals inside `range` are reported, but
+/// everything before it builds the lots. RBTC spent on fees counts as a
+/// disposal.
+pub fn build_report(
+    lines: &[StatementLine],
+    prices: &dyn PriceSource,
+    method: CostMethod,
+    range: &DateRange,
+) -> CostBasisReport {
+    let mut report = CostBasisReport::default();
+    let mut books: HashMap<Option<Address>, (String, u8, LotBook)> = HashMap::new();
+
+    for line in lines {
+        if range.end.is_some_and(|end| line.time >= end) {
+            break;
+        }
+        if !matches!(line.status, TransactionStatus::Success | TransactionStatus::Failed) {
+            continue;
+        }
+        let in_range = range.start.is_none_or(|start| line.time >= start);
+
+        let mut movements = Vec::new();
+        if line.status == TransactionStatus::Success && !line.amount.is_zero() {
+            movements.push((line.token, line.asset.symbol.clone(), line.asset.decimals, line.amount, false));
+        }
+        if !line.fee.is_zero() {
+            movements.push((None, "RBTC".to_string(), 18, -I256::from_raw(line.fee), true));
+        }
+
+        for (token, symbol, decimals, amount, is_fee) in movements {
+            let Some(price) = prices.price_at(&symbol, line.time) else {
+                report.missing_prices.push((symbol, line.time));
+                continue;
+            };
+            let (_, _, book) = books
+                .entry(token)
+                .or_insert_with(|| (symbol.clone(), decimals, LotBook::new(method)));
+            let quantity = amount.unsigned_abs();
+            let value = units(quantity, decimals) * price;
+
+            if amount.is_positive() {
+                book.acquire(line.time, quantity, value);
+                continue;
+            }
+
+            let (matched, unmatched) = book.dispose(quantity);
+            if !in_range {
+                continue;
+            }
+            let parts = matched
+                .into_iter()
+                .map(|m| (m.acquired, m.quantity, m.cost, false))
+                .chain((!unmatched.is_zero()).then_some((None, unmatched, 0.0, true)));
+            for (acquired, part, cost, unmatched) in parts {
+                report.realized.push(Realized {
+                    time: line.time,
+                    hash: line.hash,
+                    asset: symbol.clone(),
+                    decimals,
+                    quantity: part,
+                    acquired,
+                    proceeds: value * ratio(part, quantity),
+                    cost,
+                    is_fee,
+                    unmatched,
+                    counterparty: if is_fee { None } else { line.counterparty_name.clone() },
+                });
+            }
+        }
+    }
+
+    let mut books: Vec<_> = books.into_values().collect();
+    books.sort_by(|a, b| a.0.cmp(&b.0));
+    for (symbol, decimals, book) in books {
+        for lot in book.lots() {
+            report.open_lots.push((symbol.clone(), decimals, lot.clone()));
+        }
+    }
+    report
+}
+
+/// Realized gains and losses and remaining lots, computed from the local
+/// transaction history and a price file
+#[derive(Debug, Parser, Clone)]
+pub struct CostBasisCommand {
+    /// Address to report on (defaults to the current wallet)
+    #[arg(short, long)]
+    pub address: Option<String>,
+
+    /// Network whose stored history is used
+    #[arg(long, default_value = "mainnet")]
+    pub network: String,
+
+    /// CSV file with date, asset and price columns
+    #[arg(long)]
+    pub prices: String,
+
+    /// Lot matching method
+    #[arg(long, value_enum, default_value_t = CostMethod::Fifo)]
+    pub method: CostMethod,
+
+    /// Start of the reporting period (same formats as history --from)
+    #[arg(long)]
+    pub from: Option<String>,
+
+    /// End of the reporting period, inclusive
+    #[arg(long)]
+    pub to: Option<String>,
+
+    /// Timezone for dates: local, UTC or an offset such as +02:00
+    #[arg(long, default_value = "local")]
+    pub timezone: String,
+
+    /// Write the realized gains to a CSV file
+    #[arg(long)]
+    pub output: Option<String>,
+}
+
+impl CostBasisCommand {
+    pub async fn execute(&self) -> Result<()> {
+        let network = self.network.to_lowercase();
+        let address = match &self.address {
+            Some(addr) => Address::from_str(addr)
+                .map_err(|_| anyhow!("Invalid address format. Expected 0x-prefixed hex string"))?,
+            None => {
+                let wallet_file = constants::wallet_file_path();
+                if !wallet_file.exists() {
+                    anyhow::bail!("No wallets found. Create or import a wallet first.");
+                }
+                let data = fs::read_to_string(&wallet_file)?;
+                serde_json::from_str::<WalletData>(&data)?
+                    .get_current_wallet()
+                    .ok_or_else(|| anyhow!("No default wallet selected. Use `wallet switch` first."))?
+                    .address
+            }
+        };
+
+        let store = TransactionStore::load(&network, &address)?;
+        if store.transactions.is_empty() {
+            anyhow::bail!(
+                "No stored history for {:#x} on {}. Open the transaction history once to sync it.",
+                address,
+                network
+            );
+        }
+        if store.first_synced_block != Some(0) {
+            println!(
+                "{}",
+                style("⚠️  History is not synced from genesis; earlier acquisitions are missing and their disposals have no cost basis").yellow()
+            );
+        }
+
+        let prices = CsvPriceSource::load(&self.prices)?;
+        let zone = DateZone::parse(&self.timezone)?;
+        let range = parse_date_range(self.from.as_deref(), self.to.as_deref(), zone)?;
+
+        // Tokens outside the registry have unknown decimals and no reliable symbol
+        let context = ExportContext::load(address, &network, zone)?;
+        let unknown = context.unknown_tokens(&store.transactions);
+        let txs: Vec<_> = store
+            .transactions
+            .iter()
+            .filter(|tx| tx.token_address.is_none_or(|token| !unknown.contains(&token)))
+            .cloned()
+            .collect();
+        let statement = context.statement(&txs, &HashMap::new());
+        let report = build_report(&statement.lines, &prices, self.method, &range);
+
+        self.display(&report, prices.currency(), zone);
+        if !unknown.is_empty() {
+            println!(
+                "{}",
+                style(format!("ℹ️  Skipped {} token(s) not in the registry", unknown.len())).dim()
+            );
+        }
+        if let Some(path) = &self.output {
+            write_csv(path, &report, prices.currency(), zone)?;
+            println!(
+                "\n{} Wrote {} realized entries to {}",
+                style("✓").green().bold(),
+                report.realized.len(),
+                style(path).cyan()
+            );
+        }
+        Ok(())
+    }
+
+    fn display(&self, report: &CostBasisReport, currency: &str, zone: DateZone) {
+        println!(
+            "\n{}",
+            style(format!("Realized Gains and Losses ({})", self.method)).bold().underlined()
+        );
+        if report.realized.is_empty() {
+            println!("{}", style("  No disposals in this period").dim());
+        } else {
+            let mut table = TableBuilder::new();
+            table.add_header(&["Date", "Asset", "Quantity", "Acquired", "Proceeds", "Cost", "Gain/Loss", "To"]);
+            for entry in &report.realized {
+                let gain = format!("{:.2}", entry.gain());
+                table.add_row(&[
+                    &zone.format(entry.time, "%Y-%m-%d"),
+                    &if entry.is_fee { format!("{} (fee)", entry.asset) } else { entry.asset.clone() },
+                    &format_amount(I256::from_raw(entry.quantity), entry.decimals),
+                    &acquired_label(entry, zone),
+                    &format!("{:.2}", entry.proceeds),
+                    &format!("{:.2}", entry.cost),
+                    &gain,
+                    entry.counterparty.as_deref().unwrap_or("-"),
+                ]);
+            }
+            table.print();
+        }
+
+        let mut totals: Vec<(String, f64, f64)> = Vec::new();
+        for entry in &report.realized {
+            match totals.iter_mut().find(|(asset, _, _)| *asset == entry.asset) {
+                Some((_, proceeds, cost)) => {
+                    *proceeds += entry.proceeds;
+                    *cost += entry.cost;
+                }
+                None => totals.push((entry.asset.clone(), entry.proceeds, entry.cost)),
+            }
+        }
+        for (asset, proceeds, cost) in &totals {
+            let gain = proceeds - cost;
+            let gain = if gain < 0.0 {
+                style(format!("{:.2} {}", gain, currency)).red()
+            } else {
+                style(format!("{:.2} {}", gain, currency)).green()
+            };
+            println!("  {}: proceeds {:.2}, cost {:.2}, net {}", style(asset).bold(), proceeds, cost, gain);
+        }
+
+        println!("\n{}", style("Remaining Lots").bold().underlined());
+        if report.open_lots.is_empty() {
+            println!("{}", style("  None").dim());
+        } else {
+            let mut table = TableBuilder::new();
+            table.add_header(&["Asset", "Acquired", "Quantity", &format!("Cost ({})", currency)]);
+            for (asset, decimals, lot) in &report.open_lots {
+                table.add_row(&[
+                    asset,
+                    &lot.acquired
+                        .map(|t| zone.format(t, "%Y-%m-%d"))
+                        .unwrap_or_else(|| "pooled".into()),
+                    &format_amount(I256::from_raw(lot.quantity), *decimals),
+                    &format!("{:.2}", lot.cost),
+                ]);
+            }
+            table.print();
+        }
+
+        if report.realized.iter().any(|r| r.unmatched) {
+            println!(
+                "{}",
+                style("⚠️  Some disposals had no matching acquisition and use a zero cost basis").yellow()
+            );
+        }
+        if !report.missing_prices.is_empty() {
+            println!(
+                "{}",
+                style(format!(
+                    "⚠️  {} transfer(s) were left out because the price file has no earlier price:",
+                    report.missing_prices.len()
+                ))
+                .yellow()
+            );
+            for (asset, time) in report.missing_prices.iter().take(10) {
+                println!("   {} at {}", asset, zone.format(*time, "%Y-%m-%d %H:%M"));
+            }
+        }
+    }
+}
+
+fn acquired_label(entry: &Realized, zone: DateZone) -> String {
+    match (entry.unmatched, entry.acquired) {
+        (true, _) => "unknown".to_string(),
+        (false, Some(time)) => zone.format(time, "%Y-%m-%d"),
+        (false, None) => "pooled".to_string(),
+    }
+}
+
+fn write_csv(path: &str, report: &CostBasisReport, currency: &str, zone: DateZone) -> Result<()> {
+    let mut wtr = csv::Writer::from_path(path)?;
+    wtr.write_record([
+        "Date Disposed".to_string(),
+        "Transaction Hash".to_string(),
+        "Asset".to_string(),
+        "Kind".to_string(),
+        "Quantity".to_string(),
+        "Date Acquired".to_string(),
+        format!("Proceeds ({})", currency),
+        format!("Cost Basis ({})", currency),
+        format!("Gain/Loss ({})", currency),
+        "Counterparty".to_string(),
+    ])?;
+    for entry in &report.realized {
+        wtr.write_record([
+            zone.format(entry.time, "%Y-%m-%d %H:%M:%S %:z"),
+            format!("{:#x}", entry.hash),
+            entry.asset.clone(),
+            if entry.is_fee { "fee" } else { "transfer" }.to_string(),
+            format_amount(I256::from_raw(entry.quantity), entry.decimals),
+            acquired_label(entry, zone),
+            format!("{:.2}", entry.proceeds),
+            format!("{:.2}", entry.cost),
+            format!("{:.2}", entry.gain()),
+            entry.counterparty.clone().unwrap_or_default(),
+        ])?;
+    }
+    wtr.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn day(n: i64) -> DateTime<Utc> {
+        DateTime::from_timestamp(n * 86_400, 0).unwrap()
+    }
+
+    fn book(method: CostMethod) -> LotBook {
+        let mut book = LotBook::new(method);
+        book.acquire(day(1), U256::from(10), 100.0);
+        book.acquire(day(2), U256::from(10), 300.0);
+        book
+    }
+
+    #[test]
+    fn test_fifo_and_lifo_matching() {
+        let (matched, unmatched) = book(CostMethod::Fifo).dispose(U256::from(15));
+        assert!(unmatched.is_zero());
+        assert_eq!(matched.len(), 2);
+        assert_eq!(matched[0].acquired, Some(day(1)));
+        assert_eq!(matched[0].cost, 100.0);
+        assert_eq!(matched[1].quantity, U256::from(5));
+        assert_eq!(matched[1].cost, 150.0);
+
+        let (matched, _) = book(CostMethod::Lifo).dispose(U256::from(15));
+        assert_eq!(matched[0].acquired, Some(day(2)));
+        assert_eq!(matched[0].cost, 300.0);
+        assert_eq!(matched[1].cost, 50.0);
+    }
+
+    #[test]
+    fn test_average_cost_and_unmatched_quantity() {
+        let mut book = book(CostMethod::Average);
+        let (matched, unmatched) = book.dispose(U256::from(25));
+        assert_eq!(matched.len(), 1);
+        assert_eq!(matched[0].acquired, None);
+        assert_eq!(matched[0].cost, 400.0);
+        assert_eq!(unmatched, U256::from(5));
+        assert_eq!(book.lots().count(), 0);
+    }
+}
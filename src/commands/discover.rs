@@ -0,0 +1,303 @@
+use crate::api::ApiProvider;
+use crate::commands::tokens::TokenRegistry;
+use crate::config::ConfigManager;
+use crate::types::network::Network;
+use crate::types::wallet::WalletData;
+use crate::utils::alchemy::AlchemyClient;
+use crate::utils::constants;
+use crate::utils::helper::Helper;
+use crate::utils::indexer::{DEFAULT_LOOKBACK_BLOCKS, HistoryIndexer};
+use crate::utils::tx_store::TransactionStore;
+use alloy::primitives::{Address, U256, utils::format_units};
+use alloy::providers::Provider;
+use anyhow::{Result, anyhow, bail};
+use clap::Parser;
+use console::style;
+use std::collections::HashSet;
+use std::fs;
+use std::str::FromStr;
+
+/// Symbols a token may use to pass itself off as the native coin
+const NATIVE_SYMBOLS: &[&str] = &["RBTC", "R-BTC", "BTC", "WRBTC"];
+
+#[derive(Parser, Debug)]
+pub struct TokenDiscoverCommand {
+    /// Address to scan; defaults to the current wallet
+    #[arg(short, long)]
+    pub address: Option<String>,
+
+    /// Network to scan (mainnet/testnet)
+    #[arg(short, long, default_value = "mainnet")]
+    pub network: String,
+
+    /// First block to scan for Transfer logs when Alchemy is not configured
+    #[arg(long)]
+    pub from_block: Option<u64>,
+
+    /// Add every held token that was not flagged as suspicious
+    #[arg(long)]
+    pub add: bool,
+}
+
+/// A token contract the address has interacted with
+#[derive(Debug, Clone)]
+pub struct DiscoveredToken {
+    pub address: Address,
+    pub symbol: Option<String>,
+    pub decimals: Option<u8>,
+    pub balance: Option<U256>,
+    /// Symbol the token is registered under, if it already is
+    pub registered: Option<String>,
+    /// Why the token looks like a spoof or a lure
+    pub warnings: Vec<String>,
+}
+
+impl DiscoveredToken {
+    pub fn is_suspicious(&self) -> bool {
+        !self.warnings.is_empty()
+    }
+
+    /// Unregistered, readable and currently held
+    pub fn can_add(&self) -> bool {
+        self.registered.is_none()
+            && self.symbol.is_some()
+            && self.decimals.is_some()
+            && self.balance.is_some_and(|b| !b.is_zero())
+    }
+
+    pub fn label(&self) -> String {
+        let symbol = self.symbol.as_deref().unwrap_or("???");
+        let balance = match (self.balance, self.decimals) {
+            (Some(balance), Some(decimals)) => format_units(balance, decimals).unwrap_or_default(),
+            _ => "-".to_string(),
+        };
+        format!("{} {} ({:#x})", balance, symbol, self.address)
+    }
+}
+
+impl TokenDiscoverCommand {
+    pub async fn execute(&self) -> Result<()> {
+        let holder = match &self.address {
+            Some(address) => Address::from_str(address).map_err(|_| anyhow!("Invalid address: {}", address))?,
+            None => current_wallet_address()?,
+        };
+        let network = self.network.to_lowercase();
+        let tokens = discover(&network, holder, self.from_block).await?;
+        print_discovered(&tokens);
+
+        let addable: Vec<&DiscoveredToken> = tokens.iter().filter(|t| t.can_add()).collect();
+        if addable.is_empty() {
+            return Ok(());
+        }
+        if !self.add {
+            println!("\nRun again with --add to register the tokens that were not flagged");
+            return Ok(());
+        }
+        let selected: Vec<&DiscoveredToken> = addable.into_iter().filter(|t| !t.is_suspicious()).collect();
+        add_discovered(&network, &selected)
+    }
+}
+
+/// Finds the token contracts `holder` has sent or received, from Alchemy's
+/// ERC20 transfers when a key is configured and otherwise from `Transfer`
+/// logs since `from_block` plus the locally synced history, then reads
+/// their metadata and the holder's balances
+pub async fn discover(network: &str, holder: Address, from_block: Option<u64>) -> Result<Vec<DiscoveredToken>> {
+    let network_enum = match network {
+        "mainnet" => Network::Mainnet,
+        "testnet" => Network::Testnet,
+        _ => bail!("Invalid network. Use 'mainnet' or 'testnet'."),
+    };
+    let app_config = ConfigManager::new()?.load()?;
+    let (_, client) = Helper::init_eth_client(network).await?;
+
+    let alchemy_key = app_config
+        .api_key_for(&ApiProvider::Alchemy, &network_enum)
+        .map(str::to_string)
+        .or_else(|| std::env::var("ALCHEMY_API_KEY").ok());
+    let mut contracts: HashSet<Address> = match alchemy_key {
+        Some(api_key) => {
+            println!("{}", style("🔍 Scanning ERC20 transfers via Alchemy...").dim());
+            let alchemy_url = app_config
+                .endpoint_override(&network_enum)
+                .and_then(|o| o.alchemy_url.clone());
+            AlchemyClient::new(api_key, network == "testnet")
+                .with_base_url(alchemy_url)
+                .token_contracts(&format!("{:#x}", holder))
+                .await?
+        }
+        None => {
+            let latest = client
+                .provider()
+                .get_block_number()
+                .await
+                .map_err(|e| anyhow!("Failed to get latest block: {}", e))?;
+            let from = from_block.unwrap_or_else(|| latest.saturating_sub(DEFAULT_LOOKBACK_BLOCKS));
+            println!(
+                "{}",
+                style(format!("🔍 Scanning Transfer logs in blocks {} to {}...", from, latest)).dim()
+            );
+            HistoryIndexer::new(client.provider().clone())
+                .token_contracts(holder, from, latest)
+                .await?
+        }
+    };
+    // Older transfers may already be in the synced history
+    if let Ok(store) = TransactionStore::load(network, &holder) {
+        contracts.extend(store.transactions.iter().filter_map(|tx| tx.token_address));
+    }
+
+    let mut contracts: Vec<Address> = contracts.into_iter().collect();
+    contracts.sort();
+    let registry = TokenRegistry::load().map_err(|e| anyhow!("{}", e))?;
+    let registered = registry.tokens(network);
+
+    let holders = [holder];
+    let assets: Vec<Option<Address>> = contracts.iter().copied().map(Some).collect();
+    let balances = client.get_balances(&holders, &assets).await?;
+
+    let mut tokens = Vec::new();
+    for (i, address) in contracts.into_iter().enumerate() {
+        let (decimals, symbol) = match client.get_token_info(address).await {
+            Ok((decimals, symbol)) => (Some(decimals), Some(symbol)),
+            Err(_) => (None, None),
+        };
+        let registered_as = registered
+            .iter()
+            .find(|(_, info)| Address::from_str(&info.address).ok() == Some(address))
+            .map(|(symbol, _)| symbol.clone());
+        let mut token = DiscoveredToken {
+            address,
+            symbol,
+            decimals,
+            balance: balances.first().and_then(|row| row[i]),
+            registered: registered_as,
+            warnings: Vec::new(),
+        };
+        if token.registered.is_none() {
+            token.warnings = warnings(&token, &registry, network);
+        }
+        tokens.push(token);
+    }
+    Ok(tokens)
+}
+
+/// Signs of a spoofed or lure token: a symbol taken from a registered
+/// token or the native coin, or one that carries a link or look-alike
+/// characters
+fn warnings(token: &DiscoveredToken, registry: &TokenRegistry, network: &str) -> Vec<String> {
+    let Some(symbol) = &token.symbol else {
+        return vec!["does not report an ERC20 symbol and decimals".to_string()];
+    };
+    let mut warnings = Vec::new();
+    let normalized = symbol.trim().to_uppercase();
+    if let Some(info) = registry.tokens(network).get(&normalized) {
+        warnings.push(format!("uses the symbol of registered {} ({})", normalized, info.address));
+    }
+    if NATIVE_SYMBOLS.contains(&normalized.as_str()) {
+        warnings.push("imitates the native RBTC".to_string());
+    }
+    if !symbol.is_ascii() {
+        warnings.push("symbol has non-ASCII look-alike characters".to_string());
+    }
+    let lower = symbol.to_lowercase();
+    if ["://", "www.", ".com", ".io", ".org", ".xyz", ".net"].iter().any(|s| lower.contains(s))
+        || symbol.contains(char::is_whitespace)
+    {
+        warnings.push("symbol looks like a link or message".to_string());
+    }
+    warnings
+}
+
+pub fn print_discovered(tokens: &[DiscoveredToken]) {
+    if tokens.is_empty() {
+        println!("\nNo token transfers found");
+        return;
+    }
+    println!("\n{}", style(format!("Found {} token contract(s)", tokens.len())).bold());
+    for token in tokens {
+        let status = match &token.registered {
+            Some(symbol) => style(format!("registered as {}", symbol)).dim(),
+            None if token.balance.is_some_and(|b| b.is_zero()) => style("no balance".to_string()).dim(),
+            None if token.is_suspicious() => style("suspicious".to_string()).red(),
+            None => style("not registered".to_string()).green(),
+        };
+        println!("  {}  {}", token.label(), status);
+        for warning in &token.warnings {
+            println!("      {} {}", style("⚠️").yellow(), style(warning).yellow());
+        }
+    }
+}
+
+/// Registers the given tokens under their on-chain symbols
+pub fn add_discovered(network: &str, tokens: &[&DiscoveredToken]) -> Result<()> {
+    let mut registry = TokenRegistry::load().map_err(|e| anyhow!("{}", e))?;
+    for token in tokens {
+        let (Some(symbol), Some(decimals)) = (&token.symbol, token.decimals) else {
+            continue;
+        };
+        match registry.add_token(network, symbol, &token.address.to_checksum(None), decimals) {
+            Ok(()) => println!("{} {}", style("✅ Added").green(), style(symbol).bold()),
+            Err(e) => eprintln!("{} {}: {}", style("❌ Skipped").red(), symbol, e),
+        }
+    }
+    registry.save().map_err(|e| anyhow!("{}", e))
+}
+
+pub fn current_wallet_address() -> Result<Address> {
+    let wallet_file = constants::wallet_file_path();
+    if !wallet_file.exists() {
+        bail!("No wallets found. Create or import a wallet first.");
+    }
+    let wallet_data: WalletData = serde_json::from_str(&fs::read_to_string(&wallet_file)?)?;
+    wallet_data
+        .get_current_wallet()
+        .map(|wallet| wallet.address)
+        .ok_or_else(|| anyhow!("No default wallet selected. Use `wallet switch` first."))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::mock_node::{Fixtures, MockToken, TestEnv};
+    use std::collections::HashMap;
+
+    #[tokio::test]
+    async fn test_discovery_flags_spoofed_symbols() {
+        let holder = Address::with_last_byte(0xaa);
+        let sender = Address::with_last_byte(0xbb);
+        let (doc, fake_rif, spent) = (
+            Address::with_last_byte(0x71),
+            Address::with_last_byte(0x72),
+            Address::with_last_byte(0x73),
+        );
+        let mut fixtures = Fixtures::default();
+        for (token, symbol, balance) in [(doc, "DOC", 5u64), (fake_rif, "RIF", 1_000), (spent, "SOV", 0)] {
+            fixtures.tokens.insert(
+                token,
+                MockToken {
+                    symbol: symbol.to_string(),
+                    decimals: 18,
+                    balances: HashMap::from([(holder, U256::from(balance))]),
+                },
+            );
+            fixtures.logs.push(Fixtures::transfer_log(token, sender, holder, U256::from(5), 50));
+        }
+        // Someone else's transfer
+        fixtures.logs.push(Fixtures::transfer_log(doc, sender, sender, U256::from(1), 60));
+        let _env = TestEnv::start(fixtures).await;
+
+        let found = discover("testnet", holder, None).await.unwrap();
+        assert_eq!(found.len(), 3);
+        let by_address = |address| found.iter().find(|t| t.address == address).unwrap();
+        assert!(by_address(doc).can_add() && !by_address(doc).is_suspicious());
+        assert!(by_address(fake_rif).warnings[0].contains("registered RIF"));
+        assert!(!by_address(spent).can_add());
+
+        let selected: Vec<&DiscoveredToken> = found.iter().filter(|t| t.can_add() && !t.is_suspicious()).collect();
+        add_discovered("testnet", &selected).unwrap();
+        let registry = TokenRegistry::load().unwrap();
+        assert_eq!(registry.testnet["DOC"].address, doc.to_checksum(None));
+        assert_ne!(registry.testnet["RIF"].address, fake_rif.to_checksum(None));
+    }
+}
@@ -1,7 +1,14 @@
+use crate::commands::tokens::TokenRegistry;
+use crate::types::network::Network;
 use crate::types::transaction::{RskTransaction, TransactionStatus};
 use crate::types::wallet::WalletData;
 use crate::utils::alchemy::AlchemyClient;
 use crate::utils::api_validator::validate_api_key_format;
+use crate::utils::block_resolver::{BlockRange, BlockResolver, DateRange, DateZone, parse_date_range};
+use crate::utils::export::{ExportContext, ExportFormat, write_export};
+use crate::utils::indexer::HistoryIndexer;
+use crate::utils::rpc;
+use crate::utils::tx_store::TransactionStore;
 use crate::utils::{constants, table::TableBuilder};
 use crate::api::ApiProvider;
 use anyhow::Result;
@@ -12,6 +19,7 @@ use console::style;
 use alloy::primitives::Address;
 use std::fs;
 use std::str::FromStr;
+use std::time::{Duration, UNIX_EPOCH};
 
 /// Show the transaction history for an address or the current wallet
 #[derive(Parser, Clone)]
@@ -36,30 +44,42 @@ pub struct HistoryCommand {
     #[arg(short, long)]
     pub status: Option<String>,
 
-    /// Filter by token address
+    /// Filter by token (symbol from the registry, token address, or RBTC)
     #[arg(short, long)]
     pub token: Option<String>,
 
-    /// Start date for filtering (YYYY-MM-DD)
+    /// Start of the date range (YYYY-MM-DD, YYYY-MM, "last 30 days", "last month", ...)
     #[arg(short, long)]
     pub from: Option<String>,
 
-    /// End date for filtering (YYYY-MM-DD)
-    #[arg(short, long)]
+    /// End of the date range, inclusive (same formats as --from)
+    #[arg(long)]
     pub to: Option<String>,
 
+    /// Timezone for dates: local, UTC or an offset such as +02:00
+    #[arg(long, default_value = "local")]
+    pub timezone: String,
+
     /// Sort by field (timestamp, value, gas)
-    #[arg(short, long, default_value = "timestamp")]
+    #[arg(long, default_value = "timestamp")]
     pub sort_by: String,
 
     /// Sort order (asc/desc)
     #[arg(long, default_value = "desc")]
     pub sort_order: String,
 
-    /// Export transactions to CSV file
+    /// Export transactions to CSV file in the raw wei layout (see --export)
     #[arg(long)]
     pub export_csv: Option<String>,
 
+    /// Export the selected date range to a file (.csv, .jsonl, .ofx or .qif)
+    #[arg(long)]
+    pub export: Option<String>,
+
+    /// Export format; guessed from the --export file extension when omitted
+    #[arg(long, value_enum)]
+    pub export_format: Option<ExportFormat>,
+
     /// Show only incoming transactions
     #[arg(short, long)]
     pub incoming: bool,
@@ -72,9 +92,17 @@ pub struct HistoryCommand {
     #[arg(long)]
     pub api_key: Option<String>,
 
-    /// Network to query (mainnet | testnet). Defaults to mainnet.
+    /// Network to query (mainnet | testnet | regtest). Defaults to mainnet.
     #[arg(long, default_value = "mainnet")]
     pub network: String,
+
+    /// Block to start the first RPC scan from when Alchemy is not configured
+    #[arg(long)]
+    pub from_block: Option<u64>,
+
+    /// Serve history from the local store without syncing
+    #[arg(long)]
+    pub offline: bool,
 }
 
 // Custom Debug implementation that redacts the API key
@@ -89,13 +117,18 @@ impl std::fmt::Debug for HistoryCommand {
             .field("token", &self.token)
             .field("from", &self.from)
             .field("to", &self.to)
+            .field("timezone", &self.timezone)
             .field("sort_by", &self.sort_by)
             .field("sort_order", &self.sort_order)
             .field("export_csv", &self.export_csv)
+            .field("export", &self.export)
+            .field("export_format", &self.export_format)
             .field("incoming", &self.incoming)
             .field("outgoing", &self.outgoing)
             .field("api_key", &self.api_key.as_ref().map(|_| "<redacted>"))
             .field("network", &self.network)
+            .field("from_block", &self.from_block)
+            .field("offline", &self.offline)
             .finish()
     }
 }
@@ -108,11 +141,26 @@ impl HistoryCommand {
         let mut stored_api_key: Option<String> = None;
 
         // If export is requested, ensure we have a filename
-        if let Some(filename) = &self.export_csv {
-            if !filename.ends_with(".csv") {
-                return Err(anyhow::anyhow!("Export filename must end with .csv"));
-            }
+        if let Some(filename) = &self.export_csv
+            && !filename.ends_with(".csv") {
+            return Err(anyhow::anyhow!("Export filename must end with .csv"));
         }
+        let export = match (&self.export, &self.export_csv) {
+            (Some(filename), _) => {
+                let format = self
+                    .export_format
+                    .or_else(|| ExportFormat::from_path(filename))
+                    .ok_or_else(|| {
+                        anyhow::anyhow!(
+                            "Cannot tell the export format from '{}'; pass --export-format",
+                            filename
+                        )
+                    })?;
+                Some((filename, format))
+            }
+            (None, Some(filename)) => Some((filename, ExportFormat::Raw)),
+            (None, None) => None,
+        };
 
         // Try to load API key from wallet file
         if wallet_file.exists() {
@@ -143,12 +191,16 @@ impl HistoryCommand {
             .api_key
             .clone()
             .or(stored_api_key)
-            .or(std::env::var("ALCHEMY_API_KEY").ok())
-            .ok_or_else(|| anyhow::anyhow!("Alchemy API key missing – supply --api-key once"))?;
-
-        let is_testnet = self.network.to_lowercase() == "testnet";
-        if self.network.to_lowercase() != "mainnet" && !is_testnet {
-            anyhow::bail!("Invalid network: use 'mainnet' or 'testnet'");
+            .or(std::env::var("ALCHEMY_API_KEY").ok());
+
+        let network = self.network.to_lowercase();
+        let is_testnet = network == "testnet";
+        let app_config = crate::config::ConfigManager::new()?.load()?;
+        let is_custom = app_config.custom_network(&network).is_some();
+        if !is_custom && !matches!(network.as_str(), "mainnet" | "testnet" | "regtest") {
+            anyhow::bail!(
+                "Invalid network: use 'mainnet', 'testnet', 'regtest' or a configured custom network"
+            );
         }
 
         // 2. Get address to query
@@ -179,39 +231,118 @@ impl HistoryCommand {
                 .address
         };
 
-        // 3. Initialize Alchemy client and fetch transfers
-        let alchemy_client = AlchemyClient::new(final_api_key, is_testnet);
-        let response = alchemy_client
-            .get_asset_transfers(
-                &format!("{:#x}", address),
-                self.limit,
-                self.from.as_deref(),
-                self.to.as_deref(),
-            )
-            .await?;
-
-        // 4. Process transactions
-        let transfers = response["result"]["transfers"]
-            .as_array()
-            .ok_or_else(|| anyhow::anyhow!("Invalid response format from Alchemy"))?;
-
-        let mut txs = Vec::new();
-        for transfer in transfers {
-            // Convert Alchemy transfer to RskTransaction
-            let tx =
-                RskTransaction::from_alchemy_transfer(transfer, &address, &alchemy_client).await?;
-            txs.push(tx);
+        // 3. Sync the local store from Alchemy, or from the RPC node when no
+        //    Alchemy key is configured (regtest never has one). History is
+        //    always served from the store, so offline mode needs no network.
+        //    Online, the date range is resolved to block bounds first so the
+        //    RPC indexer scans the requested period.
+        let zone = DateZone::parse(&self.timezone)?;
+        let date_range = parse_date_range(self.from.as_deref(), self.to.as_deref(), zone)?;
+        let mut block_range = None;
+
+        let mut store = TransactionStore::load(&network, &address)?;
+        if self.offline {
+            if store.transactions.is_empty() {
+                anyhow::bail!(
+                    "No stored history for {:#x} on {}. Run history online once to sync it.",
+                    address,
+                    network
+                );
+            }
+            println!(
+                "{}",
+                style(format!(
+                    "📦 Offline: showing stored history (synced up to block {})",
+                    store
+                        .last_synced_block
+                        .map(|b| b.to_string())
+                        .unwrap_or_else(|| "-".into())
+                ))
+                .dim()
+            );
+        } else {
+            let provider = rpc::connect_to(&app_config, &network, final_api_key.as_deref())?;
+            let alchemy_client = final_api_key
+                .filter(|_| matches!(network.as_str(), "mainnet" | "testnet"))
+                .map(|api_key| {
+                    let alchemy_url = Network::from_str(&network)
+                        .and_then(|n| app_config.endpoint_override(&n))
+                        .and_then(|o| o.alchemy_url.clone());
+                    AlchemyClient::new(api_key, is_testnet).with_base_url(alchemy_url)
+                });
+
+            if date_range != DateRange::default() {
+                let range = BlockResolver::new(provider.clone(), &network)
+                    .resolve(&date_range)
+                    .await?;
+                println!(
+                    "{}",
+                    style(format!(
+                        "📅 Blocks {} to {}",
+                        range.from_block.map(|b| b.to_string()).unwrap_or_else(|| "genesis".into()),
+                        range.to_block.map(|b| b.to_string()).unwrap_or_else(|| "latest".into())
+                    ))
+                    .dim()
+                );
+                block_range = Some(range);
+            }
+
+            match &alchemy_client {
+                Some(client) => {
+                    store.sync_alchemy(client, &provider).await?;
+                }
+                None => {
+                    println!(
+                        "{}",
+                        style("ℹ️  Alchemy is not configured, indexing history from the RPC node").dim()
+                    );
+                    let start_block = self
+                        .from_block
+                        .or(block_range.and_then(|r| r.from_block));
+                    let mut indexer = HistoryIndexer::new(provider.clone());
+                    indexer.sync(&mut store, start_block).await?;
+                }
+            }
+            store.refresh_pending(&provider).await?;
         }
+        let mut txs = store.transactions.clone();
 
-        // 5. Apply filters
+        // 5. Apply filters; the date range first, since exports compute
+        //    running balances over every transfer in it
         if self.incoming && self.outgoing {
             anyhow::bail!("Cannot use both --incoming and --outgoing at the same time");
         }
-        if self.incoming {
-            txs.retain(|tx| tx.to == Some(address));
-        } else if self.outgoing {
-            txs.retain(|tx| tx.from == address);
+        let token_filter = self
+            .token
+            .as_deref()
+            .map(|token| resolve_token_filter(token, &network))
+            .transpose()?;
+        let status_filter = self.status.as_deref().map(str::to_lowercase);
+        let matches_filters = |tx: &RskTransaction| {
+            (!self.incoming || tx.to == Some(address))
+                && (!self.outgoing || tx.from == address)
+                && token_filter.is_none_or(|token| tx.token_address == token)
+                && status_filter.as_ref().is_none_or(|status| tx.status.to_string() == *status)
+        };
+        match block_range {
+            // Transfers without a block are pending and newer than any mined block
+            Some(range) => txs.retain(|tx| match tx.block_number {
+                Some(block) => range.contains(block.to::<u64>()),
+                None => range.to_block.is_none(),
+            }),
+            None => {
+                if let Some(start) = date_range.start {
+                    let start = UNIX_EPOCH + Duration::from_secs(start.timestamp().max(0) as u64);
+                    txs.retain(|tx| tx.timestamp >= start);
+                }
+                if let Some(end) = date_range.end {
+                    let end = UNIX_EPOCH + Duration::from_secs(end.timestamp().max(0) as u64);
+                    txs.retain(|tx| tx.timestamp < end);
+                }
+            }
         }
+        let range_txs = txs.clone();
+        txs.retain(|tx| matches_filters(tx));
 
         // 6. Handle empty result
         if txs.is_empty() {
@@ -228,41 +359,47 @@ impl HistoryCommand {
             _ => {}
         }
 
-        // 8. Export to CSV if requested
-        if let Some(filename) = &self.export_csv {
-            let mut wtr = csv::Writer::from_path(filename)?;
-
-            // Write header
-            wtr.write_record([
-                "Transaction Hash",
-                "Timestamp",
-                "From",
-                "To",
-                "Value (wei)",
-                "Token Address",
-                "Gas Price (wei)",
-                "Gas Used",
-                "Status",
-                "Block Number",
-            ])?;
-
-            // Write transactions
-            for tx in &txs {
-                let record = tx.to_csv_record();
-                wtr.write_record(&record)?;
-            }
+        // 8. Export if requested; exports cover the whole range, not only
+        //    the transactions shown in the terminal
+        if let Some((filename, format)) = export {
+            // Opening balances are only known when the store holds every
+            // earlier transfer
+            let earlier = before_range(&store.transactions, block_range, &date_range);
+            let context = ExportContext::load(address, &network, zone)?;
+            let opening = if store.first_synced_block == Some(0) {
+                context.statement(&earlier, &Default::default()).closing
+            } else {
+                if format != ExportFormat::Raw {
+                    println!(
+                        "{}",
+                        style("ℹ️  History is not synced from genesis; running balances start at zero").dim()
+                    );
+                }
+                Default::default()
+            };
 
-            wtr.flush()?;
+            let count = write_export(&context, format, filename, &range_txs, &opening, matches_filters)?;
             println!(
                 "\n{} Exported {} transactions to {}",
                 style("✓").green().bold(),
-                txs.len(),
+                count,
                 style(filename).cyan()
             );
+            for token in context.unknown_tokens(&txs) {
+                println!(
+                    "{}",
+                    style(format!(
+                        "⚠️  Token {:#x} is not in the registry; its amounts assume 18 decimals",
+                        token
+                    ))
+                    .yellow()
+                );
+            }
             return Ok(());
         }
 
-        // 9. Display results in terminal
+        // 9. Display results in terminal; exports above cover the whole range
+        txs.truncate(self.limit as usize);
         let mut table = TableBuilder::new();
         if self.detailed {
             table.add_header(&[
@@ -333,3 +470,135 @@ impl HistoryCommand {
         Ok(())
     }
 }
+
+/// Resolves a `--token` filter to the token address it matches, with
+/// `None` standing for native RBTC
+/// Stored transfers before the exported range. The filters do not apply,
+/// since a token transfer left out of the export still paid its fee in RBTC.
+fn before_range(txs: &[RskTransaction], block_range: Option<BlockRange>, date_range: &DateRange) -> Vec<RskTransaction> {
+    txs.iter()
+        .filter(|tx| match (block_range.and_then(|r| r.from_block), date_range.start) {
+            (Some(from), _) => tx.block_number.is_some_and(|b| b.to::<u64>() < from),
+            (None, Some(start)) => tx.timestamp < UNIX_EPOCH + Duration::from_secs(start.timestamp().max(0) as u64),
+            (None, None) => false,
+        })
+        .cloned()
+        .collect()
+}
+
+fn resolve_token_filter(token: &str, network: &str) -> Result<Option<Address>> {
+    if token.eq_ignore_ascii_case("rbtc") {
+        return Ok(None);
+    }
+    if let Ok(address) = Address::from_str(token) {
+        return Ok(Some(address));
+    }
+    let registry = TokenRegistry::load()
+        .map_err(|e| anyhow::anyhow!("Failed to load token registry: {}", e))?;
+    let (_, info) = registry
+        .list_tokens(Some(network))
+        .into_iter()
+        .find(|(symbol, _)| symbol.eq_ignore_ascii_case(token))
+        .ok_or_else(|| anyhow::anyhow!("Unknown token '{}' on {}", token, network))?;
+    Address::from_str(&info.address)
+        .map(Some)
+        .map_err(|_| anyhow::anyhow!("Invalid address for token '{}' in registry", token))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::mock_node::{Fixtures, TestEnv};
+    use alloy::primitives::B256;
+    use clap::Parser;
+
+    #[tokio::test]
+    async fn test_alchemy_sync_fills_the_local_store() {
+        let wallet = Address::with_last_byte(0xaa);
+        let other = Address::with_last_byte(0xbb);
+        let (sent, received) = (B256::with_last_byte(1), B256::with_last_byte(2));
+        let mut fixtures = Fixtures::default();
+        for (hash, from, to, block) in [(sent, wallet, other, 10u64), (received, other, wallet, 20)] {
+            fixtures.asset_transfers.push(serde_json::json!({
+                "uniqueId": format!("{:#x}:external", hash),
+                "hash": hash,
+                "from": from,
+                "to": to,
+                "category": "external",
+                "blockNum": format!("0x{:x}", block),
+                "rawContract": { "value": "0xde0b6b3a7640000", "address": null },
+                "metadata": { "blockTimestamp": "2025-01-01T00:00:00Z" }
+            }));
+            fixtures.receipts.insert(hash, Fixtures::receipt(hash, from, to, block));
+        }
+        let env = TestEnv::start(fixtures).await;
+
+        let command = HistoryCommand::try_parse_from([
+            "history",
+            "--network",
+            "testnet",
+            "--address",
+            &format!("{:#x}", wallet),
+            "--api-key",
+            &"k".repeat(32),
+        ])
+        .unwrap();
+        command.execute().await.unwrap();
+
+        let store = TransactionStore::load("testnet", &wallet).unwrap();
+        assert_eq!(store.transactions.len(), 2);
+        assert!(store.transactions.iter().all(|tx| tx.status == TransactionStatus::Success));
+        assert_eq!(store.last_synced_block, Some(20));
+        assert!(env.node.methods().contains(&"alchemy_getAssetTransfers".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_opening_balance_counts_fees_of_filtered_out_transfers() {
+        use alloy::primitives::{I256, U64, U256};
+        use std::time::SystemTime;
+
+        let _env = TestEnv::start(Fixtures::default()).await;
+        let me = Address::with_last_byte(0xaa);
+        let other = Address::with_last_byte(0xbb);
+        let token = Address::with_last_byte(0x70);
+        let transfer = |block: u8, from: Address, to: Address, value: u64, token: Option<Address>| RskTransaction {
+            hash: B256::with_last_byte(block),
+            from,
+            to: Some(to),
+            value: U256::from(value),
+            gas_price: U256::from(10),
+            gas: U256::from(21_000),
+            nonce: U256::ZERO,
+            input: None,
+            block_number: Some(U64::from(block)),
+            transaction_index: None,
+            timestamp: SystemTime::UNIX_EPOCH + Duration::from_secs(block as u64 * 60),
+            status: TransactionStatus::Success,
+            token_address: token,
+            confirms: None,
+            cumulative_gas_used: None,
+            logs: None,
+        };
+        let stored = vec![
+            transfer(1, other, me, 1_000_000, None),
+            // An earlier token transfer, left out by `--token RBTC`
+            transfer(2, me, other, 5, Some(token)),
+            transfer(3, me, other, 100, None),
+        ];
+        let range = BlockRange {
+            from_block: Some(3),
+            to_block: None,
+        };
+
+        let earlier = before_range(&stored, Some(range), &DateRange::default());
+        assert_eq!(earlier.len(), 2);
+        let context = ExportContext::load(me, "testnet", DateZone::Local).unwrap();
+        let opening = context.statement(&earlier, &Default::default()).closing;
+        let fee = 210_000;
+        assert_eq!(opening[&None], I256::try_from(1_000_000 - fee).unwrap());
+
+        let statement = context.statement_where(&stored[2..], &opening, |tx| tx.token_address.is_none());
+        assert_eq!(statement.lines.len(), 1);
+        assert_eq!(statement.lines[0].balance, I256::try_from(1_000_000 - 2 * fee - 100).unwrap());
+    }
+}
@@ -1,9 +1,13 @@
 pub mod api;
 pub mod balance;
 pub mod contacts;
+pub mod cost_basis;
+pub mod discover;
 pub mod history;
+pub mod portfolio;
 pub mod root;
 pub mod tokens;
+pub mod trace;
 pub mod transfer;
 pub mod tx;
 pub mod wallet;
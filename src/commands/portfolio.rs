@@ -0,0 +1,230 @@
+use crate::commands::tokens::TokenRegistry;
+use crate::config::ConfigManager;
+use crate::types::wallet::WalletData;
+use crate::utils::constants;
+use crate::utils::export::format_amount;
+use crate::utils::helper::Helper;
+use crate::utils::prices::{JsonPriceSource, PriceSource};
+use crate::utils::table::TableBuilder;
+use alloy::primitives::{Address, I256, U256, utils::format_units};
+use anyhow::{Result, anyhow};
+use chrono::Utc;
+use clap::Parser;
+use console::style;
+use std::fs;
+use std::str::FromStr;
+
+/// An asset shown in the portfolio; `token` is `None` for RBTC
+#[derive(Debug, Clone)]
+pub struct PortfolioAsset {
+    pub symbol: String,
+    pub token: Option<Address>,
+    pub decimals: u8,
+}
+
+/// Balances of every wallet in every registry token, with optional fiat values
+#[derive(Debug, Parser, Clone)]
+pub struct PortfolioCommand {
+    /// Network to check (defaults to the configured network)
+    #[arg(long)]
+    pub network: Option<String>,
+
+    /// JSON price file for fiat values (defaults to prices.json in the data directory)
+    #[arg(long)]
+    pub prices: Option<String>,
+}
+
+impl PortfolioCommand {
+    pub async fn execute(&self) -> Result<()> {
+        let config = ConfigManager::new()?.load()?;
+        let network = self
+            .network
+            .clone()
+            .unwrap_or_else(|| config.network_name())
+            .to_lowercase();
+
+        let wallet_file = constants::wallet_file_path();
+        if !wallet_file.exists() {
+            return Err(anyhow!("No wallets found. Please create or import a wallet first."));
+        }
+        let wallet_data = serde_json::from_str::<WalletData>(&fs::read_to_string(&wallet_file)?)?;
+        let mut wallets: Vec<(String, Address)> = wallet_data
+            .wallets
+            .values()
+            .map(|w| (w.name.clone(), w.address))
+            .collect();
+        if wallets.is_empty() {
+            return Err(anyhow!("No wallets available."));
+        }
+        wallets.sort();
+
+        let mut assets = portfolio_assets(&network)?;
+        if let Some(custom) = config.custom_network(&network) {
+            assets[0].symbol = custom.native_symbol.clone();
+        }
+        let prices = self.load_prices()?;
+
+        let (_, eth_client) = Helper::init_eth_client(&network).await?;
+        println!(
+            "{}",
+            style(format!(
+                "⏳ Fetching {} balances for {} wallets...",
+                assets.len(),
+                wallets.len()
+            ))
+            .dim()
+        );
+        // One multicall covers every wallet and token where Multicall3 exists
+        let holders: Vec<Address> = wallets.iter().map(|(_, address)| *address).collect();
+        let tokens: Vec<Option<Address>> = assets.iter().map(|a| a.token).collect();
+        let balances = eth_client.get_balances(&holders, &tokens).await?;
+
+        self.display(&network, &wallets, &assets, &balances, prices.as_ref());
+        Ok(())
+    }
+
+    fn load_prices(&self) -> Result<Option<JsonPriceSource>> {
+        match &self.prices {
+            Some(path) => JsonPriceSource::load(path).map(Some),
+            None => {
+                let path = JsonPriceSource::default_path();
+                if path.exists() {
+                    JsonPriceSource::load(path).map(Some)
+                } else {
+                    Ok(None)
+                }
+            }
+        }
+    }
+
+    fn display(
+        &self,
+        network: &str,
+        wallets: &[(String, Address)],
+        assets: &[PortfolioAsset],
+        balances: &[Vec<Option<U256>>],
+        prices: Option<&JsonPriceSource>,
+    ) {
+        println!(
+            "\n{}",
+            style(format!("📊 Portfolio ({})", network)).bold().underlined()
+        );
+
+        let now = Utc::now();
+        let value_of = |asset: &PortfolioAsset, amount: U256| -> Option<f64> {
+            let price = prices?.price_at(&asset.symbol, now)?;
+            let units: f64 = format_units(amount, asset.decimals).ok()?.parse().ok()?;
+            Some(units * price)
+        };
+        let value_header = prices.map(|p| format!("Value ({})", p.currency()));
+
+        let mut header: Vec<&str> = vec!["Wallet", "Address"];
+        header.extend(assets.iter().map(|a| a.symbol.as_str()));
+        if let Some(value_header) = &value_header {
+            header.push(value_header);
+        }
+
+        let mut table = TableBuilder::new();
+        table.add_header(&header);
+
+        let mut totals = vec![U256::ZERO; assets.len()];
+        let mut grand_value = 0.0;
+        let mut failed = Vec::new();
+
+        for ((name, address), amounts) in wallets.iter().zip(balances) {
+            let mut row = vec![name.clone(), Helper::format_address(address)];
+            let mut wallet_value = 0.0;
+            for (index, (asset, amount)) in assets.iter().zip(amounts).enumerate() {
+                match amount {
+                    Some(amount) => {
+                        totals[index] += *amount;
+                        wallet_value += value_of(asset, *amount).unwrap_or_default();
+                        row.push(format_amount(I256::from_raw(*amount), asset.decimals));
+                    }
+                    None => {
+                        failed.push((name.clone(), asset.symbol.clone()));
+                        row.push("error".to_string());
+                    }
+                }
+            }
+            if prices.is_some() {
+                grand_value += wallet_value;
+                row.push(format!("{:.2}", wallet_value));
+            }
+            table.add_row(&row.iter().map(String::as_str).collect::<Vec<_>>());
+        }
+
+        let mut total_row = vec!["Total".to_string(), String::new()];
+        total_row.extend(
+            assets
+                .iter()
+                .zip(&totals)
+                .map(|(asset, total)| format_amount(I256::from_raw(*total), asset.decimals)),
+        );
+        if prices.is_some() {
+            total_row.push(format!("{:.2}", grand_value));
+        }
+        table.add_row(&total_row.iter().map(String::as_str).collect::<Vec<_>>());
+        table.print();
+
+        if let Some(prices) = prices {
+            for (asset, total) in assets.iter().zip(&totals) {
+                if let Some(value) = value_of(asset, *total) {
+                    println!("  {}: {:.2} {}", style(&asset.symbol).bold(), value, prices.currency());
+                } else if !total.is_zero() {
+                    println!(
+                        "  {}: {}",
+                        style(&asset.symbol).bold(),
+                        style("no price in the price file").yellow()
+                    );
+                }
+            }
+            println!(
+                "  {} {:.2} {}",
+                style("Grand total:").bold(),
+                grand_value,
+                prices.currency()
+            );
+        } else {
+            println!(
+                "{}",
+                style(format!(
+                    "ℹ️  Add {} to show fiat values",
+                    JsonPriceSource::default_path().display()
+                ))
+                .dim()
+            );
+        }
+
+        for (name, symbol) in failed {
+            println!(
+                "{}",
+                style(format!("⚠️  Could not read the {} balance of {}; totals leave it out", symbol, name)).yellow()
+            );
+        }
+    }
+}
+
+/// The native asset (as RBTC) followed by the registry tokens of the network
+pub fn portfolio_assets(network: &str) -> Result<Vec<PortfolioAsset>> {
+    let mut assets = vec![PortfolioAsset {
+        symbol: "RBTC".to_string(),
+        token: None,
+        decimals: 18,
+    }];
+    let registry = TokenRegistry::load().map_err(|e| anyhow!("{}", e))?;
+    let mut tokens: Vec<_> = registry
+        .list_tokens(Some(network))
+        .into_iter()
+        .filter_map(|(symbol, info)| {
+            Address::from_str(&info.address).ok().map(|token| PortfolioAsset {
+                symbol,
+                token: Some(token),
+                decimals: info.decimals,
+            })
+        })
+        .collect();
+    tokens.sort_by(|a, b| a.symbol.cmp(&b.symbol));
+    assets.extend(tokens);
+    Ok(assets)
+}
@@ -1,6 +1,10 @@
 use crate::commands::api::SetApiKeyCommand;
 use crate::commands::contacts::ContactsCommand;
-use crate::commands::tokens::{TokenAddCommand, TokenListCommand, TokenRemoveCommand};
+use crate::commands::discover::TokenDiscoverCommand;
+use crate::commands::trace::TraceCommand;
+use crate::commands::tokens::{
+    TokenAddCommand, TokenImportCommand, TokenListCommand, TokenRemoveCommand, TokenUpdateCommand,
+};
 use crate::commands::wallet::WalletCommand;
 use clap::Parser;
 
@@ -67,4 +71,16 @@ pub enum Commands {
 
     /// List tokens in the registry
     TokenList(TokenListCommand),
+
+    /// Import a token list (tokenlists.org format) from a URL or file
+    TokenImport(TokenImportCommand),
+
+    /// Update imported token lists to their latest versions
+    TokenUpdate(TokenUpdateCommand),
+
+    /// Find tokens a wallet has received or sent and offer to register them
+    TokenDiscover(TokenDiscoverCommand),
+
+    /// Show the internal call tree of a transaction
+    Trace(TraceCommand),
 }
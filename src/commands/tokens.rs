@@ -1,23 +1,38 @@
+use alloy::primitives::Address;
+use anyhow::{Result, anyhow, bail};
 use clap::Parser;
+use console::style;
 use serde::{Deserialize, Serialize};
-use serde_json::json;
-use std::collections::HashMap;
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap};
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use crate::utils::constants;
+use crate::utils::eth::EthClient;
+use crate::utils::helper::Helper;
+use crate::utils::paths::AppPaths;
+use crate::utils::rpc::ChainRpc;
+use crate::utils::token_list::{self, ListDiff, ListSubscription};
+
+/// Tokens shipped with the app; always listed and never written to the
+/// user's registry file
+const BUNDLED_TOKENS: &str = include_str!("../../tokens.json");
 
 #[derive(Parser, Debug)]
 pub struct TokenAddCommand {
-    /// Token symbol (e.g., RIF, USD)
+    /// Symbol to register the token under; defaults to the one the contract reports
     #[arg(short, long)]
-    pub symbol: String,
+    pub symbol: Option<String>,
 
     /// Token contract address
     #[arg(short, long)]
     pub address: String,
 
-    /// Number of decimal places for the token
-    #[arg(short, long, default_value_t = 18)]
-    pub decimals: u8,
+    /// Expected decimal places; the token is rejected if the contract reports others
+    #[arg(short, long)]
+    pub decimals: Option<u8>,
 
     /// Network to add token to (mainnet/testnet)
     #[arg(short, long, default_value = "mainnet")]
@@ -42,43 +57,135 @@ pub struct TokenListCommand {
     pub network: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Parser, Debug)]
+pub struct TokenImportCommand {
+    /// URL or file of a token list in the tokenlists.org format
+    #[arg(short, long)]
+    pub source: String,
+
+    /// Apply the changes without asking
+    #[arg(short, long)]
+    pub yes: bool,
+}
+
+#[derive(Parser, Debug)]
+pub struct TokenUpdateCommand {
+    /// Apply the changes without asking
+    #[arg(short, long)]
+    pub yes: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
 pub struct TokenInfo {
     pub address: String,
     pub decimals: u8,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default, rename = "logoURI", skip_serializing_if = "Option::is_none")]
+    pub logo_uri: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// Token list the entry was imported from, see [`TokenRegistry::lists`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub list: Option<String>,
+}
+
+impl TokenInfo {
+    fn same_token(&self, other: &TokenInfo) -> bool {
+        self.address.eq_ignore_ascii_case(&other.address) && self.decimals == other.decimals
+    }
+
+    /// Whether this entry has a name, logo, tags or list that `other` lacks
+    fn adds_metadata_to(&self, other: &TokenInfo) -> bool {
+        (self.name.is_some() && self.name != other.name)
+            || (self.logo_uri.is_some() && self.logo_uri != other.logo_uri)
+            || (!self.tags.is_empty() && self.tags != other.tags)
+            || (self.list.is_some() && self.list != other.list)
+    }
 }
 
 #[derive(Debug, Default, Serialize, Deserialize)]
 pub struct TokenRegistry {
     pub mainnet: HashMap<String, TokenInfo>,
     pub testnet: HashMap<String, TokenInfo>,
+    /// Imported token lists by URL or file path
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub lists: BTreeMap<String, ListSubscription>,
 }
 
 impl TokenRegistry {
+    /// Registry file of the active profile
+    pub fn path() -> PathBuf {
+        constants::data_dir().join("tokens.json")
+    }
+
+    /// Bundled defaults merged with the user's entries
     pub fn load() -> Result<Self, Box<dyn std::error::Error>> {
-        let path = "tokens.json";
-        if !Path::new(path).exists() {
-            // Create a new empty registry if file doesn't exist
-            let registry = TokenRegistry {
-                mainnet: HashMap::new(),
-                testnet: HashMap::new(),
-            };
-            let json = serde_json::to_string_pretty(&json!(&registry))?;
-            fs::write(path, json)?;
-            return Ok(registry);
+        let path = Self::path();
+        // Registries used to be kept in the working directory; the default
+        // profile adopts one found there the first time
+        if !path.exists() && AppPaths::current().profile().is_none() && Path::new("tokens.json").exists() {
+            fs::create_dir_all(constants::data_dir())?;
+            fs::copy("tokens.json", &path)?;
         }
 
-        let content = fs::read_to_string(path)?;
-        let registry: TokenRegistry = serde_json::from_str(&content)?;
+        let mut registry = Self::bundled();
+        if path.exists() {
+            let content = fs::read_to_string(path)?;
+            let user: TokenRegistry = serde_json::from_str(&content)?;
+            registry.mainnet.extend(user.mainnet);
+            registry.testnet.extend(user.testnet);
+            registry.lists = user.lists;
+        }
         Ok(registry)
     }
 
+    /// Tokens shipped with the app
+    pub fn bundled() -> Self {
+        serde_json::from_str(BUNDLED_TOKENS).expect("bundled tokens.json is valid")
+    }
+
+    /// Writes the user's entries; bundled defaults are left out unless the
+    /// user's copy carries metadata of its own, such as an imported list
     pub fn save(&self) -> Result<(), Box<dyn std::error::Error>> {
-        let json = serde_json::to_string_pretty(&self)?;
-        fs::write("tokens.json", json)?;
+        let bundled = Self::bundled();
+        let user_entries = |tokens: &HashMap<String, TokenInfo>, defaults: &HashMap<String, TokenInfo>| {
+            tokens
+                .iter()
+                .filter(|(symbol, info)| {
+                    !defaults
+                        .get(*symbol)
+                        .is_some_and(|d| d.same_token(info) && !info.adds_metadata_to(d))
+                })
+                .map(|(symbol, info)| (symbol.clone(), info.clone()))
+                .collect()
+        };
+        let user = TokenRegistry {
+            mainnet: user_entries(&self.mainnet, &bundled.mainnet),
+            testnet: user_entries(&self.testnet, &bundled.testnet),
+            lists: self.lists.clone(),
+        };
+        fs::create_dir_all(constants::data_dir())?;
+        let json = serde_json::to_string_pretty(&user)?;
+        fs::write(Self::path(), json)?;
         Ok(())
     }
 
+    /// Tokens of `network`; anything but "mainnet" means testnet
+    pub fn tokens(&self, network: &str) -> &HashMap<String, TokenInfo> {
+        match network {
+            "mainnet" => &self.mainnet,
+            _ => &self.testnet,
+        }
+    }
+
+    pub fn tokens_mut(&mut self, network: &str) -> &mut HashMap<String, TokenInfo> {
+        match network {
+            "mainnet" => &mut self.mainnet,
+            _ => &mut self.testnet,
+        }
+    }
+
     pub fn add_token(
         &mut self,
         network: &str,
@@ -109,6 +216,7 @@ impl TokenRegistry {
         let token = TokenInfo {
             address: address.to_string(),
             decimals,
+            ..Default::default()
         };
 
         match network_lower.as_str() {
@@ -123,71 +231,172 @@ impl TokenRegistry {
         Ok(())
     }
 
-    pub fn remove_token(&mut self, network: &str, symbol: &str) -> Result<(), &'static str> {
-        match network.to_lowercase().as_str() {
-            "mainnet" => {
-                self.mainnet.remove(&symbol.to_uppercase());
+    pub fn remove_token(&mut self, network: &str, symbol: &str) -> Result<(), String> {
+        let symbol_upper = symbol.to_uppercase();
+        let bundled = Self::bundled();
+        let (tokens, defaults) = match network.to_lowercase().as_str() {
+            "mainnet" => (&mut self.mainnet, bundled.mainnet),
+            "testnet" => (&mut self.testnet, bundled.testnet),
+            _ => return Err("Invalid network. Use 'mainnet' or 'testnet'.".to_string()),
+        };
+        // Removing a user entry that shadows a bundled symbol restores the default
+        match defaults.get(&symbol_upper) {
+            Some(default) if tokens.get(&symbol_upper).is_none_or(|t| t.same_token(default)) => {
+                return Err(format!("{} is a bundled token and cannot be removed", symbol_upper));
             }
-            "testnet" => {
-                self.testnet.remove(&symbol.to_uppercase());
+            Some(default) => {
+                tokens.insert(symbol_upper, default.clone());
+            }
+            None => {
+                tokens.remove(&symbol_upper);
             }
-            _ => return Err("Invalid network. Use 'mainnet' or 'testnet'."),
         }
         Ok(())
     }
 
     pub fn list_tokens(&self, network: Option<&str>) -> Vec<(String, TokenInfo)> {
-        let mut result = Vec::new();
+        match network.map(str::to_lowercase).as_deref() {
+            Some(net @ ("mainnet" | "testnet")) => self
+                .tokens(net)
+                .iter()
+                .map(|(symbol, info)| (symbol.clone(), info.clone()))
+                .collect(),
+            Some(_) => Vec::new(),
+            None => ["mainnet", "testnet"]
+                .into_iter()
+                .flat_map(|net| {
+                    self.tokens(net)
+                        .iter()
+                        .map(move |(symbol, info)| (format!("{}/{}", net, symbol), info.clone()))
+                })
+                .collect(),
+        }
+    }
+}
 
-        match network {
-            Some(net) => match net.to_lowercase().as_str() {
-                "mainnet" => {
-                    for (symbol, info) in &self.mainnet {
-                        result.push((
-                            symbol.clone(),
-                            TokenInfo {
-                                address: info.address.clone(),
-                                decimals: info.decimals,
-                            },
-                        ));
-                    }
-                }
-                "testnet" => {
-                    for (symbol, info) in &self.testnet {
-                        result.push((
-                            symbol.clone(),
-                            TokenInfo {
-                                address: info.address.clone(),
-                                decimals: info.decimals,
-                            },
-                        ));
-                    }
-                }
-                _ => {}
-            },
-            None => {
-                for (symbol, info) in &self.mainnet {
-                    result.push((
-                        format!("mainnet/{}", symbol),
-                        TokenInfo {
-                            address: info.address.clone(),
-                            decimals: info.decimals,
-                        },
-                    ));
-                }
-                for (symbol, info) in &self.testnet {
-                    result.push((
-                        format!("testnet/{}", symbol),
-                        TokenInfo {
-                            address: info.address.clone(),
-                            decimals: info.decimals,
-                        },
-                    ));
-                }
-            }
+impl TokenAddCommand {
+    pub async fn execute(&self) -> Result<()> {
+        let (onchain_symbol, decimals) = verify_token(&self.network, &self.address, self.decimals).await?;
+        let symbol = self.symbol.clone().unwrap_or(onchain_symbol);
+        add_token(&self.network, &symbol, &self.address, decimals).map_err(|e| anyhow!("{}", e))
+    }
+}
+
+/// Reads the symbol and decimals a token contract reports, failing for
+/// addresses that are not ERC20 contracts
+pub async fn fetch_token_metadata(client: &EthClient, address: Address) -> Result<(String, u8)> {
+    if client.code(address).await?.is_empty() {
+        bail!("{:#x} is not a contract", address);
+    }
+    let (decimals, symbol) = client
+        .get_token_info(address)
+        .await
+        .map_err(|_| anyhow!("{:#x} does not implement ERC20 symbol() and decimals()", address))?;
+    Ok((symbol, decimals))
+}
+
+/// Looks up a token on `network` before it is registered. `decimals`, when
+/// given, must match what the contract reports.
+pub async fn verify_token(network: &str, address: &str, decimals: Option<u8>) -> Result<(String, u8)> {
+    if !matches!(network.to_lowercase().as_str(), "mainnet" | "testnet") {
+        bail!("Invalid network. Use 'mainnet' or 'testnet'.");
+    }
+    let token = Address::from_str(address).map_err(|_| anyhow!("Invalid token address: {}", address))?;
+    let (_, client) = Helper::init_eth_client(&network.to_lowercase()).await?;
+    let (symbol, onchain_decimals) = fetch_token_metadata(&client, token).await?;
+    if let Some(expected) = decimals
+        && expected != onchain_decimals
+    {
+        bail!(
+            "{} reports {} decimals, not {}",
+            symbol,
+            onchain_decimals,
+            expected
+        );
+    }
+    Ok((symbol, onchain_decimals))
+}
+
+impl TokenImportCommand {
+    pub async fn execute(&self) -> Result<()> {
+        import_list(&self.source, self.yes).await
+    }
+}
+
+impl TokenUpdateCommand {
+    pub async fn execute(&self) -> Result<()> {
+        update_lists(self.yes).await
+    }
+}
+
+/// Imports a token list, or updates it if it was imported before. The
+/// diff against the registry is shown and confirmed before anything is
+/// written; lists that are not newer than the imported version are left alone.
+pub async fn import_list(source: &str, assume_yes: bool) -> Result<()> {
+    let source = token_list::source_key(source)?;
+    let list = token_list::fetch(&source).await?;
+    let mut registry = TokenRegistry::load().map_err(|e| anyhow!("{}", e))?;
+
+    println!(
+        "\n{} {}",
+        style(format!("{} v{}", list.name, list.version)).bold(),
+        style(&source).dim()
+    );
+    match token_list::compare_versions(&registry, &list, &source) {
+        Some(Ordering::Equal) => {
+            println!("Already up to date");
+            return Ok(());
+        }
+        Some(Ordering::Less) => {
+            println!(
+                "{}",
+                style(format!(
+                    "⚠️  v{} is older than the imported v{}; not applied",
+                    list.version, registry.lists[&source].version
+                ))
+                .yellow()
+            );
+            return Ok(());
         }
-        result
+        Some(Ordering::Greater) if list.version.major > registry.lists[&source].version.major => {
+            println!(
+                "{}",
+                style("⚠️  New major version: tokens may have been removed or changed").yellow()
+            );
+        }
+        _ => {}
+    }
+
+    let diff = ListDiff::new(&registry, &list, &source);
+    diff.print();
+    if !diff.is_empty()
+        && !assume_yes
+        && !inquire::Confirm::new("Apply these changes?")
+            .with_default(true)
+            .prompt()?
+    {
+        println!("No changes made");
+        return Ok(());
     }
+    diff.apply(&mut registry, &list, &source);
+    registry.save().map_err(|e| anyhow!("{}", e))?;
+    println!("{}", style(format!("✅ {} v{} applied", list.name, list.version)).green());
+    Ok(())
+}
+
+/// Checks every imported list for a newer version
+pub async fn update_lists(assume_yes: bool) -> Result<()> {
+    let registry = TokenRegistry::load().map_err(|e| anyhow!("{}", e))?;
+    if registry.lists.is_empty() {
+        println!("No token lists imported yet");
+        return Ok(());
+    }
+    for source in registry.lists.keys() {
+        if let Err(e) = import_list(source, assume_yes).await {
+            eprintln!("{} {}", style("❌ Failed to update").red(), e);
+        }
+    }
+    Ok(())
 }
 
 pub fn add_token(
@@ -228,3 +437,68 @@ pub fn list_tokens(
 
     Ok(tokens)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::mock_node::{Fixtures, MockToken, TestEnv};
+
+    #[tokio::test]
+    async fn test_tokens_are_verified_and_kept_apart_from_defaults() {
+        let token = Address::with_last_byte(0x70);
+        let mut fixtures = Fixtures::default();
+        fixtures.tokens.insert(
+            token,
+            MockToken {
+                symbol: "DOC".to_string(),
+                decimals: 18,
+                balances: HashMap::new(),
+            },
+        );
+        let _env = TestEnv::start(fixtures).await;
+        let add = |address: Address, decimals: Option<u8>| TokenAddCommand {
+            symbol: None,
+            address: format!("{:#x}", address),
+            decimals,
+            network: "testnet".to_string(),
+        };
+
+        let err = add(Address::with_last_byte(0x71), None).execute().await.unwrap_err();
+        assert!(err.to_string().contains("not a contract"));
+        let err = add(token, Some(8)).execute().await.unwrap_err();
+        assert!(err.to_string().contains("reports 18 decimals"));
+
+        add(token, Some(18)).execute().await.unwrap();
+        let registry = TokenRegistry::load().unwrap();
+        assert_eq!(registry.testnet["DOC"].decimals, 18);
+        assert!(registry.testnet.contains_key("RIF"));
+
+        // Only the user's entry is written; defaults stay read-only
+        let saved: TokenRegistry =
+            serde_json::from_str(&fs::read_to_string(TokenRegistry::path()).unwrap()).unwrap();
+        assert_eq!(saved.testnet.keys().collect::<Vec<_>>(), vec!["DOC"]);
+        assert!(saved.mainnet.is_empty());
+        assert!(remove_token("testnet", "RIF").is_err());
+
+        // A user entry shadowing a bundled symbol can be removed again
+        let bundled_rif = TokenRegistry::bundled().testnet["RIF"].address.clone();
+        let mut registry = TokenRegistry::load().unwrap();
+        registry.testnet.get_mut("RIF").unwrap().address = format!("{:#x}", Address::with_last_byte(0x72));
+        registry.save().unwrap();
+        assert_ne!(TokenRegistry::load().unwrap().testnet["RIF"].address, bundled_rif);
+        remove_token("testnet", "RIF").unwrap();
+        assert_eq!(TokenRegistry::load().unwrap().testnet["RIF"].address, bundled_rif);
+        assert!(remove_token("testnet", "RIF").is_err());
+
+        // A list entry for a bundled token keeps its list and tags
+        let mut registry = TokenRegistry::load().unwrap();
+        let rif = registry.testnet.get_mut("RIF").unwrap();
+        rif.list = Some("https://example.com/tokens.json".to_string());
+        rif.tags = vec!["governance".to_string()];
+        registry.save().unwrap();
+        let rif = TokenRegistry::load().unwrap().testnet["RIF"].clone();
+        assert_eq!(rif.address, bundled_rif);
+        assert_eq!(rif.list.as_deref(), Some("https://example.com/tokens.json"));
+        assert_eq!(rif.tags, vec!["governance".to_string()]);
+    }
+}
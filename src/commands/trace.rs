@@ -0,0 +1,276 @@
+use alloy::primitives::{U256, keccak256, utils::format_units};
+use anyhow::anyhow;
+use clap::Parser;
+use console::style;
+use serde::Deserialize;
+
+use crate::config::ConfigManager;
+use crate::utils::rpc::{self, ChainRpc};
+use crate::utils::rpc_pool::RpcPool;
+
+/// Function signatures whose selectors are decoded in the call tree
+const KNOWN_SIGNATURES: &[&str] = &[
+    "transfer(address,uint256)",
+    "transferFrom(address,address,uint256)",
+    "approve(address,uint256)",
+    "allowance(address,address)",
+    "balanceOf(address)",
+    "totalSupply()",
+    "decimals()",
+    "symbol()",
+    "name()",
+    "transferAndCall(address,uint256,bytes)",
+    "safeTransferFrom(address,address,uint256)",
+    "safeTransferFrom(address,address,uint256,bytes)",
+    "deposit()",
+    "withdraw(uint256)",
+    "multicall(bytes[])",
+    "aggregate((address,bytes)[])",
+    "aggregate3((address,bool,bytes)[])",
+    "submitTransaction(address,uint256,bytes)",
+    "confirmTransaction(uint256)",
+    "executeTransaction(uint256)",
+];
+
+/// A single frame returned by the `callTracer`
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CallFrame {
+    #[serde(rename = "type")]
+    pub call_type: String,
+    pub from: String,
+    #[serde(default)]
+    pub to: Option<String>,
+    #[serde(default)]
+    pub value: Option<String>,
+    #[serde(default)]
+    pub gas: Option<String>,
+    #[serde(default)]
+    pub gas_used: Option<String>,
+    #[serde(default)]
+    pub input: Option<String>,
+    #[serde(default)]
+    pub error: Option<String>,
+    #[serde(default)]
+    pub revert_reason: Option<String>,
+    #[serde(default)]
+    pub calls: Vec<CallFrame>,
+}
+
+impl CallFrame {
+    /// Value carried by the call in wei
+    pub fn value_wei(&self) -> U256 {
+        self.value
+            .as_deref()
+            .and_then(|v| U256::from_str_radix(v.trim_start_matches("0x"), 16).ok())
+            .unwrap_or_default()
+    }
+
+    /// Gas used by the call, if reported
+    pub fn gas_used(&self) -> Option<u64> {
+        self.gas_used
+            .as_deref()
+            .and_then(|g| u64::from_str_radix(g.trim_start_matches("0x"), 16).ok())
+    }
+
+    /// Human readable name of the function called, if the selector is known
+    pub fn function_name(&self) -> Option<String> {
+        let input = self.input.as_deref()?.trim_start_matches("0x");
+        if input.len() < 8 {
+            return None;
+        }
+        let selector = &input[..8];
+        Some(
+            decode_selector(selector)
+                .map(str::to_string)
+                .unwrap_or_else(|| format!("0x{}", selector)),
+        )
+    }
+}
+
+/// Looks up a 4-byte selector (hex, without `0x`) in the known signatures
+fn decode_selector(selector: &str) -> Option<&'static str> {
+    KNOWN_SIGNATURES.iter().copied().find(|signature| {
+        let hash = keccak256(signature.as_bytes());
+        alloy::hex::encode(&hash[..4]).eq_ignore_ascii_case(selector)
+    })
+}
+
+/// Command to show the internal call tree of a transaction
+#[derive(Debug, Parser)]
+pub struct TraceCommand {
+    /// Transaction hash to trace
+    #[arg(short, long)]
+    pub tx_hash: String,
+
+    /// RPC endpoint with the debug module enabled (defaults to the configured network)
+    #[arg(long)]
+    pub rpc_url: Option<String>,
+}
+
+impl TraceCommand {
+    pub async fn execute(&self) -> anyhow::Result<()> {
+        let config = ConfigManager::new()?.load()?;
+
+        let provider = match &self.rpc_url {
+            Some(url) => RpcPool::shared(std::slice::from_ref(url)).provider(),
+            None => rpc::connect(&config, &config.default_network, None)?,
+        };
+
+        let params = serde_json::json!([self.tx_hash, { "tracer": "callTracer" }]);
+        let result = provider
+            .request("debug_traceTransaction", params)
+            .await
+            .map_err(|e| {
+                let message = e.to_string();
+                if message.contains("-32601")
+                    || message.to_lowercase().contains("method not found")
+                    || message.to_lowercase().contains("does not exist")
+                {
+                    anyhow!(
+                        "The node does not expose debug_traceTransaction. Tracing needs a node with the debug module enabled (e.g. regtest or a private rskj node)."
+                    )
+                } else {
+                    e
+                }
+            })?;
+
+        if result.is_null() {
+            anyhow::bail!("Transaction not found: {}", self.tx_hash);
+        }
+
+        let root: CallFrame = serde_json::from_value(result).map_err(|_| {
+            anyhow!("The node returned a trace without call frames; it may not support the callTracer")
+        })?;
+
+        self.display_trace(&root);
+        Ok(())
+    }
+
+    fn display_trace(&self, root: &CallFrame) {
+        println!("\n{}\n", style("Call Trace").bold().underlined());
+        println!("{}", style(format!("  Hash: {}", self.tx_hash)).dim());
+        println!("{}", "-".repeat(60));
+
+        print_frame(root, 0);
+
+        // Internal RBTC transfers never show up in the asset transfer history
+        let mut internal = Vec::new();
+        for call in &root.calls {
+            collect_value_transfers(call, &mut internal);
+        }
+
+        println!("\n{}", style("Internal RBTC Transfers").bold().underlined());
+        println!("{}", "-".repeat(60));
+        if internal.is_empty() {
+            println!("{}", style("  None").dim());
+        } else {
+            for frame in internal {
+                println!(
+                    "  {} → {}: {} RBTC",
+                    frame.from,
+                    frame.to.as_deref().unwrap_or("contract creation"),
+                    style(format_units(frame.value_wei(), 18).unwrap_or_default()).green()
+                );
+            }
+        }
+    }
+}
+
+/// Prints a frame and its children as an indented tree
+fn print_frame(frame: &CallFrame, depth: usize) {
+    let indent = "  ".repeat(depth);
+    let branch = if depth == 0 { "" } else { "└─ " };
+
+    let mut line = format!(
+        "{}{}{} {} → {}",
+        indent,
+        branch,
+        style(&frame.call_type).cyan().bold(),
+        frame.from,
+        frame.to.as_deref().unwrap_or("contract creation")
+    );
+    if let Some(function) = frame.function_name() {
+        line.push_str(&format!(" {}", style(function).yellow()));
+    }
+    let value = frame.value_wei();
+    if !value.is_zero() {
+        line.push_str(&format!(
+            " {}",
+            style(format!("[{} RBTC]", format_units(value, 18).unwrap_or_default())).green()
+        ));
+    }
+    if let Some(gas_used) = frame.gas_used() {
+        line.push_str(&format!(" {}", style(format!("(gas {})", gas_used)).dim()));
+    }
+    println!("{}", line);
+
+    if let Some(error) = &frame.error {
+        let reason = frame
+            .revert_reason
+            .as_deref()
+            .map(|r| format!(": {}", r))
+            .unwrap_or_default();
+        println!(
+            "{}   {}",
+            indent,
+            style(format!("✗ {}{}", error, reason)).red()
+        );
+    }
+
+    for call in &frame.calls {
+        print_frame(call, depth + 1);
+    }
+}
+
+/// Collects every frame below the top-level call that moves RBTC
+fn collect_value_transfers<'a>(frame: &'a CallFrame, out: &mut Vec<&'a CallFrame>) {
+    // Nothing below a failed call took effect
+    if frame.error.is_some() {
+        return;
+    }
+    if !frame.value_wei().is_zero() {
+        out.push(frame);
+    }
+    for call in &frame.calls {
+        collect_value_transfers(call, out);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_known_selector() {
+        assert_eq!(decode_selector("a9059cbb"), Some("transfer(address,uint256)"));
+        assert_eq!(decode_selector("deadbeef"), None);
+    }
+
+    #[test]
+    fn test_internal_transfers_skip_failed_calls() {
+        let root: CallFrame = serde_json::from_value(serde_json::json!({
+            "type": "CALL",
+            "from": "0x1",
+            "to": "0x2",
+            "value": "0x0",
+            "calls": [
+                { "type": "CALL", "from": "0x2", "to": "0x3", "value": "0x10" },
+                {
+                    "type": "CALL", "from": "0x2", "to": "0x4", "value": "0x0",
+                    "error": "execution reverted",
+                    "calls": [{ "type": "CALL", "from": "0x4", "to": "0x5", "value": "0x1" }]
+                }
+            ]
+        }))
+        .unwrap();
+
+        let mut internal = Vec::new();
+        for call in &root.calls {
+            collect_value_transfers(call, &mut internal);
+        }
+        assert_eq!(internal.len(), 1);
+        assert_eq!(internal[0].to.as_deref(), Some("0x3"));
+        assert_eq!(internal[0].value_wei(), U256::from(16));
+    }
+}
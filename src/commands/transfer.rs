@@ -1,8 +1,11 @@
 use crate::config::ConfigManager;
 use crate::types::wallet::WalletData;
+use crate::types::transaction::{RskTransaction, TransactionStatus};
 use crate::utils::{constants, secrets::SecretPassword};
+use crate::utils::tx_store::TransactionStore;
 use crate::utils::eth::EthClient;
 use crate::utils::helper::Config as HelperConfig;
+use crate::utils::http;
 use anyhow::{Result, anyhow};
 use clap::Parser;
 use colored::Colorize;
@@ -11,6 +14,7 @@ use alloy::signers::local::PrivateKeySigner;
 use rpassword::prompt_password;
 use std::fs;
 use std::str::FromStr;
+use std::time::SystemTime;
 use zeroize::Zeroizing;
 
 /// Result of a transfer operation
@@ -82,7 +86,7 @@ impl TransferCommand {
 
         // Create a new helper config with the private key (using Zeroizing wrapper)
         let client_config = HelperConfig {
-            network: config.default_network.get_config(),
+            network: config.network_config(),
             wallet: crate::utils::helper::WalletConfig {
                 current_wallet_address: None,
                 private_key: Some(Zeroizing::new(private_key.expose().to_string())),
@@ -97,31 +101,29 @@ impl TransferCommand {
             .map_err(|_| anyhow!("Invalid recipient address: {}", &self.address))?;
 
         // Parse optional token address
-        let (token_address, token_symbol) = if let Some(token_addr) = &self.token {
+        let (token_address, token_symbol, decimals) = if let Some(token_addr) = &self.token {
             // Handle RBTC case (zero address or None)
             if token_addr == "0x0000000000000000000000000000000000000000" || token_addr.is_empty() {
-                (None, Some("RBTC".to_string()))
+                (None, Some("RBTC".to_string()), 18)
             } else {
                 // Parse token address
                 let addr = Address::from_str(token_addr)
                     .map_err(|_| anyhow!("Invalid token address: {}", token_addr))?;
 
-                // Try to get token info, but don't fail if we can't
-                let symbol = match eth_client.get_token_info(addr).await {
-                    Ok((_, sym)) => sym,
-                    Err(_) => format!("Token (0x{})", &token_addr[2..10]),
-                };
+                // The amount is in the token's own decimals, so those must be readable
+                let (decimals, symbol) = eth_client
+                    .get_token_info(addr)
+                    .await
+                    .map_err(|e| anyhow!("Failed to read token {}: {}", token_addr, e))?;
 
-                (Some(addr), Some(symbol))
+                (Some(addr), Some(symbol), decimals)
             }
         } else {
             // Native RBTC transfer
-            (None, Some("RBTC".to_string()))
+            (None, Some("RBTC".to_string()), 18)
         };
 
         // Parse amount (convert string to wei or token units)
-        // Both RBTC and tokens use 18 decimals
-        let decimals = 18;
         let amount = alloy::primitives::utils::parse_units(&self.value, decimals)
             .map_err(|e| anyhow!("Invalid amount: {}", e))?;
 
@@ -143,14 +145,17 @@ impl TransferCommand {
             "Info".blue().bold()
         );
 
-        // Try to get receipt with retries
-        let mut retries = 5;
+        // Poll for the receipt with the HTTP policy's backoff, for up to
+        // its request timeout
+        let policy = http::client().policy().clone();
+        let started = std::time::Instant::now();
+        let mut attempt = 0;
         let receipt = loop {
             match eth_client.get_transaction_receipt(tx_hash).await {
                 Ok(receipt) => break receipt,
-                Err(_e) if retries > 0 => {
-                    retries -= 1;
-                    tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
+                Err(_e) if started.elapsed() < policy.request_timeout() => {
+                    tokio::time::sleep(policy.backoff(attempt)).await;
+                    attempt += 1;
                 }
                 Err(_e) => {
                     println!(
@@ -163,7 +168,7 @@ impl TransferCommand {
                     );
 
                     // Return with minimal receipt info since we couldn't get the full receipt
-                    return Ok(TransferResult {
+                    let result = TransferResult {
                         tx_hash,
                         from: default_wallet.address(),
                         to,
@@ -173,7 +178,9 @@ impl TransferCommand {
                         status: U64::from(0), // 0 indicates unknown/pending status
                         token_address,
                         token_symbol,
-                    });
+                    };
+                    record_transfer(&config.network_name(), &result, None);
+                    return Ok(result);
                 }
             }
         };
@@ -197,16 +204,130 @@ impl TransferCommand {
         // Zeroize sensitive data before returning
         // private_key is automatically zeroized when it goes out of scope
 
-        Ok(TransferResult {
+        let result = TransferResult {
             tx_hash,
             from: default_wallet.address(),
             to,
             value: amount.into(),
             gas_used: U256::from(receipt.gas_used),
-            gas_price: U256::ZERO, // Gas price not available in receipt
+            gas_price: U256::from(receipt.effective_gas_price),
             status,
             token_address,
             token_symbol,
-        })
+        };
+        record_transfer(
+            &config.network_name(),
+            &result,
+            Some(&receipt),
+        );
+        Ok(result)
+    }
+}
+
+/// Stores a sent transfer in the local history of the sending wallet.
+/// A transfer without a receipt is stored as pending and updated on the
+/// next history sync.
+pub(crate) fn record_transfer(
+    network: &str,
+    result: &TransferResult,
+    receipt: Option<&alloy::rpc::types::TransactionReceipt>,
+) {
+    let status = match receipt {
+        Some(r) if r.status() => TransactionStatus::Success,
+        Some(_) => TransactionStatus::Failed,
+        None => TransactionStatus::Pending,
+    };
+    let tx = RskTransaction {
+        hash: result.tx_hash,
+        from: result.from,
+        to: Some(result.to),
+        value: result.value,
+        gas_price: result.gas_price,
+        gas: result.gas_used,
+        nonce: U256::ZERO,
+        input: None,
+        block_number: receipt.and_then(|r| r.block_number).map(U64::from),
+        transaction_index: receipt.and_then(|r| r.transaction_index).map(U64::from),
+        timestamp: SystemTime::now(),
+        status,
+        token_address: result.token_address,
+        confirms: None,
+        cumulative_gas_used: receipt.map(|r| U256::from(r.inner.cumulative_gas_used())),
+        logs: receipt.map(|r| r.inner.logs().to_vec()),
+    };
+    if let Err(e) = TransactionStore::record_sent(network, tx) {
+        eprintln!("⚠️  Could not record transfer in local history: {}", e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::mock_node::{Fixtures, MockToken, TestEnv};
+    use alloy::consensus::Transaction;
+    use std::collections::HashMap;
+
+    #[tokio::test]
+    async fn test_transfers_are_signed_sent_and_recorded() {
+        let signer = PrivateKeySigner::random();
+        let recipient = Address::with_last_byte(0xbb);
+        let token = Address::with_last_byte(0x70);
+        let one = U256::from(10u64).pow(U256::from(18));
+        let mut fixtures = Fixtures::default();
+        fixtures.balances.insert(signer.address(), one * U256::from(2));
+        fixtures.tokens.insert(
+            token,
+            MockToken {
+                symbol: "RIF".to_string(),
+                decimals: 18,
+                balances: HashMap::from([(signer.address(), one * U256::from(5))]),
+            },
+        );
+        let env = TestEnv::start(fixtures).await;
+        let from = env.add_wallet(&signer, "password");
+
+        let result = TransferCommand {
+            address: format!("{:#x}", recipient),
+            value: "0.5".to_string(),
+            token: None,
+        }
+        .execute_with_password(Some("password"))
+        .await
+        .unwrap();
+        assert_eq!(result.status, U64::from(1));
+        assert_eq!(env.node.balance(recipient), one / U256::from(2));
+
+        let result = TransferCommand {
+            address: format!("{:#x}", recipient),
+            value: "1.5".to_string(),
+            token: Some(format!("{:#x}", token)),
+        }
+        .execute_with_password(Some("password"))
+        .await
+        .unwrap();
+        assert_eq!(result.token_symbol.as_deref(), Some("RIF"));
+        assert_eq!(env.node.token_balance(token, recipient), one * U256::from(3) / U256::from(2));
+
+        let sent = env.node.sent();
+        assert_eq!(sent.len(), 2);
+        assert_eq!(sent[0].to(), Some(recipient));
+        assert_eq!(sent[0].chain_id(), Some(31));
+        assert_eq!((sent[0].nonce(), sent[1].nonce()), (0, 1));
+        assert_eq!(sent[1].to(), Some(token));
+
+        let store = TransactionStore::load("testnet", &from).unwrap();
+        assert_eq!(store.transactions.len(), 2);
+        assert!(store.transactions.iter().all(|tx| tx.status == TransactionStatus::Success));
+
+        // A wrong password never reaches the node
+        let err = TransferCommand {
+            address: format!("{:#x}", recipient),
+            value: "0.1".to_string(),
+            token: None,
+        }
+        .execute_with_password(Some("wrong"))
+        .await;
+        assert!(err.is_err());
+        assert_eq!(env.node.sent().len(), 2);
     }
 }
@@ -1,17 +1,27 @@
+use std::collections::BTreeMap;
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::time::Duration;
 
 use anyhow::{Context, Result};
-use dirs;
 use serde::{Deserialize, Serialize};
 
 // Re-export the API types for easier access
 pub use crate::api::{ApiConfig, ApiKey, ApiProvider};
-use crate::types::network::Network;
+use crate::types::network::{CustomNetwork, EndpointOverride, Network, NetworkConfig};
+use crate::utils::constants;
+use crate::utils::paths::PROFILES_DIR;
+use crate::utils::http::HttpPolicy;
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Config {
     pub default_network: Network,
+    /// User-defined networks
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub custom_networks: Vec<CustomNetwork>,
+    /// Custom network in use when `default_network` is `Network::Custom`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub active_custom_network: Option<String>,
     #[serde(default)]
     pub api: ApiConfig,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -20,17 +30,158 @@ pub struct Config {
     pub alchemy_testnet_key: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub default_wallet: Option<String>,
+    /// Timeouts, retries and rate limits for network calls
+    #[serde(default)]
+    pub http: HttpPolicy,
+    #[serde(default)]
+    pub bulk: BulkSettings,
+    /// Start offline even when the network is reachable
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub force_offline: bool,
+    /// Endpoints replacing the hosted ones of built-in networks, keyed by
+    /// chain name (mainnet, testnet or regtest)
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub endpoint_overrides: BTreeMap<String, EndpointOverride>,
+}
+
+/// Bulk transfer settings, stored as `bulk` in the config file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BulkSettings {
+    /// Transactions a bulk transfer keeps in flight at once
+    pub send_window: usize,
+    /// Expected seconds between blocks of the network
+    pub block_time_secs: u64,
+    /// Blocks a batch waits for its last transactions to be mined, on top
+    /// of one block per send window
+    pub confirmation_blocks: u64,
+}
+
+impl Default for BulkSettings {
+    fn default() -> Self {
+        Self {
+            send_window: 8,
+            block_time_secs: 30,
+            confirmation_blocks: 10,
+        }
+    }
+}
+
+impl BulkSettings {
+    pub fn block_time(&self) -> Duration {
+        Duration::from_secs(self.block_time_secs.max(1))
+    }
+
+    /// How long a batch of `rows` transactions waits for confirmations
+    /// before it is left for a later resume
+    pub fn confirmation_timeout(&self, rows: usize) -> Duration {
+        let windows = rows.div_ceil(self.send_window.max(1)) as u32;
+        self.block_time() * (windows + self.confirmation_blocks as u32)
+    }
 }
 
 impl Config {
+    /// Looks up a custom network by name, ignoring case
+    pub fn custom_network(&self, name: &str) -> Option<&CustomNetwork> {
+        self.custom_networks
+            .iter()
+            .find(|n| n.name.eq_ignore_ascii_case(name))
+    }
+
+    /// The custom network in use, if the default network is a custom one
+    pub fn active_custom(&self) -> Option<&CustomNetwork> {
+        match self.default_network {
+            Network::Custom => self
+                .active_custom_network
+                .as_deref()
+                .and_then(|name| self.custom_network(name)),
+            _ => None,
+        }
+    }
+
+    /// Name commands use for the default network: the chain name of a
+    /// built-in network or the name of the active custom network
+    pub fn network_name(&self) -> String {
+        match self.active_custom() {
+            Some(custom) => custom.name.clone(),
+            None => self.default_network.chain_name().to_string(),
+        }
+    }
+
+    /// Name of the default network for display
+    pub fn network_label(&self) -> String {
+        match self.active_custom() {
+            Some(custom) => format!("{} (custom)", custom.name),
+            None => self.default_network.to_string(),
+        }
+    }
+
+    /// Endpoint settings of the default network, with API keys applied
+    pub fn network_config(&self) -> NetworkConfig {
+        match self.active_custom() {
+            Some(custom) => custom.get_config(),
+            None => {
+                let mut config = self.default_network.get_config();
+                let mut urls = self.rpc_urls_for(&self.default_network, None).into_iter();
+                config.rpc_url = urls.next().unwrap_or_default();
+                config.fallback_rpc_urls = urls.collect();
+                config
+            }
+        }
+    }
+
+    /// Endpoint override configured for a built-in network
+    pub fn endpoint_override(&self, network: &Network) -> Option<&EndpointOverride> {
+        self.endpoint_overrides.get(network.chain_name())
+    }
+
+    /// Chain id the default network's RPC must report
+    pub fn expected_chain_id(&self) -> Option<u64> {
+        match self.active_custom() {
+            Some(custom) => Some(custom.chain_id),
+            None => self.default_network.chain_id(),
+        }
+    }
+
+    /// Adds a custom network; names must be unique
+    pub fn add_custom_network(&mut self, network: CustomNetwork) -> Result<()> {
+        CustomNetwork::validate_name(&network.name)?;
+        if self.custom_network(&network.name).is_some() {
+            anyhow::bail!("A network named '{}' already exists", network.name);
+        }
+        self.custom_networks.push(network);
+        Ok(())
+    }
+
+    /// Removes a custom network, switching back to testnet if it was in use
+    pub fn remove_custom_network(&mut self, name: &str) -> Result<CustomNetwork> {
+        let index = self
+            .custom_networks
+            .iter()
+            .position(|n| n.name.eq_ignore_ascii_case(name))
+            .ok_or_else(|| anyhow::anyhow!("No custom network named '{}'", name))?;
+        if self.active_custom().is_some_and(|n| n.name.eq_ignore_ascii_case(name)) {
+            self.default_network = Network::Testnet;
+            self.active_custom_network = None;
+        }
+        Ok(self.custom_networks.remove(index))
+    }
+
     /// Get the appropriate API key for the current network and provider
     pub fn get_api_key(&self, provider: &ApiProvider) -> Option<&str> {
-        let network_str = match self.default_network {
+        self.api_key_for(provider, &self.default_network)
+    }
+
+    /// API key saved for a provider on a given network
+    pub fn api_key_for(&self, provider: &ApiProvider, network: &Network) -> Option<&str> {
+        let network_str = match network {
             Network::Mainnet | Network::AlchemyMainnet | Network::RootStockMainnet => "mainnet",
             Network::Testnet
             | Network::AlchemyTestnet
             | Network::RootStockTestnet
             | Network::Regtest => "testnet",
+            // Custom networks have their own endpoints and no hosted API keys
+            Network::Custom => return None,
         };
 
         // First try to get from the new API config
@@ -51,6 +202,30 @@ impl Config {
         }
     }
 
+    /// RPC endpoints of a network in order of preference. Built-in networks
+    /// authenticate with the saved RSK RPC and Alchemy keys, which is the only
+    /// place keys are put into URLs; `alchemy_key` replaces the saved one.
+    /// `Network::Custom` stands for the active custom network.
+    pub fn rpc_urls_for(&self, network: &Network, alchemy_key: Option<&str>) -> Vec<String> {
+        match network {
+            Network::Custom => self
+                .active_custom()
+                .map(|custom| custom.get_config().rpc_urls())
+                .unwrap_or_default(),
+            _ if let Some(urls) = self
+                .endpoint_override(network)
+                .map(|o| o.rpc_urls.clone())
+                .filter(|urls| !urls.is_empty()) =>
+            {
+                urls
+            }
+            _ => network.get_rpc_urls_with_keys(
+                self.api_key_for(&ApiProvider::RskRpc, network),
+                alchemy_key.or(self.api_key_for(&ApiProvider::Alchemy, network)),
+            ),
+        }
+    }
+
     /// Get RSK RPC API key for blockchain operations
     pub fn get_rsk_rpc_key(&self) -> Option<&str> {
         self.get_api_key(&ApiProvider::RskRpc)
@@ -104,10 +279,16 @@ impl Default for Config {
     fn default() -> Self {
         Self {
             default_network: Network::Testnet,
+            custom_networks: Vec::new(),
+            active_custom_network: None,
             api: ApiConfig::default(),
             alchemy_mainnet_key: None,
             alchemy_testnet_key: None,
             default_wallet: None,
+            http: HttpPolicy::default(),
+            bulk: BulkSettings::default(),
+            force_offline: false,
+            endpoint_overrides: BTreeMap::new(),
         }
     }
 }
@@ -118,9 +299,7 @@ pub struct ConfigManager {
 
 impl ConfigManager {
     pub fn new() -> Result<Self> {
-        let config_dir = dirs::config_dir()
-            .context("Could not find config directory")?
-            .join("rsk-rust-cli");
+        let config_dir = constants::config_dir();
 
         std::fs::create_dir_all(&config_dir)?;
 
@@ -184,6 +363,10 @@ impl ConfigManager {
             for entry in fs::read_dir(config_dir)? {
                 let entry = entry?;
                 let path = entry.path();
+                // Named profiles are cleared from within themselves
+                if entry.file_name() == PROFILES_DIR {
+                    continue;
+                }
 
                 if path.is_dir() {
                     fs::remove_dir_all(&path)?;
@@ -199,13 +382,16 @@ impl ConfigManager {
         }
 
         // Clear wallet data directory
-        if let Some(data_dir) = dirs::data_local_dir() {
-            let wallet_data_dir = data_dir.join("rsk-rust-cli");
+        {
+            let wallet_data_dir = constants::data_dir();
             if wallet_data_dir.exists() {
                 // Remove all files in the wallet data directory
                 for entry in fs::read_dir(&wallet_data_dir)? {
                     let entry = entry?;
                     let path = entry.path();
+                    if entry.file_name() == PROFILES_DIR {
+                        continue;
+                    }
 
                     if path.is_dir() {
                         fs::remove_dir_all(&path)?;
@@ -216,9 +402,11 @@ impl ConfigManager {
                     println!("Removed: {}", path.display());
                 }
 
-                // Remove the wallet data directory itself
-                fs::remove_dir(&wallet_data_dir)?;
-                println!("Removed: {}", wallet_data_dir.display());
+                // Remove the wallet data directory itself, unless it still
+                // holds other profiles
+                if fs::remove_dir(&wallet_data_dir).is_ok() {
+                    println!("Removed: {}", wallet_data_dir.display());
+                }
             }
         }
 
@@ -23,7 +23,7 @@ pub fn run_doctor() -> Result<()> {
 
     // Check network configuration
     println!("\n{}", style("🌐 Network Configuration:").bold());
-    println!("  Default network: {}", config.default_network);
+    println!("  Default network: {}", config.network_label());
 
     // Check API keys
     println!("\n{}", style("🔑 API Keys:").bold());
@@ -61,7 +61,8 @@ fn check_api_key(config: &Config, network: Network) {
         Network::Testnet
         | Network::AlchemyTestnet
         | Network::RootStockTestnet
-        | Network::Regtest => &config.alchemy_testnet_key,
+        | Network::Regtest
+        | Network::Custom => &config.alchemy_testnet_key,
     };
 
     let status = match key {
@@ -79,6 +80,7 @@ fn check_api_key(config: &Config, network: Network) {
             Network::AlchemyTestnet => "Alchemy Testnet",
             Network::RootStockMainnet => "Rootstock Mainnet",
             Network::RootStockTestnet => "Rootstock Testnet",
+            Network::Custom => "Custom",
         },
         status
     );
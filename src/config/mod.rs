@@ -1,9 +1,10 @@
+#[allow(clippy::module_inception)]
 mod config;
 mod doctor;
 mod setup;
 
 // Re-export types from the config module
-pub use config::{Config, ConfigManager};
+pub use config::{BulkSettings, Config, ConfigManager};
 
 // Re-export Network from the types module
 pub use crate::types::network::Network;
@@ -70,7 +70,8 @@ fn setup_api_keys(config: &mut Config, network: Network) -> Result<()> {
         Network::Testnet
         | Network::AlchemyTestnet
         | Network::RootStockTestnet
-        | Network::Regtest => "testnet",
+        | Network::Regtest
+        | Network::Custom => "testnet",
     };
 
     println!(
@@ -166,7 +167,8 @@ fn setup_api_keys(config: &mut Config, network: Network) -> Result<()> {
         Network::Testnet
         | Network::AlchemyTestnet
         | Network::RootStockTestnet
-        | Network::Regtest => {
+        | Network::Regtest
+        | Network::Custom => {
             println!("\nWould you like to set up mainnet API keys as well?");
             Network::Mainnet
         }
@@ -1,4 +1,5 @@
 use crate::commands::balance::BalanceCommand;
+use crate::commands::portfolio::PortfolioCommand;
 use crate::commands::tokens::TokenRegistry;
 use crate::config::ConfigManager;
 use crate::types::wallet::WalletData;
@@ -17,7 +18,7 @@ pub async fn show_balance() -> Result<()> {
 
     // Get the current network from config
     let config = ConfigManager::new()?.load()?;
-    let network = config.default_network.to_string().to_lowercase();
+    let network = config.network_name();
     println!("Using network: {}", network);
 
     // Load token registry
@@ -39,6 +40,7 @@ pub async fn show_balance() -> Result<()> {
             crate::commands::tokens::TokenInfo {
                 address: "0x0000000000000000000000000000000000000000".to_string(),
                 decimals: 18,
+                ..Default::default()
             },
         ),
     );
@@ -113,6 +115,19 @@ pub async fn show_balance() -> Result<()> {
     cmd.execute().await
 }
 
+/// Shows the balances of all wallets in all registry tokens
+pub async fn show_portfolio() -> Result<()> {
+    let cmd = PortfolioCommand {
+        network: None,
+        prices: None,
+    };
+    if let Err(e) = cmd.execute().await {
+        eprintln!("{}", style("❌ Portfolio Error").red().bold());
+        eprintln!("{}", style(format!("Error: {}", e)).red());
+    }
+    Ok(())
+}
+
 /// Displays offline balance information (wallet addresses only)
 pub async fn show_offline_balance() -> Result<()> {
     println!("\n{}", style("💰 Check Balance (Offline Mode)").bold());
@@ -148,7 +163,7 @@ pub async fn show_offline_balance() -> Result<()> {
         table.add_row(&[
             name,
             &Helper::format_address(&wallet.address),
-            &config.default_network.to_string(),
+            &config.network_label(),
             status,
         ]);
     }
@@ -1,31 +1,30 @@
 use crate::{
-    commands::{tokens::TokenRegistry, transfer::TransferCommand},
+    commands::{
+        contacts::{ContactsAction, ContactsCommand},
+        tokens::TokenRegistry,
+    },
     config::ConfigManager,
-    types::wallet::WalletData,
-    utils::{constants, secrets::SecretPassword},
+    types::wallet::{Wallet, WalletData},
+    utils::{
+        batch_journal::{BatchJournal, RowStatus},
+        bulk::{self, Asset, BulkRow, CsvMapping, Validator},
+        constants,
+        eth::EthClient,
+        helper::{Config as HelperConfig, Helper},
+        rpc::ChainRpc,
+        secrets::SecretPassword,
+    },
 };
 use anyhow::{Result, anyhow};
 use dialoguer::{Confirm, Input, Select};
 use alloy::primitives::Address;
-use serde::Deserialize;
 use std::fs;
+use std::path::Path;
+use zeroize::Zeroizing;
 
-#[derive(Debug, Clone)]
-struct Transfer {
-    to: Address,
-    value: String, // Keep as string to avoid precision loss
-    token_address: Option<String>,
-    token_symbol: String,
-}
-
-#[derive(Debug, Deserialize)]
-struct TransferInput {
-    to: String,
-    value: String,
-    token: Option<String>, // Optional token address for JSON input
-}
-
-/// Interactive menu for bulk token transfers
+/// Interactive menu for bulk token transfers. Every row is validated and
+/// reported before the password is asked for, and every batch is sent
+/// through a journal so it can be resumed.
 pub async fn bulk_transfer() -> Result<()> {
     println!("\n💸 Bulk Token Transfer");
     println!("=====================");
@@ -33,7 +32,7 @@ pub async fn bulk_transfer() -> Result<()> {
     // Load config to get network
     let config_manager = ConfigManager::new()?;
     let config = config_manager.load()?;
-    let network = config.default_network.to_string().to_lowercase();
+    let network = config.network_name();
 
     // Load token registry
     let registry = TokenRegistry::load().unwrap_or_default();
@@ -47,40 +46,35 @@ pub async fn bulk_transfer() -> Result<()> {
             crate::commands::tokens::TokenInfo {
                 address: "0x0000000000000000000000000000000000000000".to_string(),
                 decimals: 18,
+                ..Default::default()
             },
         ),
     );
 
-    if tokens.is_empty() {
-        return Err(anyhow!("No tokens found for {} network", network));
-    }
-
-    // Let user select token
+    // Let user select the token for rows that don't name one
     let token_choices: Vec<String> = tokens.iter().map(|(name, _)| name.clone()).collect();
-    let selected_token_name = Select::new()
+    let selected = Select::new()
         .with_prompt("Select token to send:")
         .items(&token_choices)
         .interact()?;
-
-    let selected_token_name = &token_choices[selected_token_name];
-
-    let (_, selected_token) = tokens
-        .into_iter()
-        .find(|(name, _)| name == selected_token_name)
-        .ok_or_else(|| anyhow!("Selected token not found"))?;
+    let (selected_name, selected_token) = &tokens[selected];
 
     // Extract token symbol from display name
-    let token_symbol = selected_token_name
+    let token_symbol = selected_name
         .split_whitespace()
         .next()
         .unwrap_or("UNKNOWN")
         .to_string();
-        
-    let token_address = if selected_token.address == "0x0000000000000000000000000000000000000000" {
-        None
-    } else {
-        Some(selected_token.address.clone())
+    let default_asset = match selected_token.address.parse::<Address>() {
+        Ok(address) if address != Address::ZERO => Asset {
+            address: Some(address),
+            symbol: token_symbol.clone(),
+            decimals: selected_token.decimals,
+        },
+        Ok(_) => Asset::rbtc(),
+        Err(_) => return Err(anyhow!("Invalid token address: {}", selected_token.address)),
     };
+    let token_address = default_asset.address.map(|a| a.to_checksum(None));
 
     // Load wallet data
     let wallet_file = constants::wallet_file_path();
@@ -96,180 +90,410 @@ pub async fn bulk_transfer() -> Result<()> {
         .get_current_wallet()
         .ok_or_else(|| anyhow!("No active wallet found. Please select a wallet first."))?;
 
-    // Prompt for password once at the beginning and validate it
-    let password = SecretPassword::new(rpassword::prompt_password("Enter password for the wallet: ")?);
-
-    // Validate password by trying to decrypt
-    match current_wallet.decrypt_private_key(&password) {
-        Ok(_) => {
-            println!("✅ Password validated successfully");
-        }
-        Err(_) => {
-            return Err(anyhow!("Incorrect password. Please try again."));
+    // Unfinished batches of this wallet can be picked up where they stopped
+    let unfinished: Vec<BatchJournal> = BatchJournal::list()?
+        .into_iter()
+        .filter(|j| j.network == network && j.sender == current_wallet.address() && !j.is_settled())
+        .collect();
+    if !unfinished.is_empty() {
+        let mut choices = vec!["Start a new bulk transfer".to_string()];
+        choices.extend(unfinished.iter().map(|j| format!("Resume {}", j.summary())));
+        let choice = Select::new()
+            .with_prompt(format!("{} unfinished batch(es) found", unfinished.len()))
+            .items(&choices)
+            .default(1)
+            .interact()?;
+        if choice > 0 {
+            let mut journal = unfinished.into_iter().nth(choice - 1).unwrap();
+            let (client_config, client) = Helper::init_eth_client(&network).await?;
+            journal.reconcile(&client, &ConfigManager::new()?.load()?.bulk).await?;
+            journal.print();
+            if journal.is_settled() {
+                println!("\nEvery row of this batch is already settled");
+                return save_settlement(&journal);
+            }
+            return run_batch(journal, client_config, current_wallet).await;
         }
     }
 
-    // Ask if user wants to use a file or manual input
-    let use_file = Confirm::new()
-        .with_prompt("Do you want to load recipients from a JSON file?")
-        .default(false)
+    // Recipients come from a CSV or JSON file, a contact group or manual input
+    let sources = [
+        "Enter recipients manually",
+        "Load recipients from a JSON file",
+        "Load recipients from a CSV file",
+        "Use a contact group",
+    ];
+    let source = Select::new()
+        .with_prompt("Where should the recipients come from?")
+        .items(&sources)
+        .default(0)
         .interact()?;
 
-    let transfers = if use_file {
-        // Load transfers from file
-        let file_path: String = Input::new()
-            .with_prompt("Enter path to JSON file with transfer details")
-            .interact_text()?;
+    let rows = match source {
+        1 => {
+            let file_path: String = Input::new()
+                .with_prompt("Enter path to JSON file with transfer details")
+                .interact_text()?;
+            bulk::read_json(Path::new(file_path.trim()))?
+        }
+        2 => csv_rows()?,
+        3 => group_rows(&registry, &network, token_address.clone(), &token_symbol)?,
+        _ => manual_rows(&token_symbol)?,
+    };
+    if rows.is_empty() {
+        println!("No transfers to send");
+        return Ok(());
+    }
 
-        let file_content = std::fs::read_to_string(&file_path)
-            .map_err(|e| anyhow!("Failed to read file: {}", e))?;
-
-        let transfer_inputs: Vec<TransferInput> = serde_json::from_str(&file_content)
-            .map_err(|e| anyhow!("Failed to parse JSON: {}", e))?;
-
-        transfer_inputs
-            .into_iter()
-            .map(|input| {
-                let to_addr = input
-                    .to
-                    .parse::<Address>()
-                    .map_err(|e| anyhow!("Invalid address {}: {}", input.to, e))?;
-                
-                // Use token from JSON or default to selected token
-                let transfer_token_address = input.token.or_else(|| token_address.clone());
-                
-                Ok(Transfer {
-                    to: to_addr,
-                    value: input.value,
-                    token_address: transfer_token_address,
-                    token_symbol: token_symbol.clone(),
-                })
-            })
-            .collect::<Result<Vec<_>>>()?
-    } else {
-        // Manual input
-        let count_str: String = Input::new()
-            .with_prompt("How many recipients?")
-            .validate_with(|input: &String| {
-                if input.parse::<usize>().is_ok() {
-                    Ok(())
-                } else {
-                    Err("Please enter a valid number".to_string())
-                }
-            })
-            .interact_text()?;
+    // Check every row before anything is signed
+    println!("\n🔎 Validating {} transfer(s)...", rows.len());
+    let (client_config, client) = Helper::init_eth_client(&network).await?;
+    let contacts_cmd = ContactsCommand {
+        action: ContactsAction::List,
+    };
+    let contacts = contacts_cmd.load_contacts().unwrap_or_default();
+    let validator = Validator {
+        client: &client,
+        sender: current_wallet.address(),
+        network: network.clone(),
+        registry: &registry,
+        contacts: &contacts,
+        default_asset,
+    };
+    let report = validator.validate(rows).await?;
+    report.print();
 
-        let count = count_str
-            .parse::<usize>()
-            .map_err(|_| anyhow!("Failed to parse number of recipients"))?;
-
-        let mut transfers = Vec::with_capacity(count);
-        for i in 0..count {
-            println!("\nRecipient #{}:", i + 1);
-
-            let to: String = Input::new()
-                .with_prompt("Recipient address (0x...)")
-                .validate_with(|input: &String| {
-                    if input.starts_with("0x") && input.len() == 42 {
-                        Ok(())
-                    } else {
-                        Err("Please enter a valid address starting with 0x".to_string())
-                    }
-                })
-                .interact()?;
+    let transfers = report.transfers();
+    if transfers.is_empty() {
+        println!("\nNo valid transfers to send");
+        return Ok(());
+    }
 
-            let to = to
-                .parse::<Address>()
-                .map_err(|e| anyhow!("Invalid address: {}", e))?;
+    let mut actions = Vec::new();
+    // A batch the wallet cannot cover would fail part way through
+    if report.problems.is_empty() {
+        actions.push(if report.error_count() == 0 {
+            "Send all transfers".to_string()
+        } else {
+            format!(
+                "Send only the {} valid transfers (skips {} rows)",
+                transfers.len(),
+                report.error_count()
+            )
+        });
+    }
+    actions.push("Dry run: build and estimate every transaction".to_string());
+    actions.push("Cancel".to_string());
+    loop {
+        let action = Select::new()
+            .with_prompt("What next?")
+            .items(&actions)
+            .default(actions.len() - 1)
+            .interact()?;
+        match actions[action].as_str() {
+            "Cancel" => {
+                println!("Transaction cancelled");
+                return Ok(());
+            }
+            a if a.starts_with("Dry run") => {
+                bulk::dry_run(&client, current_wallet.address(), &report).await?;
+            }
+            _ => break,
+        }
+    }
 
-            let amount: String = Input::new()
-                .with_prompt(&format!("Amount of {} to send (e.g., 1.0)", token_symbol))
-                .interact()?;
+    // The same input maps to its unfinished journal, so rerunning a file
+    // that was partly sent only sends what is missing
+    let journal = BatchJournal::open(&network, current_wallet.address(), &transfers)?;
+    if journal.rows.iter().any(|row| row.status != RowStatus::Planned) {
+        println!(
+            "\n⚠️  These transfers were already started as batch {}; rows that went out are not sent again",
+            journal.id
+        );
+        journal.print();
+    }
+    let earlier_runs = journal.earlier_runs()?;
+    if !earlier_runs.is_empty() {
+        println!("\n⚠️  These exact transfers were already sent:");
+        for earlier in &earlier_runs {
+            println!("   {}", earlier.summary());
+        }
+        let again = Confirm::new()
+            .with_prompt(format!("Pay everyone again as new batch {}?", journal.id))
+            .default(false)
+            .interact()?;
+        if !again {
+            println!("Transaction cancelled");
+            return Ok(());
+        }
+    }
+    // Rows already covered by the confirmation above are not asked about twice
+    let sent_elsewhere: Vec<(usize, String)> = journal
+        .sent_elsewhere(&BatchJournal::list()?)
+        .into_iter()
+        .filter(|(_, batch)| !earlier_runs.iter().any(|earlier| &earlier.id == batch))
+        .collect();
+    if !sent_elsewhere.is_empty() {
+        println!("\n⚠️  Rows matching transfers that already went out in another batch:");
+        for (line, batch) in &sent_elsewhere {
+            println!("   line {} (batch {})", line, batch);
+        }
+        let proceed = Confirm::new()
+            .with_prompt("Send them anyway?")
+            .default(false)
+            .interact()?;
+        if !proceed {
+            println!("Transaction cancelled");
+            return Ok(());
+        }
+    }
+    run_batch(journal, client_config, current_wallet).await
+}
 
-            transfers.push(Transfer { 
-                to, 
-                value: amount,
-                token_address: token_address.clone(),
-                token_symbol: token_symbol.clone(),
-            });
+/// Unlocks the wallet and sends the batch through its journal, waits for
+/// confirmations and writes the settlement report
+async fn run_batch(mut journal: BatchJournal, mut client_config: HelperConfig, wallet: &Wallet) -> Result<()> {
+    // Prompt for password once and validate it
+    let password = SecretPassword::new(rpassword::prompt_password("Enter password for the wallet: ")?);
+    let private_key = match wallet.decrypt_private_key(&password) {
+        Ok(key) => {
+            println!("✅ Password validated successfully");
+            key
+        }
+        Err(_) => {
+            return Err(anyhow!("Incorrect password. Please try again."));
         }
-        transfers
     };
+    client_config.wallet.private_key = Some(Zeroizing::new(private_key.expose().to_string()));
+    let client = EthClient::new(&client_config, None).await?;
+    let gas_price = client.gas_price().await?;
+    let settings = ConfigManager::new()?.load()?.bulk;
+
+    println!("\n🚀 Sending transactions (journal {})...", journal.id);
+    journal.save()?;
+    let sent = journal
+        .send(&client, gas_price, &settings)
+        .await;
+
+    println!("\n⏳ Waiting for confirmations...");
+    journal
+        .wait_for_confirmations(&client, &settings)
+        .await?;
+    journal.print();
+    if let Err(e) = &sent {
+        println!("\n❌ {}", e);
+    }
+    if !journal.is_settled() {
+        println!("\nSome transactions are not settled yet. Run bulk transfer again to resume this batch.");
+    }
 
-    // Show summary
-    println!("\n📋 Transaction Summary:");
+    println!("\n📊 Transaction Summary:");
     println!("====================");
+    println!("Total transactions: {}", journal.rows.len());
+    println!("✅ Confirmed: {}", journal.count(RowStatus::Confirmed));
+    println!("❌ Reverted: {}", journal.count(RowStatus::Reverted));
+    println!("⚠️  Replaced: {}", journal.count(RowStatus::Replaced));
+    println!("🔍 Needs review: {}", journal.count(RowStatus::Review));
+    println!(
+        "⏳ Unsettled: {}",
+        journal.rows.iter().filter(|row| !row.status.is_settled()).count()
+    );
 
-    for (i, transfer) in transfers.iter().enumerate() {
-        println!(
-            "{:2}. To: {} - Amount: {} {}",
-            i + 1,
-            transfer.to,
-            transfer.value,
-            transfer.token_symbol
-        );
-    }
+    save_settlement(&journal)
+}
 
-    println!("\nToken: {}", token_symbol);
-    println!("Total transactions: {}", transfers.len());
+fn save_settlement(journal: &BatchJournal) -> Result<()> {
+    let path: String = Input::new()
+        .with_prompt("Save settlement report to (.csv or .json)")
+        .default(journal.default_settlement_path().display().to_string())
+        .interact_text()?;
+    journal.write_settlement(Path::new(path.trim()))?;
+    println!("📄 Settlement report saved to {}", path.trim());
+    Ok(())
+}
 
-    // Confirm before sending
-    let confirm = Confirm::new()
-        .with_prompt("\nDo you want to send these transactions?")
-        .default(false)
-        .interact()?;
+fn manual_rows(token_symbol: &str) -> Result<Vec<BulkRow>> {
+    let count_str: String = Input::new()
+        .with_prompt("How many recipients?")
+        .validate_with(|input: &String| {
+            if input.parse::<usize>().is_ok() {
+                Ok(())
+            } else {
+                Err("Please enter a valid number".to_string())
+            }
+        })
+        .interact_text()?;
 
-    if !confirm {
-        println!("Transaction cancelled");
-        return Ok(());
-    }
+    let count = count_str
+        .parse::<usize>()
+        .map_err(|_| anyhow!("Failed to parse number of recipients"))?;
 
-    // Send transactions using TransferCommand
-    println!("\n🚀 Sending transactions...");
+    let mut rows = Vec::with_capacity(count);
+    for i in 0..count {
+        println!("\nRecipient #{}:", i + 1);
 
-    let mut successful = 0;
-    let mut failed = 0;
+        let recipient: String = Input::new()
+            .with_prompt("Recipient (0x address, contact name or name.rsk)")
+            .interact_text()?;
+        let amount: String = Input::new()
+            .with_prompt(format!("Amount of {} to send (e.g., 1.0)", token_symbol))
+            .interact_text()?;
 
-    for (i, transfer) in transfers.iter().enumerate() {
-        print!("Sending {}/{}... ", i + 1, transfers.len());
+        rows.push(BulkRow {
+            line: i + 1,
+            recipient,
+            amount,
+            token: None,
+        });
+    }
+    Ok(rows)
+}
 
-        let transfer_cmd = TransferCommand {
-            address: format!("{:?}", transfer.to),
-            value: transfer.value.clone(),
-            token: transfer.token_address.clone(),
-        };
+/// Rows of a CSV file, with columns matched by header name or picked by hand
+fn csv_rows() -> Result<Vec<BulkRow>> {
+    let file_path: String = Input::new()
+        .with_prompt("Enter path to CSV file with transfer details")
+        .interact_text()?;
+    let path = Path::new(file_path.trim());
+    let headers = bulk::csv_headers(path)?;
+    if headers.len() < 2 {
+        return Err(anyhow!("The CSV file needs a header row with at least two columns"));
+    }
 
-        match transfer_cmd.execute_with_password(Some(password.expose())).await {
-            Ok(result) => {
-                println!("✅ Success! Tx: {:?}", result.tx_hash);
-                successful += 1;
-            }
-            Err(e) => {
-                // Check if it's a password error and provide better message
-                let error_msg = if e.to_string().contains("Incorrect password") {
-                    "Incorrect password entered"
-                } else {
-                    &e.to_string()
-                };
-                println!("❌ Failed: {}", error_msg);
-                failed += 1;
+    let mapping = match CsvMapping::detect(&headers) {
+        Some(mapping) => {
+            let token = mapping.token.map(|i| headers[i].as_str()).unwrap_or("none");
+            println!(
+                "Columns: recipient = {}, amount = {}, token = {}",
+                headers[mapping.recipient], headers[mapping.amount], token
+            );
+            let keep = Confirm::new()
+                .with_prompt("Use these columns?")
+                .default(true)
+                .interact()?;
+            if keep { Some(mapping) } else { None }
+        }
+        None => {
+            println!("Could not tell which columns hold the recipient and amount");
+            None
+        }
+    };
+    let mapping = match mapping {
+        Some(mapping) => mapping,
+        None => {
+            let recipient = Select::new()
+                .with_prompt("Column with the recipient (address, contact or RNS name)")
+                .items(&headers)
+                .interact()?;
+            let amount = Select::new()
+                .with_prompt("Column with the amount")
+                .items(&headers)
+                .interact()?;
+            let mut token_choices = vec!["None: use the selected token".to_string()];
+            token_choices.extend(headers.iter().cloned());
+            let token = Select::new()
+                .with_prompt("Column with the token symbol or address")
+                .items(&token_choices)
+                .default(0)
+                .interact()?;
+            CsvMapping {
+                recipient,
+                amount,
+                token: token.checked_sub(1),
             }
         }
+    };
+    bulk::read_csv(path, mapping)
+}
 
-        // Small delay between transactions
-        tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+/// Symbol of a token address in the registry; `None` and the zero address are RBTC
+fn token_symbol_for(registry: &TokenRegistry, network: &str, token: Option<&str>) -> String {
+    match token {
+        None => "RBTC".to_string(),
+        Some(address) if address.parse::<Address>().ok() == Some(Address::ZERO) => "RBTC".to_string(),
+        Some(address) => registry
+            .tokens(network)
+            .iter()
+            .find(|(_, info)| info.address.eq_ignore_ascii_case(address))
+            .map(|(symbol, _)| symbol.clone())
+            .unwrap_or_else(|| format!("tokens of {}", address)),
     }
-
-    println!("\n📊 Transaction Summary:");
-    println!("====================");
-    println!("Total transactions: {}", successful + failed);
-    println!("✅ Successful: {}", successful);
-    println!("❌ Failed: {}", failed);
-
-    // password is automatically zeroized when it goes out of scope
-
-    Ok(())
 }
 
+/// Transfers to every member of a contact group. Amounts and tokens come
+/// from the member's override, then the group default, then the token
+/// picked for this run; any member can still be adjusted or skipped.
+fn group_rows(
+    registry: &TokenRegistry,
+    network: &str,
+    token_address: Option<String>,
+    token_symbol: &str,
+) -> Result<Vec<BulkRow>> {
+    let contacts_cmd = ContactsCommand {
+        action: ContactsAction::List,
+    };
+    let groups = contacts_cmd.load_groups()?;
+    if groups.is_empty() {
+        return Err(anyhow!(
+            "No contact groups found. Create one under Contacts or with `contacts group create`."
+        ));
+    }
+    let names: Vec<&str> = groups.iter().map(|g| g.name.as_str()).collect();
+    let selected = Select::new()
+        .with_prompt("Select contact group:")
+        .items(&names)
+        .interact()?;
+    let group = &groups[selected];
+    let recipients = group.recipients(&contacts_cmd.load_contacts()?);
+    if recipients.is_empty() {
+        return Err(anyhow!("Group {} has no members", group.name));
+    }
 
+    if group.default_token.is_some() {
+        println!(
+            "ℹ️  {} sends {} unless a member says otherwise",
+            group.name,
+            token_symbol_for(registry, network, group.default_token.as_deref())
+        );
+    }
+    let adjust = Confirm::new()
+        .with_prompt("Adjust amounts for individual members?")
+        .default(false)
+        .interact()?;
+
+    let mut rows = Vec::with_capacity(recipients.len());
+    for (i, recipient) in recipients.into_iter().enumerate() {
+        let token = match recipient.token {
+            Some(token) if token.parse::<Address>().ok() == Some(Address::ZERO) => None,
+            Some(token) => Some(token),
+            None => token_address.clone(),
+        };
+        let symbol = if token == token_address {
+            token_symbol.to_string()
+        } else {
+            token_symbol_for(registry, network, token.as_deref())
+        };
 
+        let value = match (&recipient.amount, adjust) {
+            (Some(amount), false) => amount.clone(),
+            (default, _) => {
+                let prompt = format!("Amount of {} for {} (0 skips)", symbol, recipient.name);
+                let input = Input::<String>::new().with_prompt(prompt);
+                match default {
+                    Some(amount) => input.default(amount.clone()).interact_text()?,
+                    None => input.interact_text()?,
+                }
+            }
+        };
+        if value.trim().parse::<f64>().is_ok_and(|v| v == 0.0) {
+            continue;
+        }
+        rows.push(BulkRow {
+            line: i + 1,
+            recipient: recipient.address.to_checksum(None),
+            amount: value.trim().to_string(),
+            token: Some(token.unwrap_or_else(|| "RBTC".to_string())),
+        });
+    }
+    Ok(rows)
+}
@@ -4,8 +4,10 @@ use dialoguer::{Confirm, Input, Password, Select, theme::ColorfulTheme};
 
 // Import config and API types
 use crate::api::ApiProvider;
-use crate::config::ConfigManager;
-use crate::types::network::Network;
+use crate::config::{BulkSettings, ConfigManager};
+use crate::types::network::{CustomNetwork, Network, fetch_chain_id};
+use crate::utils::http::{self, HttpPolicy, Provider};
+use crate::utils::rpc_pool::redact_url;
 
 // This module provides configuration management functionality
 
@@ -26,7 +28,7 @@ pub async fn show_config_menu() -> Result<()> {
 
         // Show current settings
         println!("  {}", style("Current Settings:").bold());
-        println!("  • Network: {}", style(config.default_network).cyan());
+        println!("  • Network: {}", style(config.network_label()).cyan());
 
         // Show current API key status
         let providers = [
@@ -53,6 +55,9 @@ pub async fn show_config_menu() -> Result<()> {
         let options = vec![
             format!("{}  Change Network", style("🌐").bold().blue()),
             format!("{}  Manage API Keys", style("🔑").bold().green()),
+            format!("{}  Custom Networks", style("🔗").bold().cyan()),
+            format!("{}  Connection Settings", style("⏱️").bold().yellow()),
+            format!("{}  Bulk Transfer Settings", style("📦").bold().magenta()),
             format!("{}  Clear Cache & Reset", style("🧹").bold().red()),
             format!("{}  Back to Main Menu", style("⬅️").bold().blue()),
         ];
@@ -66,7 +71,10 @@ pub async fn show_config_menu() -> Result<()> {
         match selection {
             0 => change_network(&config_manager).await?,
             1 => manage_api_keys(&config_manager).await?,
-            2 => {
+            2 => manage_custom_networks(&config_manager).await?,
+            3 => edit_http_policy(&config_manager)?,
+            4 => edit_bulk_settings(&config_manager)?,
+            5 => {
                 let confirm = Confirm::new()
                     .with_prompt("⚠️  WARNING: This will delete ALL wallet data and cannot be undone! Continue?")
                     .default(false)
@@ -81,7 +89,7 @@ pub async fn show_config_menu() -> Result<()> {
                     println!("\nOperation cancelled. No data was deleted.");
                 }
             }
-            3 => break,
+            6 => break,
             _ => {}
         }
     }
@@ -246,7 +254,7 @@ async fn change_network(config_manager: &ConfigManager) -> Result<()> {
         Network::RootStockTestnet,
     ];
 
-    let network_descriptions = [
+    let mut network_descriptions: Vec<String> = [
         "Mainnet (Production, real RSK)",
         "Testnet (Test network, free test tokens)",
         "Regtest (Local development)",
@@ -254,15 +262,32 @@ async fn change_network(config_manager: &ConfigManager) -> Result<()> {
         "Alchemy Testnet (Test network, Alchemy RPC)",
         "Rootstock Mainnet (Production, Rootstock RPC)",
         "Rootstock Testnet (Test network, Rootstock RPC)",
-    ];
+    ]
+    .iter()
+    .map(|d| d.to_string())
+    .collect();
 
-    let current_network = config.default_network;
+    // User-defined networks follow the built-in ones
+    network_descriptions.extend(config.custom_networks.iter().map(|n| {
+        format!("{} (Custom, chain {}, {})", n.name, n.chain_id, n.rpc_url())
+    }));
 
     // Find the current network's index
-    let current_index = networks
-        .iter()
-        .position(|&n| n == current_network)
-        .unwrap_or(0);
+    let current_index = match config.active_custom() {
+        Some(custom) => {
+            let name = custom.name.clone();
+            networks.len()
+                + config
+                    .custom_networks
+                    .iter()
+                    .position(|n| n.name == name)
+                    .unwrap_or(0)
+        }
+        None => networks
+            .iter()
+            .position(|&n| n == config.default_network)
+            .unwrap_or(0),
+    };
 
     let selection = Select::with_theme(&ColorfulTheme::default())
         .with_prompt("Select network:")
@@ -270,10 +295,15 @@ async fn change_network(config_manager: &ConfigManager) -> Result<()> {
         .default(current_index)
         .interact()?;
 
-    let selected_network = networks[selection];
-
     // Always update the network, even if it's the same, to ensure consistency
-    config.default_network = selected_network;
+    if let Some(&selected_network) = networks.get(selection) {
+        config.default_network = selected_network;
+        config.active_custom_network = None;
+    } else {
+        let custom = &config.custom_networks[selection - networks.len()];
+        config.active_custom_network = Some(custom.name.clone());
+        config.default_network = Network::Custom;
+    }
 
     // Save the updated config
     config_manager.save(&config)?;
@@ -281,7 +311,7 @@ async fn change_network(config_manager: &ConfigManager) -> Result<()> {
     println!(
         "\n{} Network changed to: {}",
         style("✓").green().bold(),
-        style(selected_network).bold()
+        style(config.network_label()).bold()
     );
 
     // Show a brief confirmation before returning to menu
@@ -290,3 +320,336 @@ async fn change_network(config_manager: &ConfigManager) -> Result<()> {
 
     Ok(())
 }
+
+async fn manage_custom_networks(config_manager: &ConfigManager) -> Result<()> {
+    loop {
+        let config = config_manager.load()?;
+        clearscreen::clear().ok();
+
+        println!(
+            "\n{}",
+            style("🔗 Custom Networks").bold().blue().underlined()
+        );
+        println!("{}\n", "-".repeat(40));
+
+        if config.custom_networks.is_empty() {
+            println!("  No custom networks configured");
+        } else {
+            for (i, network) in config.custom_networks.iter().enumerate() {
+                println!(
+                    "  {}. {} - chain {} ({} RPC URL{}, {})",
+                    i + 1,
+                    style(&network.name).bold(),
+                    network.chain_id,
+                    network.rpc_urls.len(),
+                    if network.rpc_urls.len() == 1 { "" } else { "s" },
+                    network.native_symbol
+                );
+            }
+        }
+
+        let options = vec![
+            format!("{}  Add Network", style("+").bold().green()),
+            format!("{}  Remove Network", style("-").bold().red()),
+            format!("{}  Back to Configuration", style("⬅️").bold().blue()),
+        ];
+
+        let selection = Select::with_theme(&ColorfulTheme::default())
+            .with_prompt("\nWhat would you like to do?")
+            .items(&options)
+            .default(0)
+            .interact()?;
+
+        match selection {
+            0 => add_custom_network(config_manager).await?,
+            1 => remove_custom_network(config_manager).await?,
+            2 => break,
+            _ => {}
+        }
+    }
+
+    Ok(())
+}
+
+async fn add_custom_network(config_manager: &ConfigManager) -> Result<()> {
+    let mut config = config_manager.load()?;
+
+    let name: String = Input::with_theme(&ColorfulTheme::default())
+        .with_prompt("Network name (lowercase, e.g. devnet)")
+        .validate_with(|input: &String| -> Result<(), String> {
+            CustomNetwork::validate_name(input.trim()).map_err(|e| e.to_string())
+        })
+        .interact_text()?;
+
+    let urls: String = Input::with_theme(&ColorfulTheme::default())
+        .with_prompt("RPC URLs (comma separated, preferred first)")
+        .validate_with(|input: &String| -> Result<(), &str> {
+            if input.split(',').any(|u| {
+                let u = u.trim();
+                !(u.starts_with("http://") || u.starts_with("https://"))
+            }) {
+                return Err("Every URL must start with http:// or https://");
+            }
+            Ok(())
+        })
+        .interact_text()?;
+    let rpc_urls: Vec<String> = urls.split(',').map(|u| u.trim().to_string()).collect();
+
+    let chain_id: String = Input::with_theme(&ColorfulTheme::default())
+        .with_prompt("Chain id (leave empty to read it from the node)")
+        .allow_empty(true)
+        .validate_with(|input: &String| -> Result<(), &str> {
+            if input.trim().is_empty() || input.trim().parse::<u64>().is_ok() {
+                Ok(())
+            } else {
+                Err("Chain id must be a number")
+            }
+        })
+        .interact_text()?;
+
+    let explorer_url: String = Input::with_theme(&ColorfulTheme::default())
+        .with_prompt("Explorer transaction URL, use {hash} for the hash (optional)")
+        .allow_empty(true)
+        .interact_text()?;
+
+    let native_symbol: String = Input::with_theme(&ColorfulTheme::default())
+        .with_prompt("Native currency symbol")
+        .default("RBTC".to_string())
+        .interact_text()?;
+
+    println!("\n{}", style("⏳ Checking chain id with the RPC endpoints...").dim());
+    let chain_id = match chain_id.trim() {
+        "" => fetch_chain_id(&rpc_urls[0]).await?,
+        id => id.parse()?,
+    };
+
+    let network = CustomNetwork {
+        name: name.trim().to_string(),
+        rpc_urls,
+        chain_id,
+        explorer_url: Some(explorer_url.trim().to_string()).filter(|u| !u.is_empty()),
+        native_symbol: native_symbol.trim().to_uppercase(),
+    };
+
+    if let Err(e) = network.verify().await {
+        println!("\n{} {}", style("❌").red(), e);
+        println!("\n{}", style("Press Enter to continue...").dim());
+        let _ = std::io::stdin().read_line(&mut String::new());
+        return Ok(());
+    }
+
+    let label = format!("{} (chain {})", network.name, network.chain_id);
+    config.add_custom_network(network)?;
+    config_manager.save(&config)?;
+
+    println!(
+        "\n{} Added network {}. Select it with Change Network.",
+        style("✓").green().bold(),
+        style(label).bold()
+    );
+    println!("\n{}", style("Press Enter to continue...").dim());
+    let _ = std::io::stdin().read_line(&mut String::new());
+
+    Ok(())
+}
+
+async fn remove_custom_network(config_manager: &ConfigManager) -> Result<()> {
+    let mut config = config_manager.load()?;
+
+    if config.custom_networks.is_empty() {
+        println!("\n{}", style("No custom networks to remove").yellow().bold());
+        return Ok(());
+    }
+
+    let names: Vec<String> = config.custom_networks.iter().map(|n| n.name.clone()).collect();
+    let selection = Select::with_theme(&ColorfulTheme::default())
+        .with_prompt("Select a network to remove:")
+        .items(&names)
+        .default(0)
+        .interact()?;
+
+    let confirm = Confirm::new()
+        .with_prompt(format!("Remove network '{}'?", names[selection]))
+        .default(false)
+        .interact()?;
+    if !confirm {
+        return Ok(());
+    }
+
+    let was_active = config.active_custom_network.as_deref() == Some(names[selection].as_str());
+    let removed = config.remove_custom_network(&names[selection])?;
+    config_manager.save(&config)?;
+
+    println!(
+        "\n{} Removed network {}",
+        style("✓").green().bold(),
+        style(&removed.name).bold()
+    );
+    if was_active {
+        println!("Active network is now {}", style(config.network_label()).bold());
+    }
+    println!("\n{}", style("Press Enter to continue...").dim());
+    let _ = std::io::stdin().read_line(&mut String::new());
+
+    Ok(())
+}
+
+fn edit_http_policy(config_manager: &ConfigManager) -> Result<()> {
+    let mut config = config_manager.load()?;
+    let policy = &config.http;
+
+    println!(
+        "\n{}",
+        style("⏱️  Connection Settings").bold().blue().underlined()
+    );
+    println!(
+        "  Proxy: {}",
+        policy.proxy.as_deref().map(redact_url).unwrap_or_else(|| "none".to_string())
+    );
+    println!(
+        "  Offline mode: {}",
+        if config.force_offline { "forced" } else { "automatic" }
+    );
+    println!("  Connect timeout: {}s", policy.connect_timeout_secs);
+    println!("  Request timeout: {}s", policy.request_timeout_secs);
+    println!(
+        "  Retries: {} (backoff {} ms doubling up to {} ms)",
+        policy.max_retries, policy.initial_backoff_ms, policy.max_backoff_ms
+    );
+    for provider in Provider::ALL {
+        let limit = policy.rate_limits.get(provider);
+        let shown = if limit.requests_per_second > 0.0 {
+            format!("{} req/s, burst {}", limit.requests_per_second, limit.burst)
+        } else {
+            "unlimited".to_string()
+        };
+        println!("  {}: {}", provider, shown);
+    }
+
+    let options = [
+        "Edit timeouts and retries",
+        "Edit rate limits",
+        "Set proxy",
+        if config.force_offline {
+            "Leave forced offline mode"
+        } else {
+            "Force offline mode"
+        },
+        "Reset to defaults",
+        "Back",
+    ];
+    let selection = Select::with_theme(&ColorfulTheme::default())
+        .with_prompt("\nWhat would you like to do?")
+        .items(&options)
+        .default(0)
+        .interact()?;
+
+    let policy = &mut config.http;
+    match selection {
+        0 => {
+            policy.connect_timeout_secs = prompt_number("Connect timeout (seconds)", policy.connect_timeout_secs)?;
+            policy.request_timeout_secs = prompt_number("Request timeout (seconds)", policy.request_timeout_secs)?;
+            policy.max_retries = prompt_number("Retries", policy.max_retries)?;
+            policy.initial_backoff_ms = prompt_number("First backoff (ms)", policy.initial_backoff_ms)?;
+            policy.max_backoff_ms = prompt_number("Longest backoff (ms)", policy.max_backoff_ms)?;
+        }
+        1 => {
+            for provider in Provider::ALL {
+                let limit = policy.rate_limits.get_mut(provider);
+                limit.requests_per_second = prompt_number(
+                    &format!("{} requests per second (0 for unlimited)", provider),
+                    limit.requests_per_second,
+                )?;
+                limit.burst = prompt_number(&format!("{} burst", provider), limit.burst)?;
+            }
+        }
+        2 => {
+            let proxy: String = Input::with_theme(&ColorfulTheme::default())
+                .with_prompt("Proxy URL (http://, https://, socks5://; empty for none)")
+                .default(policy.proxy.clone().unwrap_or_default())
+                .allow_empty(true)
+                .validate_with(|input: &String| -> Result<(), String> {
+                    if input.trim().is_empty() {
+                        return Ok(());
+                    }
+                    HttpPolicy::validate_proxy(input.trim()).map_err(|e| e.to_string())
+                })
+                .interact_text()?;
+            let proxy = proxy.trim();
+            policy.proxy = (!proxy.is_empty()).then(|| proxy.to_string());
+        }
+        3 => {
+            config.force_offline = !config.force_offline;
+            println!(
+                "{}",
+                style("Takes effect the next time the wallet starts").dim()
+            );
+        }
+        4 => {
+            // Keep the proxy; it usually reflects the local network, not tuning
+            let proxy = policy.proxy.take();
+            *policy = HttpPolicy { proxy, ..HttpPolicy::default() };
+        }
+        _ => return Ok(()),
+    }
+
+    config_manager.save(&config)?;
+    // Rebuild the shared client with the new settings
+    http::reload();
+    println!("\n{} Settings saved", style("✓").green().bold());
+    Ok(())
+}
+
+fn edit_bulk_settings(config_manager: &ConfigManager) -> Result<()> {
+    let mut config = config_manager.load()?;
+
+    println!(
+        "\n{}",
+        style("📦 Bulk Transfer Settings").bold().blue().underlined()
+    );
+    println!(
+        "  Send window: {} transactions in flight",
+        config.bulk.send_window
+    );
+    println!(
+        "  Confirmations: wait {} blocks of {}s, plus one per send window",
+        config.bulk.confirmation_blocks, config.bulk.block_time_secs
+    );
+
+    let options = ["Edit send window", "Edit confirmation wait", "Reset to defaults", "Back"];
+    let selection = Select::with_theme(&ColorfulTheme::default())
+        .with_prompt("\nWhat would you like to do?")
+        .items(&options)
+        .default(0)
+        .interact()?;
+
+    match selection {
+        0 => {
+            config.bulk.send_window =
+                prompt_number("Transactions in flight during bulk sends", config.bulk.send_window)?.max(1);
+        }
+        1 => {
+            config.bulk.block_time_secs =
+                prompt_number("Seconds between blocks", config.bulk.block_time_secs)?.max(1);
+            config.bulk.confirmation_blocks =
+                prompt_number("Blocks to wait for the last transactions", config.bulk.confirmation_blocks)?;
+        }
+        2 => config.bulk = BulkSettings::default(),
+        _ => return Ok(()),
+    }
+
+    config_manager.save(&config)?;
+    println!("\n{} Settings saved", style("✓").green().bold());
+    Ok(())
+}
+
+fn prompt_number<T>(prompt: &str, current: T) -> Result<T>
+where
+    T: Clone + std::fmt::Display + std::str::FromStr,
+    T::Err: std::fmt::Display + std::fmt::Debug,
+{
+    Ok(Input::with_theme(&ColorfulTheme::default())
+        .with_prompt(prompt)
+        .default(current)
+        .interact_text()?)
+}
@@ -1,7 +1,11 @@
 use crate::{
-    commands::contacts::{ContactsAction, ContactsCommand},
+    commands::contacts::{ContactsAction, ContactsCommand, GroupAction},
+    commands::tokens::TokenRegistry,
+    config::ConfigManager,
+    utils::contact_io::{self, ConflictPolicy, ContactFormat},
     utils::table::TableBuilder,
 };
+use std::path::PathBuf;
 use anyhow::Result;
 use console::style;
 use inquire::{Confirm, Text, validator::Validation};
@@ -17,6 +21,9 @@ pub async fn manage_contacts() -> Result<()> {
             "✏️  Update contact",
             "❌ Remove contact",
             "🔍 Search contacts",
+            "👪 Contact groups",
+            "📥 Import contacts",
+            "📤 Export contacts",
             "🏠 Back to main menu",
         ];
 
@@ -28,6 +35,9 @@ pub async fn manage_contacts() -> Result<()> {
             "✏️  Update contact" => update_contact().await?,
             "❌ Remove contact" => remove_contact().await?,
             "🔍 Search contacts" => search_contacts().await?,
+            "👪 Contact groups" => manage_groups().await?,
+            "📥 Import contacts" => import_contacts().await?,
+            "📤 Export contacts" => export_contacts().await?,
             "🏠 Back to main menu" => break,
             _ => unreachable!(),
         }
@@ -287,3 +297,242 @@ pub async fn search_contacts() -> Result<()> {
 
     Ok(())
 }
+
+/// Asks for a contacts file and its format when the extension does not tell
+fn prompt_contacts_file(message: &str, default: &str) -> Result<(PathBuf, ContactFormat)> {
+    let path = PathBuf::from(
+        Text::new(message)
+            .with_default(default)
+            .with_help_message("CSV (name, address, notes, tags), vCard (.vcf) or JSON")
+            .prompt()?
+            .trim(),
+    );
+    let format = match ContactFormat::from_path(&path) {
+        Some(format) => format,
+        None => {
+            let formats = [ContactFormat::Csv, ContactFormat::Vcard, ContactFormat::Json];
+            let choice = inquire::Select::new("File format:", vec!["CSV", "vCard", "JSON"]).raw_prompt()?;
+            formats[choice.index]
+        }
+    };
+    Ok((path, format))
+}
+
+/// Imports contacts from a spreadsheet, vCard or JSON file, showing what
+/// would change before saving
+pub async fn import_contacts() -> Result<()> {
+    println!("\n{}", style("📥 Import Contacts").bold());
+
+    let (path, format) = prompt_contacts_file("File to import:", "contacts.csv")?;
+    let parsed = match contact_io::read_contacts(&path, format) {
+        Ok(parsed) => parsed,
+        Err(e) => {
+            println!("{} {}", style("❌").red(), e);
+            return Ok(());
+        }
+    };
+
+    let policies = [
+        ("Keep the saved contact", ConflictPolicy::Keep),
+        ("Overwrite the saved contact", ConflictPolicy::Overwrite),
+        ("Import under a new name", ConflictPolicy::Rename),
+    ];
+    let labels: Vec<&str> = policies.iter().map(|(label, _)| *label).collect();
+    let choice = inquire::Select::new("When a name or address is already saved:", labels)
+        .with_help_message("Contacts with a saved address are never imported twice")
+        .raw_prompt()?;
+    let policy = policies[choice.index].1;
+
+    let cmd = ContactsCommand {
+        action: ContactsAction::List,
+    };
+    let mut contacts = cmd.load_contacts()?;
+    let summary = contact_io::merge(&mut contacts, parsed.contacts, policy);
+    println!();
+    summary.print(&parsed.invalid);
+
+    if summary.changes() == 0 {
+        println!("Nothing to import.");
+        return Ok(());
+    }
+    if Confirm::new("Save these changes?").with_default(true).prompt()? {
+        cmd.save_contacts(&contacts)?;
+        println!("✅ Contacts imported successfully!");
+    } else {
+        println!("Operation cancelled.");
+    }
+    Ok(())
+}
+
+pub async fn export_contacts() -> Result<()> {
+    println!("\n{}", style("📤 Export Contacts").bold());
+
+    let (path, format) = prompt_contacts_file("Export to:", "contacts.csv")?;
+    let cmd = ContactsCommand {
+        action: ContactsAction::List,
+    };
+    let contacts = cmd.load_contacts()?;
+    contact_io::write_contacts(&path, format, &contacts)?;
+    println!("✅ {} contacts exported to {}", contacts.len(), path.display());
+    Ok(())
+}
+
+/// Groups of contacts that bulk transfers can pay in one go
+pub async fn manage_groups() -> Result<()> {
+    let options = vec![
+        "👥 List groups",
+        "➕ Create group",
+        "✏️  Set a member's amount or token",
+        "❌ Delete group",
+        "🔙 Back",
+    ];
+    let selection = inquire::Select::new("Contact groups:", options).prompt()?;
+    let action = match selection {
+        "👥 List groups" => GroupAction::List,
+        "➕ Create group" => match prompt_new_group()? {
+            Some(action) => action,
+            None => return Ok(()),
+        },
+        "✏️  Set a member's amount or token" => match prompt_override()? {
+            Some(action) => action,
+            None => return Ok(()),
+        },
+        "❌ Delete group" => {
+            let Some(name) = select_group()? else {
+                return Ok(());
+            };
+            if !Confirm::new(&format!("Delete group {}?", name)).with_default(false).prompt()? {
+                return Ok(());
+            }
+            GroupAction::Delete { name }
+        }
+        _ => return Ok(()),
+    };
+
+    let cmd = ContactsCommand {
+        action: ContactsAction::Group { action },
+    };
+    if let Err(e) = cmd.execute().await {
+        println!("{} {}", style("❌").red(), e);
+    }
+    Ok(())
+}
+
+fn select_group() -> Result<Option<String>> {
+    let cmd = ContactsCommand {
+        action: ContactsAction::List,
+    };
+    let names: Vec<String> = cmd.load_groups()?.into_iter().map(|g| g.name).collect();
+    if names.is_empty() {
+        println!("No groups found.");
+        return Ok(None);
+    }
+    Ok(Some(inquire::Select::new("Select group:", names).prompt()?))
+}
+
+/// Token address to send, chosen from the registry; `None` keeps the default
+fn select_token(prompt: &str, keep_label: &str) -> Result<Option<String>> {
+    let network = ConfigManager::new()?.load()?.network_name();
+    let registry = TokenRegistry::load().unwrap_or_default();
+    let mut tokens = registry.list_tokens(Some(&network));
+    tokens.sort_by(|a, b| a.0.cmp(&b.0));
+
+    let mut labels = vec![keep_label.to_string(), "RBTC".to_string()];
+    labels.extend(tokens.iter().map(|(symbol, _)| symbol.clone()));
+    let choice = inquire::Select::new(prompt, labels).raw_prompt()?;
+    Ok(match choice.index {
+        0 => None,
+        1 => Some("rbtc".to_string()),
+        i => Some(tokens[i - 2].1.address.clone()),
+    })
+}
+
+fn prompt_new_group() -> Result<Option<GroupAction>> {
+    let cmd = ContactsCommand {
+        action: ContactsAction::List,
+    };
+    let contacts = cmd.load_contacts()?;
+
+    let name = Text::new("Group name:")
+        .with_help_message("e.g., contributors")
+        .prompt()?;
+
+    let labels: Vec<String> = contacts
+        .iter()
+        .map(|c| format!("{} ({})", c.name, c.address))
+        .collect();
+    let members: Vec<String> = if labels.is_empty() {
+        Vec::new()
+    } else {
+        inquire::MultiSelect::new("Members:", labels)
+            .with_help_message("Space to select; leave empty to use a tag only")
+            .raw_prompt()?
+            .into_iter()
+            .map(|option| contacts[option.index].address.to_string())
+            .collect()
+    };
+
+    let tag = Text::new("Also include contacts tagged (optional):")
+        .prompt_skippable()?
+        .map(|t| t.trim().to_string())
+        .filter(|t| !t.is_empty());
+    if members.is_empty() && tag.is_none() {
+        println!("A group needs members or a tag.");
+        return Ok(None);
+    }
+
+    let amount = Text::new("Default amount per member (optional):")
+        .with_help_message("Used by bulk transfers unless a member has its own amount")
+        .prompt_skippable()?
+        .map(|a| a.trim().to_string())
+        .filter(|a| !a.is_empty());
+    let token = select_token("Default token:", "Whatever is picked when sending")?;
+
+    Ok(Some(GroupAction::Create {
+        name,
+        members,
+        tag,
+        amount,
+        token,
+    }))
+}
+
+fn prompt_override() -> Result<Option<GroupAction>> {
+    let Some(group_name) = select_group()? else {
+        return Ok(None);
+    };
+    let cmd = ContactsCommand {
+        action: ContactsAction::List,
+    };
+    let contacts = cmd.load_contacts()?;
+    let group = cmd
+        .load_groups()?
+        .into_iter()
+        .find(|g| g.name == group_name)
+        .ok_or_else(|| anyhow::anyhow!("Group '{}' not found", group_name))?;
+    let recipients = group.recipients(&contacts);
+    if recipients.is_empty() {
+        println!("Group {} has no members.", group.name);
+        return Ok(None);
+    }
+
+    let labels: Vec<String> = recipients
+        .iter()
+        .map(|r| format!("{} - {} {}", r.name, r.amount.as_deref().unwrap_or("-"), r.token.as_deref().unwrap_or("")))
+        .collect();
+    let member = &recipients[inquire::Select::new("Member:", labels).raw_prompt()?.index];
+
+    let amount = Text::new("Amount (leave empty to keep):")
+        .prompt_skippable()?
+        .map(|a| a.trim().to_string())
+        .filter(|a| !a.is_empty());
+    let token = select_token("Token:", "Keep current")?;
+
+    Ok(Some(GroupAction::Override {
+        group: group.name,
+        member: member.address.to_string(),
+        amount,
+        token,
+        clear: false,
+    }))
+}
@@ -1,6 +1,9 @@
+use crate::commands::cost_basis::{CostBasisCommand, CostMethod};
 use crate::commands::history::HistoryCommand;
 use crate::commands::tokens::{TokenRegistry, list_tokens};
 use crate::config::ConfigManager;
+use crate::utils::block_resolver::{DateZone, parse_date_range};
+use crate::utils::export::ExportFormat;
 use crate::utils::api_validator::{validate_api_key_format, validate_api_key, ValidationResult};
 use crate::api::{ApiKey, ApiProvider};
 use anyhow::Result;
@@ -9,6 +12,15 @@ use inquire::{Confirm, Select, Text, Password, validator::Validation};
 
 /// Shows the transaction history in an interactive way
 pub async fn show_history() -> Result<()> {
+    run_history(false).await
+}
+
+/// Shows the locally stored transaction history without syncing
+pub async fn show_offline_history() -> Result<()> {
+    run_history(true).await
+}
+
+async fn run_history(offline: bool) -> Result<()> {
     println!("\n{}", style("📜 Transaction History").bold());
     println!("{}", "=".repeat(30));
 
@@ -17,21 +29,18 @@ pub async fn show_history() -> Result<()> {
     let config = config_manager.load()?;
 
     // Network selection
-    let network_options = vec!["mainnet", "testnet"];
-    let network_selection = Select::new("Select network:", network_options)
+    let mut network_options = vec!["mainnet".to_string(), "testnet".to_string(), "regtest".to_string()];
+    network_options.extend(config.custom_networks.iter().map(|n| n.name.clone()));
+    let current = config.network_name();
+    let network_selection = Select::new("Select network:", network_options.clone())
         .with_starting_cursor(
-            if config
-                .default_network
-                .to_string()
-                .to_lowercase()
-                .contains("testnet")
-            {
-                1
-            } else {
-                0
-            },
+            network_options
+                .iter()
+                .position(|n| *n == current)
+                .unwrap_or(0),
         )
         .prompt()?;
+    let network_selection = network_selection.as_str();
 
     // Default values for the history command
     let mut command = HistoryCommand {
@@ -43,24 +52,34 @@ pub async fn show_history() -> Result<()> {
         token: None,
         from: None,
         to: None,
+        timezone: "local".to_string(),
         sort_by: "timestamp".to_string(),
         sort_order: "desc".to_string(),
         incoming: false,
         outgoing: false,
         export_csv: None,
+        export: None,
+        export_format: None,
         api_key: match network_selection {
             "mainnet" => config.alchemy_mainnet_key.clone(),
             "testnet" => config.alchemy_testnet_key.clone(),
             _ => None,
         },
         network: network_selection.to_string(),
+        from_block: None,
+        offline,
     };
 
+    // Regtest and custom networks have no Alchemy, so their history always
+    // comes from the node
+    let mut index_from_rpc =
+        offline || !matches!(network_selection, "mainnet" | "testnet");
+
     // Load available tokens for the selected network
     let registry = TokenRegistry::load()
         .map_err(|e| anyhow::anyhow!("Failed to load token registry: {}", e))?;
     let tokens = registry.list_tokens(Some(network_selection));
-    let mut token_options = vec!["RBTC (Native)".to_string()];
+    let mut token_options = vec!["All Tokens".to_string(), "RBTC (Native)".to_string()];
     token_options.extend(tokens.into_iter().map(|(symbol, _info)| symbol));
 
     // Main history menu loop
@@ -88,13 +107,14 @@ pub async fn show_history() -> Result<()> {
         println!("Limit: {} transactions", command.limit);
         println!("{}", "-".repeat(40));
 
-        // Check if we have an API key, prompt if not
-        if command.api_key.is_none() {
+        // Check if we have an API key, prompt if not. Without one the
+        // history is indexed from the RPC node, which is slower.
+        if command.api_key.is_none() && !index_from_rpc {
             println!(
                 "\n{}",
-                style("⚠️  Alchemy API Key Required").yellow().bold()
+                style("⚠️  No Alchemy API Key").yellow().bold()
             );
-            println!("Transaction history requires an Alchemy API key.");
+            println!("Without an Alchemy API key, history is indexed from the RPC node (slower).");
 
             let should_add_key = Confirm::new("Would you like to add an API key now?")
                 .with_default(true)
@@ -165,12 +185,8 @@ pub async fn show_history() -> Result<()> {
                     }
                 }
             } else {
-                println!(
-                    "\n{}",
-                    style("⚠️  Transaction history requires an API key.").yellow()
-                );
                 println!("You can add an API key later from the Configuration menu.");
-                return Ok(());
+                index_from_rpc = true;
             }
         }
 
@@ -203,7 +219,8 @@ pub async fn show_history() -> Result<()> {
 
         // Show options for further actions
         let options = vec![
-            "Export to CSV",
+            "Export",
+            "Cost basis report",
             "Change network",
             "Change token",
             "Change limit",
@@ -228,7 +245,8 @@ pub async fn show_history() -> Result<()> {
                     // Reload tokens for the new network
                     match list_tokens(Some(&command.network)) {
                         Ok(tokens) => {
-                            token_options = std::iter::once("RBTC (Native)".to_string())
+                            token_options = ["All Tokens".to_string(), "RBTC (Native)".to_string()]
+                                .into_iter()
                                 .chain(tokens.into_iter().map(|(symbol, _info)| symbol))
                                 .collect();
                         }
@@ -237,15 +255,17 @@ pub async fn show_history() -> Result<()> {
                                 "Warning: Failed to load tokens: {}. Using default token options.",
                                 e
                             );
-                            token_options = vec!["RBTC (Native)".to_string()];
+                            token_options = vec!["All Tokens".to_string(), "RBTC (Native)".to_string()];
                         }
                     }
                 }
             }
             "Change token" => {
                 let token = Select::new("Select token:", token_options.clone()).prompt()?;
-                command.token = if token == "RBTC (Native)" {
+                command.token = if token == "All Tokens" {
                     None
+                } else if token == "RBTC (Native)" {
+                    Some("RBTC".to_string())
                 } else {
                     Some(token.split_whitespace().next().unwrap_or("").to_string())
                 };
@@ -289,24 +309,42 @@ pub async fn show_history() -> Result<()> {
                     }
                 }
             }
-            "Export to CSV" => {
-                let filename = Text::new("Enter filename to save (e.g., transactions.csv):")
-                    .with_default("transactions.csv")
-                    .with_validator(|input: &str| {
-                        if input.ends_with(".csv") {
+            "Export" => {
+                let formats = vec![
+                    "Statement CSV (decimal amounts, fees, running balances)",
+                    "JSON lines",
+                    "OFX (accounting software)",
+                    "QIF (accounting software)",
+                    "Raw CSV (wei values)",
+                ];
+                let format = match Select::new("Export format:", formats).prompt()? {
+                    "JSON lines" => ExportFormat::Jsonl,
+                    "OFX (accounting software)" => ExportFormat::Ofx,
+                    "QIF (accounting software)" => ExportFormat::Qif,
+                    "Raw CSV (wei values)" => ExportFormat::Raw,
+                    _ => ExportFormat::Statement,
+                };
+                let extension = format.extension();
+                let filename = Text::new("Enter filename to save:")
+                    .with_default(&format!("transactions.{}", extension))
+                    .with_validator(move |input: &str| {
+                        if input.ends_with(&format!(".{}", extension)) {
                             Ok(Validation::Valid)
                         } else {
-                            Ok(Validation::Invalid("Filename must end with .csv".into()))
+                            Ok(Validation::Invalid(
+                                format!("Filename must end with .{}", extension).into(),
+                            ))
                         }
                     })
                     .prompt()?;
 
                 let mut export_cmd = command.clone();
-                export_cmd.export_csv = Some(filename);
+                export_cmd.export = Some(filename);
+                export_cmd.export_format = Some(format);
 
                 match export_cmd.execute().await {
                     Ok(_) => {}
-                    Err(e) => eprintln!("Error exporting to CSV: {}", e),
+                    Err(e) => eprintln!("Error exporting transactions: {}", e),
                 }
 
                 continue;
@@ -329,13 +367,60 @@ pub async fn show_history() -> Result<()> {
                 println!("✓ All filters cleared");
             }
             "Filter by date range" => {
-                let from = Text::new("Start date (YYYY-MM-DD, leave empty for no start date):")
+                let from = Text::new("Start (YYYY-MM-DD, YYYY-MM, 'last 30 days', 'last month'; empty for none):")
                     .prompt_skippable()?;
-                let to = Text::new("End date (YYYY-MM-DD, leave empty for today):")
+                let to = Text::new("End, inclusive (same formats; empty for now):")
                     .prompt_skippable()?;
+                let timezone = Text::new("Timezone (local, UTC or an offset like +02:00):")
+                    .with_default(&command.timezone)
+                    .prompt()?;
+
+                let from = from.and_then(|s| if s.is_empty() { None } else { Some(s) });
+                let to = to.and_then(|s| if s.is_empty() { None } else { Some(s) });
+                let valid = DateZone::parse(&timezone)
+                    .and_then(|zone| parse_date_range(from.as_deref(), to.as_deref(), zone));
+                match valid {
+                    Ok(_) => {
+                        command.from = from;
+                        command.to = to;
+                        command.timezone = timezone;
+                    }
+                    Err(e) => println!("{}", style(format!("❌ {}", e)).red()),
+                }
+            }
+            "Cost basis report" => {
+                let prices = Text::new("Price file (CSV with date, asset and price columns):")
+                    .with_default("prices.csv")
+                    .prompt()?;
+                let method = match Select::new(
+                    "Lot matching method:",
+                    vec!["FIFO", "LIFO", "Average cost"],
+                )
+                .prompt()?
+                {
+                    "LIFO" => CostMethod::Lifo,
+                    "Average cost" => CostMethod::Average,
+                    _ => CostMethod::Fifo,
+                };
+                let output = Text::new("Save realized gains to CSV (empty to skip):")
+                    .prompt_skippable()?
+                    .filter(|s| !s.is_empty());
 
-                command.from = from.and_then(|s| if s.is_empty() { None } else { Some(s) });
-                command.to = to.and_then(|s| if s.is_empty() { None } else { Some(s) });
+                // The date range filter selects the reporting period
+                let report = CostBasisCommand {
+                    address: command.address.clone(),
+                    network: command.network.clone(),
+                    prices,
+                    method,
+                    from: command.from.clone(),
+                    to: command.to.clone(),
+                    timezone: command.timezone.clone(),
+                    output,
+                };
+                if let Err(e) = report.execute().await {
+                    eprintln!("Error building cost basis report: {}", e);
+                }
+                continue;
             }
             "Back to main menu" => break,
             _ => {}
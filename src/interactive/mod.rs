@@ -19,15 +19,16 @@ use dialoguer::{Select, theme::ColorfulTheme};
 
 // Re-export public functions
 pub use self::{
-    balance::{show_balance, show_offline_balance}, bulk_transfer::bulk_transfer, config::show_config_menu,
-    contacts::manage_contacts, history::show_history, system::system_menu, tokens::token_menu,
+    balance::{show_balance, show_offline_balance, show_portfolio}, bulk_transfer::bulk_transfer, config::show_config_menu,
+    contacts::manage_contacts, history::{show_history, show_offline_history}, system::system_menu, tokens::token_menu,
     transfer::send_funds, tx::check_transaction_status, wallet::create_wallet_with_name,
     wallet::wallet_menu,
 };
 
 // Import for network status display
-use crate::config::ConfigManager;
-use crate::utils::network::{check_connectivity, NetworkStatus};
+use crate::config::{Config, ConfigManager};
+use crate::utils::network::{EndpointCheck, NetworkStatus, check_network};
+use crate::utils::paths::AppPaths;
 
 // Import Network from the types module
 use crate::types::network::Network;
@@ -36,15 +37,18 @@ use crate::types::network::Network;
 pub use crate::types::network::Network as ConfigNetwork;
 
 // Helper function to get styled network status
-fn get_network_status(network: Network) -> console::StyledObject<&'static str> {
-    match network {
-        Network::Mainnet => style("🔗 Mainnet").cyan(),
-        Network::Testnet => style("🔗 Testnet").yellow(),
-        Network::Regtest => style("🔗 Regtest").magenta(),
-        Network::AlchemyMainnet => style("🔗 Alchemy Mainnet").blue(),
-        Network::AlchemyTestnet => style("🔗 Alchemy Testnet").blue(),
-        Network::RootStockMainnet => style("🔗 Rootstock Mainnet").green(),
-        Network::RootStockTestnet => style("🔗 Rootstock Testnet").green(),
+fn get_network_status(config: &Config) -> console::StyledObject<String> {
+    if let Some(custom) = config.active_custom() {
+        return style(format!("🔗 {} (custom)", custom.name)).white();
+    }
+    let label = format!("🔗 {}", config.default_network);
+    match config.default_network {
+        Network::Mainnet => style(label).cyan(),
+        Network::Testnet => style(label).yellow(),
+        Network::Regtest => style(label).magenta(),
+        Network::AlchemyMainnet | Network::AlchemyTestnet => style(label).blue(),
+        Network::RootStockMainnet | Network::RootStockTestnet => style(label).green(),
+        Network::Custom => style(label).white(),
     }
 }
 
@@ -65,19 +69,37 @@ pub async fn start() -> Result<()> {
     println!("{}\n", "-".repeat(40));
 
     // Check network connectivity
-    let network_status = check_connectivity().await;
-    let is_online = network_status == NetworkStatus::Online;
+    let report = check_network().await;
+    let is_online = report.status() == NetworkStatus::Online;
 
     // Display current status
     let config_manager = ConfigManager::new()?;
     let config = config_manager.load()?;
 
-    if is_online {
-        println!("  {}", style("🟢 Online").green());
+    if report.forced_offline {
+        println!("  {}", style("🔴 Offline (forced in settings)").red());
+    } else if is_online {
+        println!(
+            "  {}",
+            style(format!(
+                "🟢 Online ({}/{} endpoints healthy)",
+                report.healthy(),
+                report.endpoints.len()
+            ))
+            .green()
+        );
     } else {
         println!("  {}", style("🔴 Offline").red());
     }
-    println!("  {}", get_network_status(config.default_network));
+    for (url, check) in &report.endpoints {
+        if !matches!(check, EndpointCheck::Healthy { .. }) {
+            println!("     {}", style(format!("{}: {}", url, check)).dim());
+        }
+    }
+    println!("  {}", get_network_status(&config));
+    if let Some(profile) = AppPaths::current().profile() {
+        println!("  {}", style(format!("👤 Profile: {}", profile)).cyan());
+    }
 
     // Check if wallet data file exists and count wallets
     let wallet_file = constants::wallet_file_path();
@@ -115,6 +137,8 @@ pub async fn start() -> Result<()> {
         if is_online {
             options.push(format!("{}  Check Balance", style("💰").bold().green()));
             option_map.push(0);
+            options.push(format!("{}  Portfolio", style("📊").bold().green()));
+            option_map.push(11);
             options.push(format!("{}  Send Funds", style("💸").bold().yellow()));
             option_map.push(1);
             options.push(format!("{}  Bulk Transfer", style("📤").bold().yellow()));
@@ -126,6 +150,8 @@ pub async fn start() -> Result<()> {
         } else {
             options.push(format!("{}  Check Balance {}", style("💰").bold().dim(), style("(offline)").dim()));
             option_map.push(0);
+            options.push(format!("{}  Transaction History {}", style("📜").bold().dim(), style("(offline)").dim()));
+            option_map.push(4);
         }
 
         // Always available options
@@ -177,7 +203,13 @@ pub async fn start() -> Result<()> {
             1 => send_funds().await?,
             2 => bulk_transfer().await?,
             3 => check_transaction_status().await?,
-            4 => show_history().await?,
+            4 => {
+                if is_online {
+                    show_history().await?;
+                } else {
+                    show_offline_history().await?;
+                }
+            },
             5 => wallet_menu().await?,
             6 => token_menu().await?,
             7 => manage_contacts().await?,
@@ -187,6 +219,7 @@ pub async fn start() -> Result<()> {
                 println!("\n👋 Goodbye!");
                 break;
             }
+            11 => show_portfolio().await?,
             _ => unreachable!(),
         }
     }
@@ -2,16 +2,27 @@ use crate::config::ConfigManager;
 use crate::types::network::Network;
 use crate::utils::eth::EthClient;
 use crate::utils::helper::Config;
+use crate::utils::network::{EndpointCheck, check_network};
+use crate::utils::paths::AppPaths;
+use crate::utils::rpc_pool::redact_url;
+use crate::utils::table::TableBuilder;
 use crate::utils::terminal::{self, show_version};
 use anyhow::Result;
 use console::style;
 use dialoguer::{Select, theme::ColorfulTheme};
-use alloy::providers::Provider;
+use crate::utils::rpc::ChainRpc;
 use std::io;
 use std::time::Duration;
 
 /// Helper function to get styled network status
-fn get_network_status(network: &Network) -> String {
+fn get_network_status(config: &crate::config::Config) -> String {
+    if let Some(custom) = config.active_custom() {
+        return style(format!("🔗 {} (custom, chain {})", custom.name, custom.chain_id))
+            .white()
+            .bold()
+            .to_string();
+    }
+    let network = &config.default_network;
     match network {
         Network::Mainnet => style(format!("🌐 {}", network)).green().bold().to_string(),
         Network::Testnet => style(format!("🔧 {}", network)).yellow().bold().to_string(),
@@ -31,8 +42,7 @@ fn get_config_status(has_key: bool) -> String {
 /// Get current block number from the network
 async fn get_block_number(eth_client: &EthClient) -> Result<u64> {
     let block_number = eth_client
-        .provider()
-        .get_block_number()
+        .block_number()
         .await
         .map_err(|_| anyhow::anyhow!("Failed to get block number"))?;
     Ok(block_number)
@@ -41,8 +51,7 @@ async fn get_block_number(eth_client: &EthClient) -> Result<u64> {
 /// Get current gas price from the network
 async fn get_gas_price(eth_client: &EthClient) -> Result<u128> {
     eth_client
-        .provider()
-        .get_gas_price()
+        .gas_price()
         .await
         .map_err(|_| anyhow::anyhow!("Failed to get gas price"))
 }
@@ -69,7 +78,18 @@ async fn show_system_info() -> Result<()> {
 
     println!("\n{}", style("System Information").bold().underlined());
     println!("• Version: {}", style(env!("CARGO_PKG_VERSION")).cyan());
-    println!("• Network: {}", get_network_status(&config.default_network));
+    println!("• Network: {}", get_network_status(&config));
+    let paths = AppPaths::current();
+    println!("• Profile: {}", style(paths.profile_label()).cyan());
+    println!("• Data: {}", style(paths.data_dir().display()).dim());
+    println!("• Config: {}", style(paths.config_dir().display()).dim());
+    let profiles = paths.profiles();
+    if !profiles.is_empty() {
+        println!(
+            "• Named profiles: {}",
+            style(profiles.join(", ")).dim()
+        );
+    }
 
     // Show configuration status
     match config.default_network {
@@ -77,25 +97,34 @@ async fn show_system_info() -> Result<()> {
             let has_key = config
                 .alchemy_mainnet_key
                 .as_ref()
-                .map_or(false, |k| !k.is_empty());
+                .is_some_and(|k| !k.is_empty());
             println!("• Service Configuration: {}", get_config_status(has_key));
         }
         Network::Testnet => {
             let has_key = config
                 .alchemy_testnet_key
                 .as_ref()
-                .map_or(false, |k| !k.is_empty());
+                .is_some_and(|k| !k.is_empty());
             println!("• Service Configuration: {}", get_config_status(has_key));
         }
         _ => {}
     }
+    if let Some(proxy) = &config.http.proxy {
+        println!("• Proxy: {}", style(redact_url(proxy)).cyan());
+    }
+
+    show_connectivity().await;
+    if config.force_offline {
+        println!();
+        return Ok(());
+    }
 
     // Show network details if connected
     println!("\n{}", style("Network Status").bold().underlined());
 
     // Create an EthClient to fetch network info
     let helper_config = Config {
-        network: config.default_network.get_config(),
+        network: config.network_config(),
         wallet: Default::default(),
     };
 
@@ -124,6 +153,8 @@ async fn show_system_info() -> Result<()> {
                 Ok(health) => println!("• Network Health: {}", health),
                 Err(_) => println!("• Network Health: {}", style("Unavailable").red().bold()),
             }
+
+            show_endpoint_pool(&eth_client).await;
         }
         Err(e) => {
             println!("• Network Status: {}", style("Disconnected").red().bold());
@@ -135,6 +166,69 @@ async fn show_system_info() -> Result<()> {
     Ok(())
 }
 
+/// Show whether each endpoint serves the expected chain with a recent block
+async fn show_connectivity() {
+    let report = check_network().await;
+    println!("\n{}", style("Connectivity").bold().underlined());
+    if report.forced_offline {
+        println!("• {}", style("Offline mode is forced in settings").yellow());
+        return;
+    }
+    for (url, check) in &report.endpoints {
+        let line = format!("{}: {}", url, check);
+        match check {
+            EndpointCheck::Healthy { .. } => println!("• {}", style(line).green()),
+            EndpointCheck::Stale { .. } => println!("• {}", style(line).yellow()),
+            _ => println!("• {}", style(line).red()),
+        }
+    }
+}
+
+/// Probe every RPC endpoint of the network and show the pool's health
+async fn show_endpoint_pool(eth_client: &EthClient) {
+    let pool = eth_client.rpc_pool();
+    pool.probe().await;
+    let endpoints = pool.status();
+    let best = endpoints
+        .iter()
+        .enumerate()
+        .filter(|(_, e)| e.available)
+        .min_by(|a, b| a.1.score.total_cmp(&b.1.score))
+        .map(|(index, _)| index);
+
+    println!("\n{}", style("RPC Endpoints").bold().underlined());
+    let mut table = TableBuilder::new();
+    table.add_header(&["", "Endpoint", "Status", "Latency", "Block", "Lag", "OK / Failed"]);
+    for (index, endpoint) in endpoints.iter().enumerate() {
+        let status = if !endpoint.available {
+            "cooling down"
+        } else if endpoint.failures > 0 && endpoint.successes == 0 {
+            "failing"
+        } else {
+            "healthy"
+        };
+        table.add_row(&[
+            if Some(index) == best { "▶" } else { "" },
+            &endpoint.url,
+            status,
+            &endpoint
+                .latency_ms
+                .map(|ms| format!("{:.0} ms", ms))
+                .unwrap_or_else(|| "-".into()),
+            &endpoint.block.map(|b| b.to_string()).unwrap_or_else(|| "-".into()),
+            &endpoint.lag.map(|l| l.to_string()).unwrap_or_else(|| "-".into()),
+            &format!("{} / {}", endpoint.successes, endpoint.failures),
+        ]);
+    }
+    table.print();
+
+    for endpoint in endpoints.iter().filter(|e| !e.available) {
+        if let Some(error) = &endpoint.last_error {
+            println!("  {} {}", style(format!("{}:", endpoint.url)).dim(), style(error).red());
+        }
+    }
+}
+
 /// System menu for various system-related commands
 pub async fn system_menu() -> Result<()> {
     loop {
@@ -1,4 +1,6 @@
-use crate::commands::tokens;
+use crate::commands::discover::{self, DiscoveredToken};
+use crate::commands::tokens::{self, TokenRegistry};
+use crate::utils::token_list;
 use anyhow::Result;
 use console::style;
 use inquire::validator::Validation;
@@ -10,6 +12,9 @@ pub async fn token_menu() -> Result<()> {
             String::from("➕ Add Token"),
             String::from("🗑️ Remove Token"),
             String::from("📋 List Tokens"),
+            String::from("🔍 Discover Tokens"),
+            String::from("📥 Import Token List"),
+            String::from("🔄 Update Token Lists"),
             String::from("🏠 Back to Main Menu"),
         ];
 
@@ -21,6 +26,13 @@ pub async fn token_menu() -> Result<()> {
             "➕ Add Token" => add_token().await?,
             "🗑️ Remove Token" => remove_token().await?,
             "📋 List Tokens" => list_tokens().await?,
+            "🔍 Discover Tokens" => discover_tokens().await?,
+            "📥 Import Token List" => import_list().await?,
+            "🔄 Update Token Lists" => {
+                if let Err(e) = tokens::update_lists(false).await {
+                    eprintln!("\n{} {}", style("❌ Failed to update token lists:").red(), style(e).bold());
+                }
+            }
             _ => break,
         }
     }
@@ -39,10 +51,6 @@ async fn add_token() -> Result<()> {
     .prompt()?
     .to_string();
 
-    let symbol = inquire::Text::new("Token symbol (e.g., USDT):")
-        .with_help_message("Enter the token's ticker symbol")
-        .prompt()?;
-
     let address = inquire::Text::new("Token contract address (0x...):")
         .with_validator(|input: &str| {
             if input.starts_with("0x") && input.len() == 42 {
@@ -55,16 +63,29 @@ async fn add_token() -> Result<()> {
         })
         .prompt()?;
 
-    let decimals = inquire::Text::new("Token decimals (e.g., 18):")
-        .with_default("18")
-        .with_validator(|input: &str| match input.parse::<u8>() {
-            Ok(_) => Ok(Validation::Valid),
-            Err(_) => Ok(Validation::Invalid(
-                "Please enter a valid number (0-255)".into(),
-            )),
-        })
-        .prompt()?
-        .parse::<u8>()?;
+    // Symbol and decimals come from the contract itself
+    let (onchain_symbol, decimals) = match tokens::verify_token(&network, &address, None).await {
+        Ok(info) => info,
+        Err(e) => {
+            eprintln!(
+                "\n{} {}",
+                style("❌ Failed to add token:").red(),
+                style(e).bold()
+            );
+            return Ok(());
+        }
+    };
+    println!(
+        "{} {} with {} decimals",
+        style("Contract reports").dim(),
+        style(&onchain_symbol).bold(),
+        decimals
+    );
+
+    let symbol = inquire::Text::new("Token symbol:")
+        .with_default(&onchain_symbol)
+        .with_help_message("Symbol to register the token under")
+        .prompt()?;
 
     // Save the token to the user's token list
     match tokens::add_token(&network, &symbol, &address, decimals) {
@@ -152,11 +173,25 @@ async fn list_tokens() -> Result<()> {
             if tokens.is_empty() {
                 println!("\nNo tokens found");
             } else {
-                println!("\n{:<15} {:<42} DECIMALS", "SYMBOL", "ADDRESS");
-                println!("{}", "-".repeat(70));
+                let list_names = TokenRegistry::load()
+                    .map(|registry| token_list::list_names(&registry))
+                    .unwrap_or_default();
+                println!("\n{:<15} {:<42} {:<9} SOURCE", "SYMBOL", "ADDRESS", "DECIMALS");
+                println!("{}", "-".repeat(85));
 
                 for (symbol, info) in tokens {
-                    println!("{:<15} {:<42} {}", symbol, info.address, info.decimals);
+                    let source = info
+                        .list
+                        .as_ref()
+                        .map(|list| list_names.get(list).cloned().unwrap_or_else(|| list.clone()))
+                        .unwrap_or_default();
+                    println!(
+                        "{:<15} {:<42} {:<9} {}",
+                        symbol,
+                        info.address,
+                        info.decimals,
+                        style(source).dim()
+                    );
                 }
             }
         }
@@ -171,3 +206,76 @@ async fn list_tokens() -> Result<()> {
 
     Ok(())
 }
+
+async fn import_list() -> Result<()> {
+    println!("\n{}", style("📥 Import Token List").bold());
+    println!("{}", "=".repeat(30));
+
+    let source = inquire::Text::new("Token list URL or file:")
+        .with_help_message("A list in the tokenlists.org format; only RSK tokens (chain 30 and 31) are imported")
+        .prompt()?;
+
+    if let Err(e) = tokens::import_list(source.trim(), false).await {
+        eprintln!(
+            "\n{} {}",
+            style("❌ Failed to import token list:").red(),
+            style(e).bold()
+        );
+    }
+    Ok(())
+}
+
+async fn discover_tokens() -> Result<()> {
+    println!("\n{}", style("🔍 Discover Tokens").bold());
+    println!("{}", "=".repeat(30));
+
+    let network = inquire::Select::new(
+        "Select network:",
+        vec![String::from("mainnet"), String::from("testnet")],
+    )
+    .prompt()?;
+
+    let found = match discover::current_wallet_address() {
+        Ok(address) => discover::discover(&network, address, None).await,
+        Err(e) => Err(e),
+    };
+    let found = match found {
+        Ok(found) => found,
+        Err(e) => {
+            eprintln!(
+                "\n{} {}",
+                style("❌ Failed to discover tokens:").red(),
+                style(e).bold()
+            );
+            return Ok(());
+        }
+    };
+    discover::print_discovered(&found);
+
+    let addable: Vec<&DiscoveredToken> = found.iter().filter(|t| t.can_add()).collect();
+    if addable.is_empty() {
+        return Ok(());
+    }
+    let options: Vec<String> = addable
+        .iter()
+        .map(|t| match t.is_suspicious() {
+            true => format!("⚠️  {}", t.label()),
+            false => t.label(),
+        })
+        .collect();
+    // Flagged tokens are offered but never preselected
+    let defaults: Vec<usize> = addable
+        .iter()
+        .enumerate()
+        .filter(|(_, t)| !t.is_suspicious())
+        .map(|(i, _)| i)
+        .collect();
+    let selected = inquire::MultiSelect::new("Add to the token registry:", options)
+        .with_default(&defaults)
+        .raw_prompt()?;
+    let selected: Vec<&DiscoveredToken> = selected.iter().map(|option| addable[option.index]).collect();
+    if !selected.is_empty() {
+        discover::add_discovered(&network, &selected)?;
+    }
+    Ok(())
+}
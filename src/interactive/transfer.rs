@@ -7,6 +7,7 @@ use crate::{
     config::ConfigManager,
     interactive::transfer_preview,
 };
+use alloy::primitives::{Address, U256, utils::parse_units};
 use anyhow::{Context, Result, anyhow};
 use colored::*;
 use console::style;
@@ -19,7 +20,7 @@ pub async fn send_funds() -> Result<()> {
 
     // Get the current network from config
     let config = ConfigManager::new()?.load()?;
-    let network = config.default_network.to_string().to_lowercase();
+    let network = config.network_name();
     println!("Using network: {}", network);
 
     // Ask user if they want to select from contacts or enter address manually
@@ -84,6 +85,7 @@ pub async fn send_funds() -> Result<()> {
             crate::commands::tokens::TokenInfo {
                 address: "0x0000000000000000000000000000000000000000".to_string(),
                 decimals: 18,
+                ..Default::default()
             },
         ),
     );
@@ -149,33 +151,49 @@ pub async fn send_funds() -> Result<()> {
         .unwrap_or(&display_name)
         .to_string();
 
+    let decimals = token_info.decimals;
     let amount = loop {
         let input = inquire::Text::new(&format!("Amount of {} to send:", token_symbol))
             .with_help_message("Enter the amount to send")
-            .with_validator(|input: &str| {
-                if input.parse::<f64>().is_ok() {
+            .with_validator(move |input: &str| {
+                if parse_units(input.trim(), decimals).is_ok() {
                     Ok(Validation::Valid)
                 } else {
-                    Ok(Validation::Invalid("Please enter a valid number".into()))
+                    Ok(Validation::Invalid(
+                        format!("Please enter a valid amount with at most {} decimals", decimals).into(),
+                    ))
                 }
             })
             .prompt()?;
 
-        // Convert RBTC to wei for preview
-        let rbtc: f64 = input.parse().unwrap_or(0.0);
-        let wei = (rbtc * 1e18) as u128;
+        // Amount in the token's smallest unit for the preview
+        let units: U256 = parse_units(input.trim(), decimals)
+            .map_err(|e| anyhow!("Invalid amount: {}", e))?
+            .into();
 
         // Show preview and ask for confirmation
+        let preview_token = if token_info.address == "0x0000000000000000000000000000000000000000" {
+            None
+        } else {
+            Some(
+                token_info
+                    .address
+                    .parse::<Address>()
+                    .map_err(|e| anyhow!("Invalid token address: {}", e))?,
+            )
+        };
         let confirmed = transfer_preview::show_transaction_preview(
             &to,
-            &wei.to_string(),
-            config.default_network,
-            &display_name,
+            units,
+            decimals,
+            &config.network_label(),
+            &token_symbol,
+            preview_token,
         )
         .await?;
 
         if confirmed {
-            break input;
+            break input.trim().to_string();
         } else {
             println!("Transaction cancelled. Please enter a new amount or press Ctrl+C to exit.");
         }
@@ -1,51 +1,45 @@
 use crate::{
     config::ConfigManager,
-    types::network::{Network, NetworkConfig},
+    types::wallet::WalletData,
     utils::{
-        eth::EthClient,
+        constants,
+        eth::{EthClient, TransferSimulation},
         helper::{Config as HelperConfig, WalletConfig},
     },
 };
 use anyhow::{Result, anyhow};
 use console::style;
 use dialoguer::Confirm;
-use alloy::primitives::{Address, U256};
-use alloy::providers::Provider;
-use std::str::FromStr;
-
-/// Helper function to convert wei to RBTC
-fn convert_wei_to_rbtc(wei: U256) -> f64 {
-    // 1 RBTC = 10^18 wei
-    let wei_f64 = wei.to::<u128>() as f64;
-    wei_f64 / 1_000_000_000_000_000_000.0
-}
+use alloy::primitives::{Address, U256, utils::format_units};
+use crate::utils::rpc::ChainRpc;
+use std::fs;
 
-/// Displays transaction details and asks for confirmation
-pub async fn show_transaction_preview(to: &str, amount: &str, network: Network, token_symbol: &str) -> Result<bool> {
+/// Displays transaction details, simulates the transfer and asks for
+/// confirmation. `amount` is in the smallest unit of the asset, which has
+/// `decimals` decimals.
+pub async fn show_transaction_preview(
+    to: &str,
+    amount: U256,
+    decimals: u8,
+    network: &str,
+    token_symbol: &str,
+    token_address: Option<Address>,
+) -> Result<bool> {
     println!("\n{}", style("Transaction Preview").bold().underlined());
     println!("• To: {}", style(to).cyan());
 
-    // Parse amount
-    let amount_wei =
-        U256::from_str(amount).map_err(|e| anyhow::anyhow!("Invalid amount format: {}", e))?;
-
-    // Convert to token units for display
-    let amount_tokens = convert_wei_to_rbtc(amount_wei);
+    let amount_tokens = format_units(amount, decimals)?;
     println!(
-        "• Amount: {} {} ({} wei)",
-        style(amount_tokens).green(),
+        "• Amount: {} {} ({} base units)",
+        style(&amount_tokens).green(),
         style(token_symbol).green(),
-        style(amount_wei).dim()
+        style(amount).dim()
     );
 
     // Get current config and initialize EthClient
     let config = ConfigManager::new()?.load()?;
     let helper_config = HelperConfig {
-        network: NetworkConfig {
-            name: config.default_network.to_string(),
-            rpc_url: config.default_network.get_config().rpc_url,
-            explorer_url: config.default_network.get_config().explorer_url,
-        },
+        network: config.network_config(),
         wallet: WalletConfig {
             current_wallet_address: None,
             private_key: None,
@@ -56,8 +50,7 @@ pub async fn show_transaction_preview(to: &str, amount: &str, network: Network,
 
     // Fetch current gas price from the network
     let gas_price = eth_client
-        .provider()
-        .get_gas_price()
+        .gas_price()
         .await
         .map_err(|e| anyhow!("Failed to get gas price: {}", e))?;
 
@@ -65,13 +58,18 @@ pub async fn show_transaction_preview(to: &str, amount: &str, network: Network,
     let to_address: Address = to
         .parse()
         .map_err(|_| anyhow!("Invalid recipient address"))?;
-    let estimated_gas = eth_client
-        .estimate_gas(
-            to_address, amount_wei, None, // No token address for native transfers
-        )
-        .await?;
+    let sender = load_sender_address()?;
+    // A transfer that reverts cannot be estimated, so fall back to a plain
+    // transfer's gas and let the simulation below explain the failure
+    let estimated_gas = match eth_client
+        .estimate_gas(sender, to_address, amount, token_address)
+        .await
+    {
+        Ok(gas) => gas,
+        Err(_) => U256::from(21_000),
+    };
     let gas_cost = U256::from(gas_price).checked_mul(estimated_gas).unwrap_or_default();
-    let gas_cost_rbtc = convert_wei_to_rbtc(gas_cost);
+    let gas_cost_rbtc = format_units(gas_cost, 18)?;
 
     println!("• Network: {}", style(network).cyan());
     println!(
@@ -79,11 +77,11 @@ pub async fn show_transaction_preview(to: &str, amount: &str, network: Network,
         style(convert_wei_to_gwei(U256::from(gas_price))).yellow()
     );
     println!("• Estimated Gas: {}", style(estimated_gas).yellow());
-    println!("• Estimated Fee: {} RBTC", style(gas_cost_rbtc).red());
+    println!("• Estimated Fee: {} RBTC", style(&gas_cost_rbtc).red());
 
     if token_symbol == "RBTC" {
-        let total_amount = amount_wei.checked_add(gas_cost).unwrap_or(amount_wei);
-        let total_rbtc = convert_wei_to_rbtc(total_amount);
+        let total_amount = amount.checked_add(gas_cost).unwrap_or(amount);
+        let total_rbtc = format_units(total_amount, 18)?;
         println!(
             "• Total (Amount + Fee): {} RBTC",
             style(total_rbtc).green().bold()
@@ -91,12 +89,49 @@ pub async fn show_transaction_preview(to: &str, amount: &str, network: Network,
     } else {
         println!(
             "• Total: {} {} + {} RBTC (gas fee)",
-            style(amount_tokens).green().bold(),
+            style(&amount_tokens).green().bold(),
             style(token_symbol).green().bold(),
-            style(gas_cost_rbtc).red()
+            style(&gas_cost_rbtc).red()
         );
     }
 
+    // Simulate the transfer against the pending block before asking to send it
+    let simulation = match sender {
+        Some(from) => Some(
+            eth_client
+                .simulate_transfer(from, to_address, amount, token_address, gas_cost)
+                .await,
+        ),
+        None => None,
+    };
+
+    match simulation {
+        Some(Ok(simulation)) => {
+            show_simulation(&simulation, token_address.is_none());
+            if !simulation.success {
+                let send_anyway = Confirm::new()
+                    .with_prompt("The transaction is expected to fail. Send it anyway?")
+                    .default(false)
+                    .interact()?;
+                if !send_anyway {
+                    return Ok(false);
+                }
+            }
+        }
+        Some(Err(e)) => {
+            println!(
+                "\n{}",
+                style(format!("⚠️  Could not simulate transaction: {}", e)).yellow()
+            );
+        }
+        None => {
+            println!(
+                "\n{}",
+                style("⚠️  No default wallet selected, skipping simulation").yellow()
+            );
+        }
+    }
+
     // Ask for confirmation
     let confirm = Confirm::new()
         .with_prompt("\nDo you want to send this transaction?")
@@ -106,6 +141,64 @@ pub async fn show_transaction_preview(to: &str, amount: &str, network: Network,
     Ok(confirm)
 }
 
+/// Prints the simulation outcome and the expected balance changes
+fn show_simulation(simulation: &TransferSimulation, is_native: bool) {
+    println!("\n{}", style("Simulation").bold().underlined());
+    if simulation.success {
+        println!("• Result: {}", style("✅ Succeeds against pending block").green());
+    } else {
+        println!("• Result: {}", style("❌ Reverts against pending block").red().bold());
+        if let Some(reason) = &simulation.revert_reason {
+            println!("• Reason: {}", style(reason).red());
+        }
+        if is_native && simulation.recipient_is_contract {
+            println!(
+                "{}",
+                style("⚠️  The recipient is a contract that does not accept RBTC").yellow()
+            );
+        }
+        return;
+    }
+
+    if simulation.recipient_is_contract {
+        println!("• Recipient: {}", style("contract").yellow());
+    }
+
+    println!("\n{}", style("Expected Balance Changes").bold());
+    for change in &simulation.balance_changes {
+        let before = format_units(change.before, change.decimals).unwrap_or_default();
+        let after = format_units(change.after, change.decimals).unwrap_or_default();
+        let delta = if change.after >= change.before {
+            style(format!(
+                "+{}",
+                format_units(change.after - change.before, change.decimals).unwrap_or_default()
+            ))
+            .green()
+        } else {
+            style(format!(
+                "-{}",
+                format_units(change.before - change.after, change.decimals).unwrap_or_default()
+            ))
+            .red()
+        };
+        println!(
+            "• 0x{:x}: {} → {} {} ({})",
+            change.holder, before, after, change.asset, delta
+        );
+    }
+}
+
+/// Returns the address of the default wallet, if one is selected
+fn load_sender_address() -> Result<Option<Address>> {
+    let wallet_file = constants::wallet_file_path();
+    if !wallet_file.exists() {
+        return Ok(None);
+    }
+    let data = fs::read_to_string(&wallet_file)?;
+    let wallet_data: WalletData = serde_json::from_str(&data)?;
+    Ok(wallet_data.get_current_wallet().map(|w| w.address))
+}
+
 /// Helper function to convert wei to Gwei
 fn convert_wei_to_gwei(wei: U256) -> f64 {
     let gwei = wei.to::<u128>() as f64 / 1_000_000_000.0;
@@ -2,7 +2,7 @@ use anyhow::Result;
 use console::style;
 use dialoguer::Input;
 
-use crate::{commands::tx::TxCommand, config::ConfigManager, types::network::Network, interactive::config::show_config_menu};
+use crate::{commands::{trace::TraceCommand, tx::TxCommand}, config::ConfigManager, types::network::Network, interactive::config::show_config_menu};
 
 /// Interactive transaction status checker
 pub async fn check_transaction_status() -> Result<()> {
@@ -55,6 +55,23 @@ pub async fn check_transaction_status() -> Result<()> {
 
         match cmd.execute().await {
             Ok(_) => {
+                // Offer the internal call tree for nodes with the debug module
+                let show_trace = dialoguer::Confirm::new()
+                    .with_prompt("\nShow call trace? (requires a node with debug_traceTransaction)")
+                    .default(false)
+                    .interact()?;
+
+                if show_trace {
+                    let trace_cmd = TraceCommand {
+                        tx_hash: tx_hash.clone(),
+                        rpc_url: None,
+                    };
+                    if let Err(e) = trace_cmd.execute().await {
+                        println!("\n{}", style("❌ Could not trace transaction:").red());
+                        println!("{}", e);
+                    }
+                }
+
                 // Offer to check another transaction
                 let check_another = dialoguer::Confirm::new()
                     .with_prompt("\nCheck another transaction?")
@@ -1,7 +1,9 @@
 #![allow(warnings)]
 use anyhow::{Result, anyhow};
+use clap::Parser;
 use dotenvy::dotenv;
 use std::env;
+use std::path::PathBuf;
 
 mod api;
 mod commands;
@@ -11,21 +13,38 @@ mod setup;
 mod types;
 mod utils;
 
+/// Rootstock wallet. Runs in interactive mode; the options only choose
+/// where its files live.
+#[derive(Parser, Debug)]
+#[command(version)]
+struct Cli {
+    /// Keep data and config under this directory instead of the platform defaults
+    #[arg(long, env = "RSK_CLI_HOME")]
+    data_dir: Option<PathBuf>,
+
+    /// Profile with its own wallets, config, tokens and contacts
+    #[arg(long, env = "RSK_CLI_PROFILE")]
+    profile: Option<String>,
+}
+
 #[tokio::main]
 async fn main() -> Result<()> {
-    // Check if any command line arguments were provided
-    if env::args().count() > 1 {
-        eprintln!("This program only runs in interactive mode. Please run without any arguments.");
-        eprintln!("Usage: cargo run");
-        std::process::exit(1);
+    // Load environment variables from .env file if it exists, so it can
+    // also set RSK_CLI_HOME and RSK_CLI_PROFILE
+    dotenv().ok();
+
+    let cli = Cli::parse();
+    match utils::paths::AppPaths::new(cli.data_dir, cli.profile.as_deref()) {
+        Ok(paths) => utils::paths::AppPaths::install(paths),
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(2);
+        }
     }
 
     // Initialize logging
     env_logger::init();
 
-    // Load environment variables from .env file if it exists
-    dotenv().ok();
-
     // Ensure wallet is configured
     if let Err(e) = setup::ensure_configured().await {
         eprintln!("Failed to configure wallet: {}", e);
@@ -210,14 +210,13 @@ impl Contact {
         if self.created_at.timestamp() < 0 {
             return Err(anyhow::anyhow!("Created at timestamp cannot be negative"));
         }
-        if let Some(stats) = &self.transaction_stats {
-            if let Some(last_tx) = stats.last_transaction {
-                if last_tx.timestamp() > chrono::Local::now().timestamp() {
-                    return Err(anyhow::anyhow!(
-                        "Last transaction timestamp cannot be in the future"
-                    ));
-                }
-            }
+        if let Some(stats) = &self.transaction_stats
+            && let Some(last_tx) = stats.last_transaction
+            && last_tx.timestamp() > chrono::Local::now().timestamp()
+        {
+            return Err(anyhow::anyhow!(
+                "Last transaction timestamp cannot be in the future"
+            ));
         }
 
         if self.created_at.timestamp() < 1_000_000_000 {
@@ -290,3 +289,141 @@ impl fmt::Display for Contact {
         Ok(())
     }
 }
+
+/// Amount or token one member of a group gets instead of the group default
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MemberOverride {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub amount: Option<String>,
+    /// Token address; `None` keeps the group's token
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+}
+
+/// Recipients paid together, such as contributors paid every month.
+/// Members are the listed addresses plus every contact carrying `tag`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContactGroup {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub members: Vec<Address>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tag: Option<String>,
+    /// Amount sent to each member unless overridden
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_amount: Option<String>,
+    /// Token address sent by default; `None` sends RBTC
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_token: Option<String>,
+    #[serde(default, skip_serializing_if = "std::collections::BTreeMap::is_empty")]
+    pub overrides: std::collections::BTreeMap<Address, MemberOverride>,
+}
+
+/// A group member with the amount and token it should be sent
+#[derive(Debug, Clone, PartialEq)]
+pub struct GroupRecipient {
+    /// Contact name, or the address when it is no longer a contact
+    pub name: String,
+    pub address: Address,
+    pub amount: Option<String>,
+    pub token: Option<String>,
+}
+
+impl ContactGroup {
+    pub fn new(name: String) -> Self {
+        Self {
+            name,
+            members: Vec::new(),
+            tag: None,
+            default_amount: None,
+            default_token: None,
+            overrides: Default::default(),
+        }
+    }
+
+    /// Members in order, listed ones first, each address once
+    pub fn recipients(&self, contacts: &[Contact]) -> Vec<GroupRecipient> {
+        let tagged = contacts.iter().filter(|c| {
+            self.tag
+                .as_ref()
+                .is_some_and(|tag| c.tags.iter().any(|t| t.eq_ignore_ascii_case(tag)))
+        });
+        let mut addresses: Vec<Address> = self.members.clone();
+        for contact in tagged {
+            if !addresses.contains(&contact.address) {
+                addresses.push(contact.address);
+            }
+        }
+
+        addresses
+            .into_iter()
+            .map(|address| {
+                let name = contacts
+                    .iter()
+                    .find(|c| c.address == address)
+                    .map(|c| c.name.clone())
+                    .unwrap_or_else(|| address.to_string());
+                let member = self.overrides.get(&address).cloned().unwrap_or_default();
+                GroupRecipient {
+                    name,
+                    address,
+                    amount: member.amount.or_else(|| self.default_amount.clone()),
+                    token: member.token.or_else(|| self.default_token.clone()),
+                }
+            })
+            .collect()
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        if self.name.trim().is_empty() {
+            return Err(anyhow::anyhow!("Group name cannot be empty"));
+        }
+        if self.members.is_empty() && self.tag.is_none() {
+            return Err(anyhow::anyhow!("A group needs members or a tag"));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_group_recipients_apply_overrides_and_tags() {
+        let contact = |name: &str, byte: u8, tags: &[&str]| {
+            Contact::new(
+                name.to_string(),
+                Address::with_last_byte(byte),
+                None,
+                tags.iter().map(|t| t.to_string()).collect(),
+            )
+        };
+        let contacts = vec![
+            contact("Ana", 1, &["Contributor"]),
+            contact("Ben", 2, &[]),
+            contact("Cy", 3, &["contributor"]),
+        ];
+        let mut group = ContactGroup::new("monthly".to_string());
+        group.members = vec![Address::with_last_byte(2), Address::with_last_byte(3), Address::with_last_byte(9)];
+        group.tag = Some("contributor".to_string());
+        group.default_amount = Some("10".to_string());
+        group.overrides.insert(
+            Address::with_last_byte(1),
+            MemberOverride {
+                amount: Some("25".to_string()),
+                token: Some("0x0000000000000000000000000000000000000070".to_string()),
+            },
+        );
+
+        let recipients = group.recipients(&contacts);
+        let names: Vec<&str> = recipients.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(names[..3], ["Ben", "Cy", &Address::with_last_byte(9).to_string()]);
+        assert_eq!(names[3], "Ana");
+        assert_eq!(recipients[0].amount.as_deref(), Some("10"));
+        assert_eq!(recipients[0].token, None);
+        assert_eq!(recipients[3].amount.as_deref(), Some("25"));
+        assert!(recipients[3].token.is_some());
+        assert!(ContactGroup::new("empty".to_string()).validate().is_err());
+    }
+}
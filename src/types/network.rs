@@ -1,3 +1,6 @@
+use crate::utils::rpc_pool::RpcPool;
+use crate::utils::rpc::ChainRpc;
+use anyhow::{Result, anyhow};
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -5,10 +8,139 @@ pub struct NetworkConfig {
     pub name: String,
     pub rpc_url: String,
     pub explorer_url: String,
+    /// Endpoints to fail over to when `rpc_url` is unavailable
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fallback_rpc_urls: Vec<String>,
+}
+
+impl NetworkConfig {
+    /// All endpoints in order of preference
+    pub fn rpc_urls(&self) -> Vec<String> {
+        std::iter::once(self.rpc_url.clone())
+            .chain(self.fallback_rpc_urls.iter().cloned())
+            .collect()
+    }
 }
 
 use std::fmt;
 
+/// Names reserved for the built-in networks
+const BUILT_IN_NAMES: &[&str] = &[
+    "mainnet",
+    "testnet",
+    "regtest",
+    "custom",
+    "alchemy-mainnet",
+    "alchemy-testnet",
+    "rootstock-mainnet",
+    "rootstock-testnet",
+];
+
+fn default_native_symbol() -> String {
+    "RBTC".to_string()
+}
+
+/// A user-defined network, such as a private rskj node or a regtest node
+/// on a non-default port
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CustomNetwork {
+    pub name: String,
+    /// RPC endpoints, the first one is preferred
+    pub rpc_urls: Vec<String>,
+    pub chain_id: u64,
+    /// Transaction page URL with a `{hash}` placeholder
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub explorer_url: Option<String>,
+    #[serde(default = "default_native_symbol")]
+    pub native_symbol: String,
+}
+
+impl CustomNetwork {
+    /// Checks that a name can be used for a custom network. Names key local
+    /// data directories, so like the built-in chain names they are limited
+    /// to lowercase letters, digits, `-` and `_`.
+    pub fn validate_name(name: &str) -> Result<()> {
+        if name.is_empty() {
+            return Err(anyhow!("Network name cannot be empty"));
+        }
+        if !name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_')
+        {
+            return Err(anyhow!(
+                "Network name may only contain lowercase letters, digits, '-' and '_'"
+            ));
+        }
+        if BUILT_IN_NAMES.contains(&name) {
+            return Err(anyhow!("'{}' is the name of a built-in network", name));
+        }
+        Ok(())
+    }
+
+    pub fn rpc_url(&self) -> &str {
+        self.rpc_urls.first().map(String::as_str).unwrap_or_default()
+    }
+
+    /// Link to a transaction in the network's explorer, if one is configured
+    pub fn explorer_tx_url(&self, tx_hash: &str) -> Option<String> {
+        let template = self.explorer_url.as_deref()?;
+        Some(if template.contains("{hash}") {
+            template.replace("{hash}", tx_hash)
+        } else {
+            format!("{}/tx/{}", template.trim_end_matches('/'), tx_hash)
+        })
+    }
+
+    pub fn get_config(&self) -> NetworkConfig {
+        NetworkConfig {
+            name: self.name.clone(),
+            rpc_url: self.rpc_url().to_string(),
+            explorer_url: self.explorer_url.clone().unwrap_or_default(),
+            fallback_rpc_urls: self.rpc_urls.iter().skip(1).cloned().collect(),
+        }
+    }
+
+    /// Checks every RPC URL answers `eth_chainId` with the configured chain id
+    pub async fn verify(&self) -> Result<()> {
+        if self.rpc_urls.is_empty() {
+            return Err(anyhow!("At least one RPC URL is required"));
+        }
+        for url in &self.rpc_urls {
+            let chain_id = fetch_chain_id(url).await?;
+            if chain_id != self.chain_id {
+                return Err(anyhow!(
+                    "{} reports chain id {}, expected {}",
+                    url,
+                    chain_id,
+                    self.chain_id
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Endpoints used for a built-in network instead of the hosted ones, such
+/// as a private node or a local mock node
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct EndpointOverride {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub rpc_urls: Vec<String>,
+    /// Alchemy API base URL, without the API key
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub alchemy_url: Option<String>,
+}
+
+/// Asks an RPC endpoint for its chain id
+pub async fn fetch_chain_id(url: &str) -> Result<u64> {
+    url::Url::parse(url).map_err(|e| anyhow!("Invalid RPC URL '{}': {}", url, e))?;
+    RpcPool::shared(&[url.to_string()])
+        .provider()
+        .chain_id()
+        .await
+        .map_err(|e| anyhow!("Failed to get chain id from {}: {}", url, e))
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Network {
     Mainnet,
@@ -18,6 +150,8 @@ pub enum Network {
     AlchemyTestnet,
     RootStockMainnet,
     RootStockTestnet,
+    /// A user-defined network, see `Config::active_custom_network`
+    Custom,
 }
 
 impl fmt::Display for Network {
@@ -30,48 +164,95 @@ impl fmt::Display for Network {
             Network::AlchemyTestnet => write!(f, "Alchemy Testnet"),
             Network::RootStockMainnet => write!(f, "Rootstock Mainnet"),
             Network::RootStockTestnet => write!(f, "Rootstock Testnet"),
+            Network::Custom => write!(f, "Custom"),
         }
     }
 }
 
 impl Network {
+    /// Short name of the underlying chain (mainnet, testnet or regtest),
+    /// used to key locally stored data. Custom networks are keyed by their
+    /// own name instead, see `Config::network_name`.
+    pub fn chain_name(&self) -> &'static str {
+        match self {
+            Network::Mainnet | Network::AlchemyMainnet | Network::RootStockMainnet => "mainnet",
+            Network::Testnet | Network::AlchemyTestnet | Network::RootStockTestnet => "testnet",
+            Network::Regtest => "regtest",
+            Network::Custom => "custom",
+        }
+    }
+
+    /// EIP-155 chain id of a built-in network
+    pub fn chain_id(&self) -> Option<u64> {
+        match self {
+            Network::Mainnet | Network::AlchemyMainnet | Network::RootStockMainnet => Some(30),
+            Network::Testnet | Network::AlchemyTestnet | Network::RootStockTestnet => Some(31),
+            Network::Regtest => Some(33),
+            Network::Custom => None,
+        }
+    }
+
+    /// Age beyond which a node's latest block means it is not keeping up.
+    /// Local and custom chains may only mine on demand, so they have none.
+    pub fn max_block_age(&self) -> Option<std::time::Duration> {
+        match self {
+            Network::Regtest | Network::Custom => None,
+            _ => Some(std::time::Duration::from_secs(10 * 60)),
+        }
+    }
+
     pub fn get_config(&self) -> NetworkConfig {
         match self {
             Network::Mainnet => NetworkConfig {
                 name: "RSK Mainnet".to_string(),
                 rpc_url: "https://public-node.rsk.co".to_string(),
                 explorer_url: "https://explorer.rsk.co".to_string(),
+                fallback_rpc_urls: Vec::new(),
             },
             Network::Testnet => NetworkConfig {
                 name: "RSK Testnet".to_string(),
                 rpc_url: "https://public-node.testnet.rsk.co".to_string(),
                 explorer_url: "https://explorer.testnet.rsk.co".to_string(),
+                fallback_rpc_urls: Vec::new(),
             },
             Network::Regtest => NetworkConfig {
                 name: "RSK Regtest".to_string(),
                 rpc_url: "http://localhost:4444".to_string(),
                 explorer_url: "".to_string(),
+                fallback_rpc_urls: Vec::new(),
             },
             // Legacy network types - use public nodes by default
             Network::AlchemyMainnet => NetworkConfig {
                 name: "RSK Mainnet".to_string(),
                 rpc_url: "https://public-node.rsk.co".to_string(),
                 explorer_url: "https://explorer.rsk.co".to_string(),
+                fallback_rpc_urls: Vec::new(),
             },
             Network::AlchemyTestnet => NetworkConfig {
                 name: "RSK Testnet".to_string(),
                 rpc_url: "https://public-node.testnet.rsk.co".to_string(),
                 explorer_url: "https://explorer.testnet.rsk.co".to_string(),
+                fallback_rpc_urls: Vec::new(),
             },
             Network::RootStockMainnet => NetworkConfig {
                 name: "RSK Mainnet".to_string(),
                 rpc_url: "https://public-node.rsk.co".to_string(),
                 explorer_url: "https://explorer.rsk.co".to_string(),
+                fallback_rpc_urls: Vec::new(),
             },
             Network::RootStockTestnet => NetworkConfig {
                 name: "RSK Testnet".to_string(),
                 rpc_url: "https://public-node.testnet.rsk.co".to_string(),
                 explorer_url: "https://explorer.testnet.rsk.co".to_string(),
+                fallback_rpc_urls: Vec::new(),
+            },
+            // Endpoints of custom networks live in the config, see
+            // `Config::network_config`
+            Network::Custom => NetworkConfig {
+                name: "Custom".to_string(),
+                rpc_url: String::new(),
+                explorer_url: String::new(),
+                fallback_rpc_urls: Vec::new(),
             },
         }
     }
@@ -82,39 +263,46 @@ impl Network {
         rsk_api_key: Option<&str>,
         alchemy_api_key: Option<&str>,
     ) -> String {
-        match self {
-            Network::Mainnet | Network::AlchemyMainnet | Network::RootStockMainnet => {
-                // Prefer RSK RPC API if available
-                if let Some(key) = rsk_api_key {
-                    format!("https://rpc.rootstock.io/{}", key)
-                }
-                // Fall back to Alchemy if available
-                else if let Some(key) = alchemy_api_key {
-                    format!("https://rootstock-mainnet.g.alchemy.com/v2/{}", key)
-                }
-                // Default to public node
-                else {
-                    "https://public-node.rsk.co".to_string()
-                }
-            }
-            Network::Testnet | Network::AlchemyTestnet | Network::RootStockTestnet => {
-                // Prefer RSK RPC API if available
-                if let Some(key) = rsk_api_key {
-                    format!("https://rpc.testnet.rootstock.io/{}", key)
-                }
-                // Fall back to Alchemy if available
-                else if let Some(key) = alchemy_api_key {
-                    format!("https://rootstock-testnet.g.alchemy.com/v2/{}", key)
-                }
-                // Default to public node
-                else {
-                    "https://public-node.testnet.rsk.co".to_string()
-                }
-            }
-            Network::Regtest => "http://localhost:4444".to_string(),
+        self.get_rpc_urls_with_keys(rsk_api_key, alchemy_api_key)
+            .into_iter()
+            .next()
+            .unwrap_or_default()
+    }
+
+    /// Every usable RPC URL in order of preference: RSK RPC, then Alchemy
+    /// when keys are configured, and always the public node last
+    pub fn get_rpc_urls_with_keys(
+        &self,
+        rsk_api_key: Option<&str>,
+        alchemy_api_key: Option<&str>,
+    ) -> Vec<String> {
+        let (rsk_host, alchemy_host, public_node) = match self {
+            Network::Mainnet | Network::AlchemyMainnet | Network::RootStockMainnet => (
+                "rpc.rootstock.io",
+                "rootstock-mainnet.g.alchemy.com",
+                "https://public-node.rsk.co",
+            ),
+            Network::Testnet | Network::AlchemyTestnet | Network::RootStockTestnet => (
+                "rpc.testnet.rootstock.io",
+                "rootstock-testnet.g.alchemy.com",
+                "https://public-node.testnet.rsk.co",
+            ),
+            Network::Regtest => return vec!["http://localhost:4444".to_string()],
+            Network::Custom => return Vec::new(),
+        };
+
+        let mut urls = Vec::new();
+        if let Some(key) = rsk_api_key {
+            urls.push(format!("https://{}/{}", rsk_host, key));
+        }
+        if let Some(key) = alchemy_api_key {
+            urls.push(format!("https://{}/v2/{}", alchemy_host, key));
         }
+        urls.push(public_node.to_string());
+        urls
     }
 
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(s: &str) -> Option<Self> {
         match s.to_lowercase().as_str() {
             "mainnet" => Some(Network::Mainnet),
@@ -128,3 +316,34 @@ impl Network {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_custom_network_names_and_explorer_links() {
+        assert!(CustomNetwork::validate_name("devnet-2").is_ok());
+        assert!(CustomNetwork::validate_name("DevNet").is_err());
+        assert!(CustomNetwork::validate_name("testnet").is_err());
+        assert!(CustomNetwork::validate_name("my net").is_err());
+
+        let mut network = CustomNetwork {
+            name: "devnet".to_string(),
+            rpc_urls: vec!["http://localhost:4444".to_string()],
+            chain_id: 33,
+            explorer_url: Some("https://scan.example/transaction/{hash}?net=dev".to_string()),
+            native_symbol: default_native_symbol(),
+        };
+        assert_eq!(
+            network.explorer_tx_url("0xab").as_deref(),
+            Some("https://scan.example/transaction/0xab?net=dev")
+        );
+        network.explorer_url = Some("https://scan.example/".to_string());
+        assert_eq!(
+            network.explorer_tx_url("0xab").as_deref(),
+            Some("https://scan.example/tx/0xab")
+        );
+        assert_eq!(network.get_config().rpc_url, "http://localhost:4444");
+    }
+}
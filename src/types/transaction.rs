@@ -1,8 +1,7 @@
-use crate::utils::alchemy::AlchemyClient;
 use anyhow::{Result, anyhow};
 use chrono::{DateTime, Utc};
 use alloy::primitives::{Address, Bytes, B256, U64, U256};
-use alloy::providers::{Provider, ProviderBuilder};
+use crate::utils::rpc::ChainRpc;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::str::FromStr;
@@ -61,6 +60,8 @@ pub struct TransactionReceipt {
     pub block_number: Option<U256>,
     pub block_hash: Option<B256>,
     pub cumulative_gas_used: U256,
+    pub effective_gas_price: U256,
+    pub logs: Vec<alloy::rpc::types::Log>,
 }
 
 impl RskTransaction {
@@ -107,7 +108,7 @@ impl RskTransaction {
     pub async fn from_alchemy_transfer(
         transfer: &Value,
         _wallet_address: &Address,
-        alchemy_client: &AlchemyClient,
+        rpc: &dyn ChainRpc,
     ) -> Result<Self> {
         // Parse hash
         let hash = transfer["hash"]
@@ -130,8 +131,10 @@ impl RskTransaction {
             .as_str()
             .and_then(|s| Address::from_str(s).ok());
 
-        // Handle value (can be number or hex string)
-        let value = if let Some(num) = transfer["value"].as_u64() {
+        // Prefer the raw hex value; `value` is a decimal in whole units
+        let value = if let Some(raw) = transfer["rawContract"]["value"].as_str() {
+            U256::from_str_radix(raw.trim_start_matches("0x"), 16)?
+        } else if let Some(num) = transfer["value"].as_u64() {
             U256::from(num)
         } else if let Some(hex_str) = transfer["value"].as_str() {
             U256::from_str_radix(hex_str.trim_start_matches("0x"), 16)?
@@ -139,46 +142,37 @@ impl RskTransaction {
             U256::ZERO
         };
 
-        // Get transaction receipt for status and gas used
-        let rpc_url = alchemy_client.get_base_url();
-        let receipt = Self::get_transaction_receipt(&hash, &rpc_url).await?;
-        let (status, gas_used) = match receipt {
-            Some(r) => (r.status, r.gas_used),
-            None => (TransactionStatus::Pending, U256::ZERO),
+        // Get transaction receipt for status, gas used and logs
+        let receipt = Self::get_transaction_receipt(&hash, rpc).await?;
+        let (status, gas_used, effective_gas_price, logs) = match receipt {
+            Some(r) => (r.status, r.gas_used, Some(r.effective_gas_price), Some(r.logs)),
+            None => (TransactionStatus::Pending, U256::ZERO, None, None),
         };
 
-        // Get block number and timestamp
-        let (block_number, timestamp) = if let Some(block_num) = transfer["blockNum"]
+        let block_number = transfer["blockNum"]
             .as_str()
-            .and_then(|s| U256::from_str_radix(s.trim_start_matches("0x"), 16).ok())
-        {
-            if let Some(block) = alchemy_client
-                .get_block_by_number(block_num.to::<u64>())
-                .await?
-            {
-                let timestamp = block
-                    .get("timestamp")
-                    .and_then(|t| t.as_str())
-                    .and_then(|s| u64::from_str_radix(s.trim_start_matches("0x"), 16).ok())
-                    .map(|t| SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(t));
-                (Some(block_num), timestamp.unwrap_or_else(SystemTime::now))
-            } else {
-                (Some(block_num), SystemTime::now())
-            }
-        } else {
-            (None, SystemTime::now())
-        };
+            .and_then(|s| U256::from_str_radix(s.trim_start_matches("0x"), 16).ok());
 
-        // Try to get timestamp from metadata first
-        let timestamp = if let Some(timestamp_str) = transfer["metadata"]["blockTimestamp"].as_str()
-        {
-            if let Ok(dt) = chrono::DateTime::parse_from_rfc3339(timestamp_str) {
-                SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(dt.timestamp() as u64)
-            } else {
-                timestamp
-            }
-        } else {
-            timestamp
+        // Prefer the timestamp from the transfer metadata and only fetch
+        // the block when it is missing
+        let metadata_timestamp = transfer["metadata"]["blockTimestamp"]
+            .as_str()
+            .and_then(|s| chrono::DateTime::parse_from_rfc3339(s).ok())
+            .map(|dt| SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(dt.timestamp() as u64));
+        let timestamp = match (metadata_timestamp, block_number) {
+            (Some(timestamp), _) => timestamp,
+            (None, Some(block_num)) => rpc
+                .block(block_num.to::<u64>())
+                .await?
+                .and_then(|block| {
+                    block
+                        .get("timestamp")
+                        .and_then(|t| t.as_str())
+                        .and_then(|s| u64::from_str_radix(s.trim_start_matches("0x"), 16).ok())
+                })
+                .map(|t| SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(t))
+                .unwrap_or_else(SystemTime::now),
+            (None, None) => SystemTime::now(),
         };
 
         // Determine token address for ERC20 transfers
@@ -190,7 +184,8 @@ impl RskTransaction {
             None
         };
 
-        // Get gas price if available
+        // Get gas price if available; asset transfers usually omit it, so
+        // fall back to the receipt to keep fees accurate
         let gas_price = transfer["gasPrice"]
             .as_str()
             .and_then(|s| U256::from_str_radix(s.trim_start_matches("0x"), 16).ok())
@@ -198,7 +193,8 @@ impl RskTransaction {
                 transfer["effectiveGasPrice"]
                     .as_str()
                     .and_then(|s| U256::from_str_radix(s.trim_start_matches("0x"), 16).ok())
-            });
+            })
+            .or(effective_gas_price);
 
         // Get nonce if available
         let nonce = transfer["nonce"]
@@ -222,16 +218,15 @@ impl RskTransaction {
             token_address,
             confirms: None, // Would need to be calculated from current block
             cumulative_gas_used: Some(gas_used), // From receipt if available
-            logs: None,     // Could be populated from receipt if needed
+            logs,
         })
     }
 
     async fn get_transaction_receipt(
         hash: &B256,
-        rpc_url: &str,
+        rpc: &dyn ChainRpc,
     ) -> Result<Option<TransactionReceipt>> {
-        let provider = ProviderBuilder::new().on_http(rpc_url.parse()?);
-        let receipt = provider.get_transaction_receipt(*hash).await?;
+        let receipt = rpc.receipt(*hash).await?;
 
         Ok(receipt.map(|r| TransactionReceipt {
             transaction_hash: r.transaction_hash,
@@ -244,6 +239,8 @@ impl RskTransaction {
             block_number: r.block_number.map(U256::from),
             block_hash: r.block_hash,
             cumulative_gas_used: U256::from(r.inner.cumulative_gas_used()),
+            effective_gas_price: U256::from(r.effective_gas_price),
+            logs: r.inner.logs().to_vec(),
         }))
     }
 }
@@ -71,8 +71,8 @@ impl Wallet {
         let mut key = [0u8; 32];
         scrypt(password.as_bytes(), &salt, &params, &mut key)?;
 
-        let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key));
-        let ciphertext = cipher.encrypt(Nonce::from_slice(&nonce), private_key)
+        let cipher = Aes256Gcm::new(&Key::<Aes256Gcm>::from(key));
+        let ciphertext = cipher.encrypt(&Nonce::from(nonce), private_key)
             .map_err(|e| anyhow!("Encryption failed: {}", e))?;
 
         // Zeroize sensitive data
@@ -107,8 +107,12 @@ impl Wallet {
         // Try GCM first (new format), fallback to CBC (legacy)
         let result = if nonce_or_iv.len() == 12 {
             // New GCM format
-            let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key));
-            let mut plaintext = cipher.decrypt(Nonce::from_slice(&nonce_or_iv), encrypted_key.as_ref())
+            let cipher = Aes256Gcm::new(&Key::<Aes256Gcm>::from(key));
+            let nonce: [u8; 12] = nonce_or_iv
+                .as_slice()
+                .try_into()
+                .map_err(|_| anyhow!("Invalid nonce length"))?;
+            let mut plaintext = cipher.decrypt(&Nonce::from(nonce), encrypted_key.as_ref())
                 .map_err(|_| anyhow!("Incorrect password. Please try again."))?;
 
             if plaintext.len() != 32 {
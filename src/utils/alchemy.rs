@@ -1,36 +1,86 @@
 // src/utils/alchemy.rs
+use alloy::primitives::Address;
 use anyhow::{Result, anyhow};
-use reqwest::Client;
+use crate::utils::http::{self, Retry};
 use serde_json::Value;
+use std::collections::HashSet;
+use std::str::FromStr;
+
+/// Largest page `alchemy_getAssetTransfers` returns
+const MAX_PAGE_SIZE: u32 = 1000;
+
+/// Transfer categories requested from Alchemy; `internal` is dropped on
+/// networks that do not support it
+const TRANSFER_CATEGORIES: &[&str] = &["external", "internal", "erc20"];
+
+/// JSON-RPC codes Alchemy answers a request with an unsupported parameter with
+const INVALID_REQUEST_CODES: &[i64] = &[-32600, -32602];
+
+/// Error object of a failed Alchemy request
+#[derive(Debug, thiserror::Error)]
+#[error("Alchemy API error: {0}")]
+struct ApiError(Value);
+
+impl ApiError {
+    /// Whether the network does not support the `internal` transfer
+    /// category. Server faults such as "Internal error" (-32603) do not count.
+    fn is_internal_category_unsupported(&self) -> bool {
+        let message = self.0["message"].as_str().unwrap_or_default().to_lowercase();
+        self.0["code"].as_i64().is_some_and(|code| INVALID_REQUEST_CODES.contains(&code))
+            && message.contains("internal")
+            && ["not supported", "only supported", "unsupported"]
+                .iter()
+                .any(|phrase| message.contains(phrase))
+    }
+}
+
+/// Which side of a transfer the queried address is on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransferDirection {
+    Outgoing,
+    Incoming,
+}
+
+/// Identifier of a transfer, stable across both query directions
+fn transfer_id(transfer: &Value) -> String {
+    match transfer["uniqueId"].as_str() {
+        Some(id) => id.to_string(),
+        None => format!(
+            "{}:{}:{}:{}:{}",
+            transfer["hash"].as_str().unwrap_or_default(),
+            transfer["category"].as_str().unwrap_or_default(),
+            transfer["from"].as_str().unwrap_or_default(),
+            transfer["to"].as_str().unwrap_or_default(),
+            transfer["rawContract"]["address"].as_str().unwrap_or_default(),
+        ),
+    }
+}
 
 pub struct AlchemyClient {
-    client: Client,
     api_key: String,
     is_testnet: bool,
+    base_url: Option<String>,
 }
 
 impl AlchemyClient {
-    // pub fn new(api_key: String, is_testnet: bool) -> Self {
-    //     Self {
-    //         client: reqwest::Client::new(),
-    //         api_key,
-    //         is_testnet,
-    //     }
-    // }
     pub fn new(api_key: String, is_testnet: bool) -> Self {
-        let client = Client::builder()
-            .https_only(true) // Restrict to HTTPS
-            .use_rustls_tls() // Use rustls for TLS (more secure, avoids system-specific issues)
-            .build()
-            .expect("Failed to build reqwest client");
         Self {
-            client,
             api_key,
             is_testnet,
+            base_url: None,
         }
     }
 
+    /// Sends requests to `base_url` instead of the hosted Alchemy API
+    pub fn with_base_url(mut self, base_url: Option<String>) -> Self {
+        self.base_url = base_url;
+        self
+    }
+
     pub fn get_base_url(&self) -> String {
+        if let Some(base_url) = &self.base_url {
+            return base_url.trim_end_matches('/').to_string();
+        }
         let network = if self.is_testnet {
             "testnet"
         } else {
@@ -39,75 +89,190 @@ impl AlchemyClient {
         format!("https://rootstock-{}.g.alchemy.com/v2", network)
     }
 
+    /// JSON-RPC endpoint with the API key, for standard `eth_` calls
+    pub fn rpc_url(&self) -> String {
+        format!("{}/{}", self.get_base_url(), self.api_key)
+    }
+
+    /// Fetches one page of `alchemy_getAssetTransfers` for transfers sent
+    /// from or received by `address`
+    #[allow(clippy::too_many_arguments)]
     pub async fn get_asset_transfers(
         &self,
         address: &str,
+        direction: TransferDirection,
+        categories: &[&str],
         limit: u32,
         from_block: Option<&str>,
         to_block: Option<&str>,
+        page_key: Option<&str>,
     ) -> Result<Value> {
         let url = self.get_base_url();
 
-        let params = serde_json::json!([{
+        let mut params = serde_json::json!({
             "fromBlock": from_block.unwrap_or("0x0"),
             "toBlock": to_block.unwrap_or("latest"),
-            "fromAddress": address,
-            "category": ["external", "erc20"],
+            "category": categories,
             "withMetadata": true,
             "excludeZeroValue": false,
             "maxCount": format!("0x{:x}", limit),
-        }]);
-
-        let response = self
-            .client
-            .post(&url)
-            .header("Authorization", format!("Bearer {}", self.api_key))
-            .json(&serde_json::json!({
-                "jsonrpc": "2.0",
-                "id": 1,
-                "method": "alchemy_getAssetTransfers",
-                "params": params
-            }))
-            .send()
-            .await
-            .map_err(|e| anyhow!("Request failed: {}", e))?
-            .json::<Value>()
-            .await
-            .map_err(|e| anyhow!("Failed to parse response: {}", e))?;
+        });
+        let address_field = match direction {
+            TransferDirection::Outgoing => "fromAddress",
+            TransferDirection::Incoming => "toAddress",
+        };
+        params[address_field] = Value::String(address.to_string());
+        if let Some(page_key) = page_key {
+            params["pageKey"] = Value::String(page_key.to_string());
+        }
+
+        let request = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "alchemy_getAssetTransfers",
+            "params": [params]
+        });
+        // Long syncs page through many requests; the shared client keeps
+        // them under the Alchemy rate limit and waits out throttling
+        let response = http::client()
+            .post_json(&url, &request, Some(&self.api_key), Retry::Transient)
+            .await?;
 
         if let Some(error) = response.get("error") {
-            return Err(anyhow!("Alchemy API error: {}", error));
+            return Err(ApiError(error.clone()).into());
         }
 
         Ok(response)
     }
 
-    pub async fn get_block_by_number(&self, block_number: u64) -> Result<Option<Value>> {
-        let url = self.get_base_url();
-        let block_number_hex = format!("0x{:x}", block_number);
-
-        let response = self
-            .client
-            .post(&url)
-            .header("Authorization", format!("Bearer {}", self.api_key))
-            .json(&serde_json::json!({
-                "jsonrpc": "2.0",
-                "id": 1,
-                "method": "eth_getBlockByNumber",
-                "params": [block_number_hex, false]  // false to get transaction hashes only
-            }))
-            .send()
-            .await
-            .map_err(|e| anyhow!("Request failed: {}", e))?
-            .json::<Value>()
-            .await?;
+    /// Fetches transfers in both directions, following `pageKey` until
+    /// `limit` transfers are collected or the block range is exhausted.
+    /// Results are de-duplicated and sorted by block.
+    pub async fn get_all_transfers(
+        &self,
+        address: &str,
+        limit: Option<usize>,
+        from_block: Option<&str>,
+        to_block: Option<&str>,
+    ) -> Result<Vec<Value>> {
+        let mut transfers: Vec<Value> = Vec::new();
+        let mut seen = HashSet::new();
 
-        if let Some(error) = response.get("error") {
-            return Err(anyhow!("Alchemy API error: {}", error));
+        for direction in [TransferDirection::Outgoing, TransferDirection::Incoming] {
+            let mut categories = TRANSFER_CATEGORIES.to_vec();
+            let mut page_key: Option<String> = None;
+            let mut collected = 0;
+
+            loop {
+                let page_size = limit
+                    .map(|l| l.saturating_sub(collected).min(MAX_PAGE_SIZE as usize) as u32)
+                    .unwrap_or(MAX_PAGE_SIZE);
+                if page_size == 0 {
+                    break;
+                }
+
+                let response = match self
+                    .get_asset_transfers(
+                        address,
+                        direction,
+                        &categories,
+                        page_size,
+                        from_block,
+                        to_block,
+                        page_key.as_deref(),
+                    )
+                    .await
+                {
+                    Ok(response) => response,
+                    // Not every network supports internal transfers
+                    Err(e)
+                        if categories.contains(&"internal")
+                            && e.downcast_ref::<ApiError>().is_some_and(ApiError::is_internal_category_unsupported) =>
+                    {
+                        categories.retain(|c| *c != "internal");
+                        continue;
+                    }
+                    Err(e) => return Err(e),
+                };
+
+                let page = response["result"]["transfers"]
+                    .as_array()
+                    .ok_or_else(|| anyhow!("Invalid response format from Alchemy"))?;
+                collected += page.len();
+                for transfer in page {
+                    if seen.insert(transfer_id(transfer)) {
+                        transfers.push(transfer.clone());
+                    }
+                }
+
+                page_key = response["result"]["pageKey"].as_str().map(str::to_string);
+                if page_key.is_none() {
+                    break;
+                }
+            }
+        }
+
+        transfers.sort_by_key(|t| {
+            t["blockNum"]
+                .as_str()
+                .and_then(|s| u64::from_str_radix(s.trim_start_matches("0x"), 16).ok())
+                .unwrap_or(u64::MAX)
+        });
+        Ok(transfers)
+    }
+
+    /// Contracts of every ERC20 token `address` has sent or received
+    pub async fn token_contracts(&self, address: &str) -> Result<HashSet<Address>> {
+        let mut contracts = HashSet::new();
+        for direction in [TransferDirection::Outgoing, TransferDirection::Incoming] {
+            let mut page_key: Option<String> = None;
+            loop {
+                let response = self
+                    .get_asset_transfers(
+                        address,
+                        direction,
+                        &["erc20"],
+                        MAX_PAGE_SIZE,
+                        None,
+                        None,
+                        page_key.as_deref(),
+                    )
+                    .await?;
+                let page = response["result"]["transfers"]
+                    .as_array()
+                    .ok_or_else(|| anyhow!("Invalid response format from Alchemy"))?;
+                contracts.extend(
+                    page.iter()
+                        .filter(|t| t["category"].as_str() == Some("erc20"))
+                        .filter_map(|t| t["rawContract"]["address"].as_str())
+                        .filter_map(|a| Address::from_str(a).ok()),
+                );
+                page_key = response["result"]["pageKey"].as_str().map(str::to_string);
+                if page_key.is_none() {
+                    break;
+                }
+            }
         }
+        Ok(contracts)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_only_unsupported_category_errors_drop_internal_transfers() {
+        let unsupported = ApiError(json!({
+            "code": -32600,
+            "message": "internal category is only supported for ETH and MATIC"
+        }));
+        assert!(unsupported.is_internal_category_unsupported());
 
-        Ok(response
-            .get("result")
-            .and_then(|r| if r.is_null() { None } else { Some(r.clone()) }))
+        let server_fault = ApiError(json!({"code": -32603, "message": "Internal error"}));
+        assert!(!server_fault.is_internal_category_unsupported());
+        let other_param = ApiError(json!({"code": -32602, "message": "invalid fromBlock: internal parse failure"}));
+        assert!(!other_param.is_internal_category_unsupported());
     }
 }
@@ -4,10 +4,9 @@ use reqwest::Client;
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::PathBuf;
-use std::sync::OnceLock;
+use crate::utils::{constants, http};
 
 const API_KEYS_FILE: &str = "api_keys.json";
-static HTTP_CLIENT: OnceLock<Client> = OnceLock::new();
 
 #[derive(Debug, Serialize, Deserialize, Default, Clone)]
 pub struct ApiKeys {
@@ -60,14 +59,13 @@ impl ApiKeys {
         ))
     }
 
-    pub fn get_http_client() -> &'static Client {
-        HTTP_CLIENT.get_or_init(Client::new)
+    /// Client with the configured proxy and timeouts
+    pub fn get_http_client() -> Client {
+        http::client().inner().clone()
     }
 
     fn get_config_path() -> Result<PathBuf> {
-        let mut path = dirs::config_dir()
-            .context("Could not find config directory")?
-            .join("rsk-rust-cli");
+        let mut path = constants::config_dir();
         
         std::fs::create_dir_all(&path)?;
         path.push(API_KEYS_FILE);
@@ -1,8 +1,8 @@
 use crate::api::{ApiProvider, ApiKey};
+use crate::utils::http;
 use anyhow::{Result, anyhow};
 use reqwest::Client;
 use serde_json::{json, Value};
-use std::time::Duration;
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum ValidationResult {
@@ -12,13 +12,14 @@ pub enum ValidationResult {
 }
 
 pub async fn validate_api_key(api_key: &ApiKey) -> Result<ValidationResult> {
-    let client = Client::builder()
-        .timeout(Duration::from_secs(10))
-        .build()?;
+    // A rejected key must not be retried, so skip the retry policy and use
+    // only the shared client's timeouts
+    let http = http::client();
+    let client = http.inner();
 
     match api_key.provider {
-        ApiProvider::RskRpc => validate_rsk_key(&client, api_key).await,
-        ApiProvider::Alchemy => validate_alchemy_rsk_key(&client, api_key).await,
+        ApiProvider::RskRpc => validate_rsk_key(client, api_key).await,
+        ApiProvider::Alchemy => validate_alchemy_rsk_key(client, api_key).await,
         ApiProvider::Custom(_) => Ok(ValidationResult::Valid),
     }
 }
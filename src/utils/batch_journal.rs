@@ -0,0 +1,941 @@
+use crate::commands::transfer::{TransferResult, record_transfer};
+use crate::config::BulkSettings;
+use crate::utils::bulk::PlannedTransfer;
+use crate::utils::eth::EthClient;
+use crate::utils::nonce_manager::{ConfirmationTracker, is_nonce_too_low};
+use crate::utils::rpc::ChainRpc;
+use crate::utils::{constants, http, secure_fs};
+use alloy::consensus::TxEnvelope;
+use alloy::eips::eip2718::{Decodable2718, Encodable2718};
+use alloy::primitives::{Address, B256, Bytes, U64, U256, keccak256, utils::format_units};
+use alloy::providers::Provider;
+use alloy::rpc::types::TransactionReceipt;
+use anyhow::{Result, anyhow, bail};
+use chrono::{DateTime, Utc};
+use console::style;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+/// Times a batch resyncs its nonces after other transactions took them
+/// before giving up
+const MAX_NONCE_RESYNCS: usize = 3;
+
+/// Where a journaled row stands
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RowStatus {
+    /// Not signed yet
+    Planned,
+    /// Signed and journaled; it may or may not have reached a node
+    Signed,
+    /// Accepted by a node and waiting to be mined
+    Sent,
+    /// Mined and succeeded
+    Confirmed,
+    /// Mined and reverted
+    Reverted,
+    /// Its nonce was used by another transaction. Never resent
+    /// automatically, since the other transaction may have paid the row.
+    Replaced,
+    /// Refused for "nonce too low" while the node could not say whether
+    /// the signed transaction went through. Never resent automatically;
+    /// settled as confirmed or reverted if its receipt turns up.
+    Review,
+}
+
+impl RowStatus {
+    pub fn is_settled(self) -> bool {
+        matches!(self, Self::Confirmed | Self::Reverted | Self::Replaced | Self::Review)
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::Planned => "planned",
+            Self::Signed => "signed",
+            Self::Sent => "sent",
+            Self::Confirmed => "confirmed",
+            Self::Reverted => "reverted",
+            Self::Replaced => "replaced",
+            Self::Review => "needs review",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JournalRow {
+    #[serde(flatten)]
+    pub transfer: PlannedTransfer,
+    pub nonce: Option<u64>,
+    pub tx_hash: Option<B256>,
+    /// The signed transaction, so a resumed batch sends exactly the same one
+    pub raw_tx: Option<Bytes>,
+    pub status: RowStatus,
+    pub block: Option<u64>,
+    pub error: Option<String>,
+}
+
+/// Record of a bulk transfer batch, saved before every transaction is
+/// broadcast so an interrupted batch can be resumed without paying anyone
+/// twice
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchJournal {
+    pub id: String,
+    /// Hash of the network, sender and planned transfers; the same input
+    /// maps to the same journal until that journal is settled
+    pub input_hash: B256,
+    pub network: String,
+    pub sender: Address,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub rows: Vec<JournalRow>,
+}
+
+impl BatchJournal {
+    pub fn input_hash(network: &str, sender: Address, transfers: &[PlannedTransfer]) -> B256 {
+        let canonical: Vec<_> = transfers
+            .iter()
+            .map(|t| (t.line, t.to, t.asset.address, t.amount))
+            .collect();
+        let input = serde_json::to_vec(&(network.to_lowercase(), sender, canonical)).unwrap_or_default();
+        keccak256(input)
+    }
+
+    fn path(id: &str) -> PathBuf {
+        constants::batches_dir().join(format!("{}.json", id))
+    }
+
+    /// The unfinished journal for this input, if it was started before, or
+    /// a new one. Once a journal is settled the same input is a new payment,
+    /// so it gets a new journal with a run-specific id.
+    pub fn open(network: &str, sender: Address, transfers: &[PlannedTransfer]) -> Result<Self> {
+        let input_hash = Self::input_hash(network, sender, transfers);
+        if let Some(journal) = Self::list()?
+            .into_iter()
+            .find(|journal| journal.input_hash == input_hash && !journal.is_settled())
+        {
+            return Ok(journal);
+        }
+        let now = Utc::now();
+        let mut id = hex::encode(&input_hash[..8]);
+        let mut run = now.timestamp_nanos_opt().unwrap_or_default();
+        while Self::path(&id).exists() {
+            let run_hash = keccak256([input_hash.as_slice(), &run.to_be_bytes()].concat());
+            id = hex::encode(&run_hash[..8]);
+            run += 1;
+        }
+        Ok(Self {
+            id,
+            input_hash,
+            network: network.to_lowercase(),
+            sender,
+            created_at: now,
+            updated_at: now,
+            rows: transfers
+                .iter()
+                .map(|transfer| JournalRow {
+                    transfer: transfer.clone(),
+                    nonce: None,
+                    tx_hash: None,
+                    raw_tx: None,
+                    status: RowStatus::Planned,
+                    block: None,
+                    error: None,
+                })
+                .collect(),
+        })
+    }
+
+    /// Settled journals for the same input, newest first. A new journal
+    /// with earlier runs pays everyone again.
+    pub fn earlier_runs(&self) -> Result<Vec<Self>> {
+        Ok(Self::list()?
+            .into_iter()
+            .filter(|other| other.id != self.id && other.input_hash == self.input_hash && other.is_settled())
+            .collect())
+    }
+
+    pub fn load(id: &str) -> Result<Self> {
+        let path = Self::path(id);
+        let data = fs::read_to_string(&path).map_err(|e| anyhow!("Failed to read batch {}: {}", id, e))?;
+        serde_json::from_str(&data).map_err(|e| anyhow!("Failed to parse batch journal {}: {}", path.display(), e))
+    }
+
+    /// Every journal, newest first
+    pub fn list() -> Result<Vec<Self>> {
+        let dir = constants::batches_dir();
+        if !dir.exists() {
+            return Ok(Vec::new());
+        }
+        let mut journals = Vec::new();
+        for entry in fs::read_dir(&dir)? {
+            let path = entry?.path();
+            if path.extension().is_some_and(|e| e == "json")
+                && let Some(id) = path.file_stem().and_then(|s| s.to_str())
+                && let Ok(journal) = Self::load(id)
+            {
+                journals.push(journal);
+            }
+        }
+        journals.sort_by_key(|j| std::cmp::Reverse(j.created_at));
+        Ok(journals)
+    }
+
+    /// Writes the journal through a temporary file, so a crash leaves
+    /// either the old or the new version
+    pub fn save(&mut self) -> Result<()> {
+        self.updated_at = Utc::now();
+        let path = Self::path(&self.id);
+        let tmp = path.with_extension("json.tmp");
+        secure_fs::write_secure(&tmp, &serde_json::to_string_pretty(self)?)?;
+        fs::rename(&tmp, &path)?;
+        Ok(())
+    }
+
+    pub fn is_settled(&self) -> bool {
+        self.rows.iter().all(|row| row.status.is_settled())
+    }
+
+    pub fn count(&self, status: RowStatus) -> usize {
+        self.rows.iter().filter(|row| row.status == status).count()
+    }
+
+    pub fn summary(&self) -> String {
+        format!(
+            "batch {} from {} on {}: {} of {} confirmed",
+            self.id,
+            self.created_at.format("%Y-%m-%d %H:%M"),
+            self.network,
+            self.count(RowStatus::Confirmed),
+            self.rows.len()
+        )
+    }
+
+    /// Unsent rows paying the same recipient the same amount as a row that
+    /// went out in another batch, as `(line, batch id)`. Catches an input
+    /// file edited after part of it was sent, which maps to a new journal.
+    pub fn sent_elsewhere(&self, others: &[BatchJournal]) -> Vec<(usize, String)> {
+        let same = |a: &PlannedTransfer, b: &PlannedTransfer| {
+            a.to == b.to && a.asset.address == b.asset.address && a.amount == b.amount
+        };
+        self.rows
+            .iter()
+            .filter(|row| row.status == RowStatus::Planned)
+            .filter_map(|row| {
+                others
+                    .iter()
+                    .filter(|other| other.id != self.id && other.sender == self.sender && other.network == self.network)
+                    .find(|other| {
+                        other.rows.iter().any(|o| {
+                            matches!(o.status, RowStatus::Signed | RowStatus::Sent | RowStatus::Confirmed)
+                                && same(&o.transfer, &row.transfer)
+                        })
+                    })
+                    .map(|other| (row.transfer.line, other.id.clone()))
+            })
+            .collect()
+    }
+
+    /// Settles signed, sent and under-review rows from their receipts. A
+    /// signed or sent row without a receipt whose nonce the sender has
+    /// already used was replaced by another transaction, if its transaction
+    /// is still unknown once the next block is out; otherwise it is left
+    /// for review.
+    pub async fn reconcile(&mut self, client: &EthClient, settings: &BulkSettings) -> Result<()> {
+        let used_nonces = client
+            .provider()
+            .get_transaction_count(self.sender)
+            .await
+            .map_err(|e| anyhow!("Failed to get nonce: {}", e))?;
+        let mut changed = false;
+        let mut taken = Vec::new();
+        for i in 0..self.rows.len() {
+            let row = &self.rows[i];
+            let (Some(hash), Some(nonce)) = (row.tx_hash, row.nonce) else {
+                continue;
+            };
+            if !matches!(row.status, RowStatus::Signed | RowStatus::Sent | RowStatus::Review) {
+                continue;
+            }
+            let receipt = client.receipt(hash).await?;
+            match receipt {
+                Some(receipt) => self.settle(i, &receipt),
+                None if nonce < used_nonces && row.status != RowStatus::Review => {
+                    taken.push((i, hash));
+                    continue;
+                }
+                None => continue,
+            }
+            changed = true;
+        }
+        if !taken.is_empty() {
+            let hashes: Vec<B256> = taken.iter().map(|(_, hash)| *hash).collect();
+            let unknown = unknown_after_next_block(client, &hashes, settings.block_time()).await;
+            for ((i, _), unknown) in taken.into_iter().zip(unknown) {
+                let row = &mut self.rows[i];
+                let nonce = row.nonce.unwrap_or_default();
+                if unknown {
+                    row.status = RowStatus::Replaced;
+                    row.error = Some(format!("nonce {} was used by another transaction", nonce));
+                } else {
+                    row.status = RowStatus::Review;
+                    row.error = Some(format!(
+                        "nonce {} was used, but the node still knows this transaction or could not say; needs manual review",
+                        nonce
+                    ));
+                }
+            }
+            changed = true;
+        }
+        if changed {
+            self.save()?;
+        }
+        Ok(())
+    }
+
+    fn settle(&mut self, index: usize, receipt: &TransactionReceipt) {
+        let row = &mut self.rows[index];
+        if row.tx_hash.is_none() || (row.status.is_settled() && row.status != RowStatus::Review) {
+            return;
+        }
+        row.status = if receipt.status() {
+            RowStatus::Confirmed
+        } else {
+            RowStatus::Reverted
+        };
+        row.block = receipt.block_number;
+        row.error = None;
+        record_transfer(&self.network, &row.result(self.sender, receipt), Some(receipt));
+    }
+
+    /// Applies receipts the background tracker found
+    fn apply_confirmations(&mut self, tracker: &mut ConfirmationTracker) {
+        for (hash, receipt) in tracker.confirmed() {
+            if let Some(index) = self.rows.iter().position(|row| row.tx_hash == Some(hash)) {
+                self.settle(index, &receipt);
+            }
+        }
+    }
+
+    /// Sends every row that is not mined yet, `window` transactions at a
+    /// time. Journaled transactions are broadcast again unchanged. New rows
+    /// take nonces from the client's nonce manager and are signed and saved
+    /// before their window is broadcast concurrently, while confirmations
+    /// are tracked in the background.
+    ///
+    /// A row refused for "nonce too low" whose transaction the node has
+    /// neither seen nor mined, and still has not once a later block is out,
+    /// can never be mined, so it is signed again after resyncing the nonces.
+    /// When a lookup fails or finds the transaction, or no new block comes,
+    /// the row is left for manual review instead. Any other refusal stops
+    /// the batch: later nonces wait on it, and resuming sends it again.
+    pub async fn send(&mut self, client: &EthClient, gas_price: u128, settings: &BulkSettings) -> Result<()> {
+        if client.signer_address() != Some(self.sender) {
+            bail!("The batch was planned for {}, not the unlocked wallet", self.sender);
+        }
+        self.reconcile(client, settings).await?;
+        let window = settings.send_window.max(1);
+        let mut tracker = client.track_confirmations();
+
+        let journaled: Vec<usize> = (0..self.rows.len())
+            .filter(|&i| matches!(self.rows[i].status, RowStatus::Signed | RowStatus::Sent))
+            .collect();
+        for chunk in journaled.chunks(window) {
+            let mut txs = Vec::with_capacity(chunk.len());
+            for &i in chunk {
+                txs.push(self.rows[i].signed_tx()?);
+            }
+            for ((&i, tx), result) in chunk.iter().zip(&txs).zip(client.broadcast_all(&txs).await) {
+                let row = &mut self.rows[i];
+                match result {
+                    Ok(hash) => {
+                        println!("↻ line {} resent as 0x{:x}", row.transfer.line, hash);
+                        row.status = RowStatus::Sent;
+                        row.error = None;
+                        tracker.watch(*tx.tx_hash());
+                    }
+                    // Settled as mined or replaced by the next reconcile
+                    Err(e) => row.error = Some(e.to_string()),
+                }
+            }
+        }
+        self.save()?;
+        if let Some(highest) = self.rows.iter().filter_map(|row| row.nonce).max() {
+            client.advance_nonce_to(highest + 1).await?;
+        }
+
+        let chain_id = client.chain_id().await?;
+        let total = self.rows.len();
+        let mut resyncs = 0;
+        loop {
+            let planned: Vec<usize> = (0..total)
+                .filter(|&i| self.rows[i].status == RowStatus::Planned)
+                .take(window)
+                .collect();
+            if planned.is_empty() {
+                break;
+            }
+
+            // The whole window is on disk before any of it is broadcast
+            let mut txs = Vec::with_capacity(planned.len());
+            for &i in &planned {
+                let nonce = client.next_nonce().await?;
+                let request = self.rows[i].transfer.request(self.sender, nonce, gas_price, chain_id);
+                let tx = client.sign_transaction(request).await?;
+                let row = &mut self.rows[i];
+                row.nonce = Some(nonce);
+                row.tx_hash = Some(*tx.tx_hash());
+                row.raw_tx = Some(tx.encoded_2718().into());
+                row.status = RowStatus::Signed;
+                txs.push(tx);
+            }
+            self.save()?;
+
+            let mut rejected = None;
+            let mut resync = false;
+            let mut taken = Vec::new();
+            for ((&i, tx), result) in planned.iter().zip(&txs).zip(client.broadcast_all(&txs).await) {
+                let line = self.rows[i].transfer.line;
+                let hash = *tx.tx_hash();
+                match result {
+                    Ok(_) => {
+                        println!("✅ line {} ({}/{}) sent: 0x{:x}", line, i + 1, total, hash);
+                        self.rows[i].status = RowStatus::Sent;
+                        tracker.watch(hash);
+                    }
+                    Err(e) if is_nonce_too_low(&e.to_string()) => taken.push((i, hash, e.to_string())),
+                    Err(e) => {
+                        self.rows[i].error = Some(e.to_string());
+                        rejected.get_or_insert((line, e));
+                    }
+                }
+            }
+            if !taken.is_empty() {
+                let hashes: Vec<B256> = taken.iter().map(|(_, hash, _)| *hash).collect();
+                let unknown = unknown_after_next_block(client, &hashes, settings.block_time()).await;
+                for ((i, hash, error), unknown) in taken.into_iter().zip(unknown) {
+                    let row = &mut self.rows[i];
+                    let (line, nonce) = (row.transfer.line, row.nonce.unwrap_or_default());
+                    if !unknown {
+                        println!(
+                            "⚠️  line {}: nonce {} was taken and the node could not say whether 0x{:x} went through; check it before resending",
+                            line, nonce, hash
+                        );
+                        row.status = RowStatus::Review;
+                        row.error = Some(format!("{}; needs manual review", error));
+                        continue;
+                    }
+                    println!("↻ line {}: nonce {} was taken, signing again", line, nonce);
+                    row.nonce = None;
+                    row.tx_hash = None;
+                    row.raw_tx = None;
+                    row.status = RowStatus::Planned;
+                    resync = true;
+                }
+            }
+            self.apply_confirmations(&mut tracker);
+            self.save()?;
+
+            if let Some((line, e)) = rejected {
+                client.resync_nonce().await?;
+                bail!("Stopped at line {}: {}. Resume the batch to retry.", line, e);
+            }
+            if resync {
+                resyncs += 1;
+                if resyncs > MAX_NONCE_RESYNCS {
+                    bail!("Other transactions from this wallet keep taking the batch's nonces. Resume the batch when they are done.");
+                }
+                client.resync_nonce().await?;
+            }
+        }
+        self.apply_confirmations(&mut tracker);
+        self.save()
+    }
+
+    /// Polls until every sent row is settled or the batch's confirmation
+    /// timeout passes. Sent transactions a node has dropped are broadcast
+    /// again, so a gap in the nonces does not hold back the rows after it.
+    pub async fn wait_for_confirmations(&mut self, client: &EthClient, settings: &BulkSettings) -> Result<()> {
+        let policy = http::client().policy().clone();
+        let timeout = settings.confirmation_timeout(self.rows.len());
+        let started = Instant::now();
+        let mut attempt = 0;
+        loop {
+            self.reconcile(client, settings).await?;
+            if self.is_settled() || started.elapsed() >= timeout {
+                return Ok(());
+            }
+            for row in self.rows.iter().filter(|row| row.status == RowStatus::Sent) {
+                let Some(hash) = row.tx_hash else { continue };
+                if matches!(client.transaction(hash).await, Ok(None)) {
+                    let _ = client.broadcast(&row.signed_tx()?).await;
+                }
+            }
+            tokio::time::sleep(policy.backoff(attempt)).await;
+            attempt += 1;
+        }
+    }
+
+    pub fn print(&self) {
+        println!("\n📒 {}", style(self.summary()).bold());
+        for row in &self.rows {
+            let status = match row.status {
+                RowStatus::Confirmed => style(row.status.label()).green(),
+                RowStatus::Reverted | RowStatus::Replaced | RowStatus::Review => style(row.status.label()).red(),
+                _ => style(row.status.label()).yellow(),
+            };
+            let hash = row.tx_hash.map(|h| format!("0x{:x}", h)).unwrap_or_default();
+            println!(
+                "  line {:>4}  {}  {}  {}  {}",
+                row.transfer.line,
+                row.transfer.to,
+                row.transfer.asset.format(row.transfer.amount),
+                status,
+                style(hash).dim()
+            );
+            if let Some(error) = &row.error {
+                println!("             {}", style(error).red());
+            }
+        }
+    }
+
+    /// Writes one line per row as JSON when the path ends in `.json`,
+    /// otherwise as CSV
+    pub fn write_settlement(&self, path: &Path) -> Result<()> {
+        let lines: Vec<SettlementLine> = self.rows.iter().map(SettlementLine::from).collect();
+        if path.extension().is_some_and(|e| e.eq_ignore_ascii_case("json")) {
+            fs::write(path, serde_json::to_string_pretty(&lines)?)?;
+        } else {
+            let mut writer = csv::Writer::from_path(path)?;
+            for line in &lines {
+                writer.serialize(line)?;
+            }
+            writer.flush()?;
+        }
+        Ok(())
+    }
+
+    pub fn default_settlement_path(&self) -> PathBuf {
+        constants::batches_dir().join(format!("{}-settlement.csv", self.id))
+    }
+}
+
+/// Whether no node knows each of `hashes`, asked again once a block after
+/// the current one is out, so an endpoint lagging behind the chain is not
+/// taken at its word. A failed lookup, or no new block within a few block
+/// times, counts as known.
+async fn unknown_after_next_block(client: &EthClient, hashes: &[B256], block_time: Duration) -> Vec<bool> {
+    let mut unknown = Vec::with_capacity(hashes.len());
+    for &hash in hashes {
+        unknown.push(is_unknown(client, hash).await);
+    }
+    if !unknown.contains(&true) {
+        return unknown;
+    }
+    let Ok(current) = client.block_number().await else {
+        return vec![false; hashes.len()];
+    };
+    let policy = http::client().policy().clone();
+    let started = Instant::now();
+    let mut attempt = 0;
+    while !client.block_number().await.is_ok_and(|latest| latest > current) {
+        if started.elapsed() >= block_time * 3 {
+            return vec![false; hashes.len()];
+        }
+        tokio::time::sleep(policy.backoff(attempt).min(block_time)).await;
+        attempt += 1;
+    }
+    for (&hash, unknown) in hashes.iter().zip(unknown.iter_mut()) {
+        if *unknown {
+            *unknown = is_unknown(client, hash).await;
+        }
+    }
+    unknown
+}
+
+/// Whether the node has neither seen nor mined the transaction
+async fn is_unknown(client: &EthClient, hash: B256) -> bool {
+    matches!(
+        (client.transaction(hash).await, client.receipt(hash).await),
+        (Ok(None), Ok(None))
+    )
+}
+
+impl JournalRow {
+    fn signed_tx(&self) -> Result<TxEnvelope> {
+        let raw = self
+            .raw_tx
+            .as_ref()
+            .ok_or_else(|| anyhow!("Line {} has no signed transaction", self.transfer.line))?;
+        TxEnvelope::decode_2718(&mut raw.as_ref())
+            .map_err(|e| anyhow!("Journaled transaction of line {} is corrupt: {}", self.transfer.line, e))
+    }
+
+    /// The mined transfer, with the fee and status of its receipt
+    fn result(&self, from: Address, receipt: &TransactionReceipt) -> TransferResult {
+        TransferResult {
+            tx_hash: receipt.transaction_hash,
+            from,
+            to: self.transfer.to,
+            value: self.transfer.amount,
+            gas_used: U256::from(receipt.gas_used),
+            gas_price: U256::from(receipt.effective_gas_price),
+            status: U64::from(receipt.status() as u8),
+            token_address: self.transfer.asset.address,
+            token_symbol: Some(self.transfer.asset.symbol.clone()),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct SettlementLine {
+    line: usize,
+    recipient: String,
+    address: String,
+    token: String,
+    amount: String,
+    status: &'static str,
+    nonce: Option<u64>,
+    tx_hash: Option<String>,
+    block: Option<u64>,
+    error: Option<String>,
+}
+
+impl From<&JournalRow> for SettlementLine {
+    fn from(row: &JournalRow) -> Self {
+        let transfer = &row.transfer;
+        Self {
+            line: transfer.line,
+            recipient: transfer.recipient.clone(),
+            address: transfer.to.to_checksum(None),
+            token: transfer.asset.symbol.clone(),
+            amount: format_units(transfer.amount, transfer.asset.decimals).unwrap_or_default(),
+            status: row.status.label(),
+            nonce: row.nonce,
+            tx_hash: row.tx_hash.map(|h| format!("0x{:x}", h)),
+            block: row.block,
+            error: row.error.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::BulkSettings;
+    use crate::utils::bulk::Asset;
+    use crate::utils::helper::Config;
+    use crate::utils::mock_node::{Fixtures, TestEnv};
+    use alloy::signers::local::PrivateKeySigner;
+    use zeroize::Zeroizing;
+
+    #[tokio::test]
+    async fn test_resumed_batch_never_pays_a_row_twice() {
+        let signer = PrivateKeySigner::random();
+        let sender = signer.address();
+        let mut fixtures = Fixtures::default();
+        fixtures.balances.insert(sender, U256::from(10u64).pow(U256::from(18)));
+        let env = TestEnv::start(fixtures).await;
+        let mut config = Config::default();
+        config.network.rpc_url = env.node.url().to_string();
+        config.wallet.private_key = Some(Zeroizing::new(hex::encode(signer.to_bytes())));
+        let client = EthClient::new(&config, None).await.unwrap();
+
+        let transfers: Vec<PlannedTransfer> = (1..=3)
+            .map(|line| PlannedTransfer {
+                line,
+                recipient: format!("payee {}", line),
+                to: Address::with_last_byte(line as u8),
+                asset: Asset::rbtc(),
+                amount: U256::from(1000 * line),
+                gas: 21_000,
+            })
+            .collect();
+
+        // The first row went out; the second was journaled but the process
+        // died before broadcasting it
+        let mut journal = BatchJournal::open("testnet", sender, &transfers).unwrap();
+        for (i, row) in journal.rows.iter_mut().take(2).enumerate() {
+            let request = row.transfer.request(sender, i as u64, 1, 31);
+            let tx = client.sign_transaction(request).await.unwrap();
+            if i == 0 {
+                client.broadcast(&tx).await.unwrap();
+            }
+            row.nonce = Some(i as u64);
+            row.tx_hash = Some(*tx.tx_hash());
+            row.raw_tx = Some(tx.encoded_2718().into());
+            row.status = RowStatus::Signed;
+        }
+        journal.save().unwrap();
+
+        let settings = BulkSettings {
+            send_window: 2,
+            block_time_secs: 1,
+            confirmation_blocks: 1,
+        };
+
+        // Running the same input again picks up the journal
+        let mut resumed = BatchJournal::open("testnet", sender, &transfers).unwrap();
+        assert_eq!(resumed.id, journal.id);
+        assert_eq!(resumed.count(RowStatus::Signed), 2);
+        resumed.send(&client, 1, &settings).await.unwrap();
+        resumed.wait_for_confirmations(&client, &settings).await.unwrap();
+        assert!(resumed.is_settled());
+        assert_eq!(resumed.count(RowStatus::Confirmed), 3);
+
+        let sent = env.node.sent();
+        assert_eq!(sent.len(), 3);
+        assert_eq!(*sent[1].tx_hash(), journal.rows[1].tx_hash.unwrap());
+        for line in 1..=3u8 {
+            assert_eq!(env.node.balance(Address::with_last_byte(line)), U256::from(1000 * line as u64));
+        }
+
+        // History keeps the fee of every transfer from its receipt
+        let store = crate::utils::tx_store::TransactionStore::load("testnet", &sender).unwrap();
+        assert_eq!(store.transactions.len(), 3);
+        for tx in &store.transactions {
+            assert_eq!(tx.status, crate::types::transaction::TransactionStatus::Success);
+            assert_eq!(tx.gas * tx.gas_price, U256::from(21_000u64 * 60_000_000));
+        }
+
+        // An edited file is a new batch, but its rows that already went out are flagged
+        let edited = BatchJournal::open("testnet", sender, &transfers[1..]).unwrap();
+        assert_ne!(edited.id, resumed.id);
+        let flagged = edited.sent_elsewhere(&BatchJournal::list().unwrap());
+        assert_eq!(flagged, vec![(2, resumed.id.clone()), (3, resumed.id.clone())]);
+
+        // Running a finished batch again is a new payment under its own id,
+        // which the caller confirms before sending
+        let mut again = BatchJournal::open("testnet", sender, &transfers).unwrap();
+        assert_ne!(again.id, resumed.id);
+        assert_eq!(again.count(RowStatus::Planned), 3);
+        let earlier: Vec<String> = again.earlier_runs().unwrap().into_iter().map(|j| j.id).collect();
+        assert_eq!(earlier, vec![resumed.id.clone()]);
+        assert_eq!(again.sent_elsewhere(&BatchJournal::list().unwrap()).len(), 3);
+        again.save().unwrap();
+        again.send(&client, 1, &settings).await.unwrap();
+        again.wait_for_confirmations(&client, &settings).await.unwrap();
+        assert_eq!(again.count(RowStatus::Confirmed), 3);
+        assert_eq!(env.node.sent().len(), 6);
+        for line in 1..=3u8 {
+            assert_eq!(env.node.balance(Address::with_last_byte(line)), U256::from(2000 * line as u64));
+        }
+
+        // The second run is settled too, so a third gets yet another id
+        let third = BatchJournal::open("testnet", sender, &transfers).unwrap();
+        assert!(third.id != resumed.id && third.id != again.id);
+        assert_eq!(third.earlier_runs().unwrap().len(), 2);
+
+        let path = env.home.path().join("settlement.csv");
+        resumed.write_settlement(&path).unwrap();
+        let csv = fs::read_to_string(&path).unwrap();
+        assert_eq!(csv.lines().count(), 4);
+        assert!(csv.lines().nth(2).unwrap().contains(",confirmed,1,"));
+    }
+
+    #[tokio::test]
+    async fn test_confirmations_outlast_the_request_timeout() {
+        let signer = PrivateKeySigner::random();
+        let sender = signer.address();
+        let mut fixtures = Fixtures::default();
+        fixtures.balances.insert(sender, U256::from(10u64).pow(U256::from(18)));
+        let env = TestEnv::start(fixtures).await;
+        let manager = crate::config::ConfigManager::new().unwrap();
+        let mut app_config = manager.load().unwrap();
+        app_config.http.request_timeout_secs = 1;
+        app_config.bulk = BulkSettings {
+            send_window: 2,
+            block_time_secs: 1,
+            confirmation_blocks: 5,
+        };
+        manager.save(&app_config).unwrap();
+        http::reload();
+        let request_timeout = http::client().policy().request_timeout();
+
+        let mut config = Config::default();
+        config.network.rpc_url = env.node.url().to_string();
+        config.wallet.private_key = Some(Zeroizing::new(hex::encode(signer.to_bytes())));
+        let client = EthClient::new(&config, None).await.unwrap();
+        let transfers: Vec<PlannedTransfer> = (1..=2)
+            .map(|line| PlannedTransfer {
+                line,
+                recipient: format!("payee {}", line),
+                to: Address::with_last_byte(line as u8),
+                asset: Asset::rbtc(),
+                amount: U256::from(1000 * line),
+                gas: 21_000,
+            })
+            .collect();
+
+        // The node accepts the batch but mines it only after a request
+        // would have timed out
+        env.node.hold_mining(true);
+        let mut journal = BatchJournal::open("testnet", sender, &transfers).unwrap();
+        journal.send(&client, 1, &app_config.bulk).await.unwrap();
+        let node = env.node.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(request_timeout + Duration::from_millis(500)).await;
+            node.hold_mining(false);
+        });
+
+        let started = Instant::now();
+        assert!(app_config.bulk.confirmation_timeout(journal.rows.len()) > request_timeout);
+        journal.wait_for_confirmations(&client, &app_config.bulk).await.unwrap();
+        assert!(started.elapsed() > request_timeout);
+        assert_eq!(journal.count(RowStatus::Confirmed), 2);
+        assert!(journal.is_settled());
+    }
+
+    #[tokio::test]
+    async fn test_pipelined_send_signs_again_when_a_nonce_is_taken() {
+        let signer = PrivateKeySigner::random();
+        let sender = signer.address();
+        let mut fixtures = Fixtures::default();
+        fixtures.balances.insert(sender, U256::from(10u64).pow(U256::from(18)));
+        let env = TestEnv::start(fixtures).await;
+        let mut config = Config::default();
+        config.network.rpc_url = env.node.url().to_string();
+        config.wallet.private_key = Some(Zeroizing::new(hex::encode(signer.to_bytes())));
+        let client = EthClient::new(&config, None).await.unwrap();
+        let other_client = EthClient::new(&config, None).await.unwrap();
+
+        let transfers: Vec<PlannedTransfer> = (1..=7)
+            .map(|line| PlannedTransfer {
+                line,
+                recipient: format!("payee {}", line),
+                to: Address::with_last_byte(line as u8),
+                asset: Asset::rbtc(),
+                amount: U256::from(1000 * line),
+                gas: 21_000,
+            })
+            .collect();
+
+        // The client's nonces start at 0, then another transaction takes it
+        client.advance_nonce_to(0).await.unwrap();
+        other_client
+            .send_transaction(Address::with_last_byte(0xee), U256::from(1), None)
+            .await
+            .unwrap();
+
+        // Blocks keep coming, so the taken nonce's transaction is seen to be
+        // unknown after the next one too
+        let node = env.node.clone();
+        tokio::spawn(async move {
+            loop {
+                tokio::time::sleep(Duration::from_millis(200)).await;
+                node.mine();
+            }
+        });
+        let settings = BulkSettings {
+            send_window: 3,
+            block_time_secs: 1,
+            confirmation_blocks: 5,
+        };
+        let mut journal = BatchJournal::open("testnet", sender, &transfers).unwrap();
+        journal.send(&client, 1, &settings).await.unwrap();
+        journal.wait_for_confirmations(&client, &settings).await.unwrap();
+        assert_eq!(journal.count(RowStatus::Confirmed), 7);
+
+        let mut nonces: Vec<u64> = journal.rows.iter().filter_map(|row| row.nonce).collect();
+        nonces.sort();
+        assert_eq!(nonces, (1..=7).collect::<Vec<_>>());
+        assert_eq!(env.node.sent().len(), 8);
+        for line in 1..=7u8 {
+            assert_eq!(env.node.balance(Address::with_last_byte(line)), U256::from(1000 * line as u64));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_taken_nonce_is_left_for_review_when_the_node_cannot_tell() {
+        let signer = PrivateKeySigner::random();
+        let sender = signer.address();
+        let mut fixtures = Fixtures::default();
+        fixtures.balances.insert(sender, U256::from(10u64).pow(U256::from(18)));
+        let env = TestEnv::start(fixtures).await;
+        let mut config = Config::default();
+        config.network.rpc_url = env.node.url().to_string();
+        config.wallet.private_key = Some(Zeroizing::new(hex::encode(signer.to_bytes())));
+        let client = EthClient::new(&config, None).await.unwrap();
+        let other_client = EthClient::new(&config, None).await.unwrap();
+
+        let transfers: Vec<PlannedTransfer> = (1..=3)
+            .map(|line| PlannedTransfer {
+                line,
+                recipient: format!("payee {}", line),
+                to: Address::with_last_byte(line as u8),
+                asset: Asset::rbtc(),
+                amount: U256::from(1000 * line),
+                gas: 21_000,
+            })
+            .collect();
+
+        client.advance_nonce_to(0).await.unwrap();
+        other_client
+            .send_transaction(Address::with_last_byte(0xee), U256::from(1), None)
+            .await
+            .unwrap();
+
+        // Receipts cannot be looked up, so the first row is not signed again
+        env.node.fail("eth_getTransactionReceipt", true);
+        let mut journal = BatchJournal::open("testnet", sender, &transfers).unwrap();
+        let settings = BulkSettings {
+            send_window: 3,
+            block_time_secs: 1,
+            confirmation_blocks: 5,
+        };
+        journal.send(&client, 1, &settings).await.unwrap();
+        env.node.fail("eth_getTransactionReceipt", false);
+        journal.wait_for_confirmations(&client, &settings).await.unwrap();
+
+        assert_eq!(journal.rows[0].status, RowStatus::Review);
+        assert_eq!(journal.rows[0].nonce, Some(0));
+        assert_eq!(journal.count(RowStatus::Confirmed), 2);
+        assert!(journal.is_settled());
+        assert_eq!(env.node.sent().len(), 3);
+        assert_eq!(env.node.balance(Address::with_last_byte(1)), U256::ZERO);
+    }
+
+    #[tokio::test]
+    async fn test_taken_nonce_is_not_signed_again_without_a_new_block() {
+        let signer = PrivateKeySigner::random();
+        let sender = signer.address();
+        let mut fixtures = Fixtures::default();
+        fixtures.balances.insert(sender, U256::from(10u64).pow(U256::from(18)));
+        let env = TestEnv::start(fixtures).await;
+        let mut config = Config::default();
+        config.network.rpc_url = env.node.url().to_string();
+        config.wallet.private_key = Some(Zeroizing::new(hex::encode(signer.to_bytes())));
+        let client = EthClient::new(&config, None).await.unwrap();
+        let other_client = EthClient::new(&config, None).await.unwrap();
+
+        let transfers = vec![PlannedTransfer {
+            line: 1,
+            recipient: "payee 1".to_string(),
+            to: Address::with_last_byte(1),
+            asset: Asset::rbtc(),
+            amount: U256::from(1000),
+            gas: 21_000,
+        }];
+        client.advance_nonce_to(0).await.unwrap();
+        other_client
+            .send_transaction(Address::with_last_byte(0xee), U256::from(1), None)
+            .await
+            .unwrap();
+
+        // The node does not know the refused transaction, but it might only
+        // be lagging, and no later block comes to check it against
+        let settings = BulkSettings {
+            send_window: 1,
+            block_time_secs: 1,
+            confirmation_blocks: 1,
+        };
+        let mut journal = BatchJournal::open("testnet", sender, &transfers).unwrap();
+        journal.send(&client, 1, &settings).await.unwrap();
+
+        assert_eq!(journal.rows[0].status, RowStatus::Review);
+        assert!(journal.is_settled());
+        assert_eq!(env.node.sent().len(), 1);
+        assert_eq!(env.node.balance(Address::with_last_byte(1)), U256::ZERO);
+    }
+}
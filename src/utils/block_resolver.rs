@@ -0,0 +1,395 @@
+use crate::utils::constants;
+use crate::utils::secure_fs;
+use alloy::providers::Provider;
+use crate::utils::rpc_pool::PoolProvider;
+use anyhow::{Result, anyhow};
+use chrono::{
+    DateTime, Datelike, Days, FixedOffset, Local, Months, NaiveDate, NaiveDateTime, NaiveTime,
+    TimeZone, Utc,
+};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+
+/// A time range; `start` is inclusive and `end` exclusive
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct DateRange {
+    pub start: Option<DateTime<Utc>>,
+    pub end: Option<DateTime<Utc>>,
+}
+
+/// Block bounds matching a [`DateRange`], both inclusive
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct BlockRange {
+    pub from_block: Option<u64>,
+    pub to_block: Option<u64>,
+}
+
+impl BlockRange {
+    pub fn contains(&self, block: u64) -> bool {
+        self.from_block.is_none_or(|from| block >= from) && self.to_block.is_none_or(|to| block <= to)
+    }
+}
+
+/// Timezone used to interpret calendar dates
+#[derive(Debug, Clone, Copy)]
+pub enum DateZone {
+    Local,
+    Fixed(FixedOffset),
+}
+
+impl DateZone {
+    /// Parses `local`, `UTC`/`Z` or an offset such as `+02:00`, `-0530` or `UTC+3`
+    pub fn parse(input: &str) -> Result<Self> {
+        let input = input.trim();
+        let lower = input.to_lowercase();
+        if lower.is_empty() || lower == "local" {
+            return Ok(DateZone::Local);
+        }
+        if lower == "utc" || lower == "z" || lower == "gmt" {
+            return Ok(DateZone::Fixed(FixedOffset::east_opt(0).unwrap()));
+        }
+        let offset = lower
+            .strip_prefix("utc")
+            .or_else(|| lower.strip_prefix("gmt"))
+            .unwrap_or(&lower);
+        let (sign, rest) = match offset.chars().next() {
+            Some('+') => (1, &offset[1..]),
+            Some('-') => (-1, &offset[1..]),
+            _ => return Err(anyhow!("Invalid timezone '{}': use local, UTC or an offset like +02:00", input)),
+        };
+        let digits: String = rest.chars().filter(|c| c.is_ascii_digit()).collect();
+        let (hours, minutes) = match (rest.contains(':'), digits.len()) {
+            (true, _) => {
+                let mut parts = rest.splitn(2, ':');
+                let h = parts.next().unwrap_or_default().parse::<i32>();
+                let m = parts.next().unwrap_or_default().parse::<i32>();
+                (h.ok(), m.ok())
+            }
+            (false, 1 | 2) => (digits.parse().ok(), Some(0)),
+            (false, 4) => (digits[..2].parse().ok(), digits[2..].parse().ok()),
+            _ => (None, None),
+        };
+        let (Some(hours), Some(minutes)) = (hours, minutes) else {
+            return Err(anyhow!("Invalid timezone offset '{}'", input));
+        };
+        FixedOffset::east_opt(sign * (hours * 3600 + minutes * 60))
+            .map(DateZone::Fixed)
+            .ok_or_else(|| anyhow!("Timezone offset out of range: '{}'", input))
+    }
+
+    /// Formats a point in time as seen in this zone
+    pub fn format(self, datetime: DateTime<Utc>, fmt: &str) -> String {
+        match self {
+            DateZone::Local => datetime.with_timezone(&Local).format(fmt).to_string(),
+            DateZone::Fixed(offset) => datetime.with_timezone(&offset).format(fmt).to_string(),
+        }
+    }
+
+    fn to_utc(self, datetime: NaiveDateTime) -> Result<DateTime<Utc>> {
+        let resolved = match self {
+            DateZone::Local => Local.from_local_datetime(&datetime).earliest().map(|d| d.to_utc()),
+            DateZone::Fixed(offset) => offset.from_local_datetime(&datetime).earliest().map(|d| d.to_utc()),
+        };
+        resolved.ok_or_else(|| anyhow!("Invalid local time {}", datetime))
+    }
+
+    fn today(self, now: DateTime<Utc>) -> NaiveDate {
+        match self {
+            DateZone::Local => now.with_timezone(&Local).date_naive(),
+            DateZone::Fixed(offset) => now.with_timezone(&offset).date_naive(),
+        }
+    }
+}
+
+/// Parses history `--from`/`--to` values into a time range.
+///
+/// Each value may be a date (`2024-03-01`), a date and time
+/// (`2024-03-01 14:30`), a month (`2024-03`), `today`, `yesterday`,
+/// `this week`, `this month`, `last month`, `this year`, `last year`, or a
+/// relative range such as `last 30 days` or `last 2 weeks`. `--from` alone
+/// leaves the end open, except for relative ranges, which end now. A `--to`
+/// value that names a period includes all of it, so `--from 2024-03 --to
+/// 2024-03` selects March.
+pub fn parse_date_range(from: Option<&str>, to: Option<&str>, zone: DateZone) -> Result<DateRange> {
+    parse_date_range_at(from, to, zone, Utc::now())
+}
+
+fn parse_date_range_at(
+    from: Option<&str>,
+    to: Option<&str>,
+    zone: DateZone,
+    now: DateTime<Utc>,
+) -> Result<DateRange> {
+    let mut range = DateRange::default();
+    if let Some(from) = from.filter(|s| !s.trim().is_empty()) {
+        let (start, end) = parse_period(from, zone, now)?;
+        range.start = Some(start);
+        range.end = end.filter(|_| is_relative_range(from));
+    }
+    if let Some(to) = to.filter(|s| !s.trim().is_empty()) {
+        let (start, end) = parse_period(to, zone, now)?;
+        // A bare time is an exact bound, a period includes all of it
+        range.end = Some(end.unwrap_or(start));
+    }
+    if let (Some(start), Some(end)) = (range.start, range.end)
+        && start >= end
+    {
+        return Err(anyhow!("The start of the date range must be before its end"));
+    }
+    Ok(range)
+}
+
+/// Whether a value is a span ending now, such as `last 30 days`
+fn is_relative_range(input: &str) -> bool {
+    let text = input.trim().to_lowercase();
+    let mut parts = text.split_whitespace();
+    parts.next() == Some("last")
+        && parts.next().is_some_and(|count| count.parse::<u32>().is_ok())
+        && parts.next().is_some()
+        && parts.next().is_none()
+}
+
+/// Parses one value into its start and, for periods, its exclusive end
+fn parse_period(input: &str, zone: DateZone, now: DateTime<Utc>) -> Result<(DateTime<Utc>, Option<DateTime<Utc>>)> {
+    let text = input.trim().to_lowercase();
+    let today = zone.today(now);
+    let midnight = |date: NaiveDate| zone.to_utc(date.and_time(NaiveTime::MIN));
+    let day_period = |first: NaiveDate, after: NaiveDate| -> Result<(DateTime<Utc>, Option<DateTime<Utc>>)> {
+        Ok((midnight(first)?, Some(midnight(after)?)))
+    };
+    let month_start = |date: NaiveDate| date.with_day(1).unwrap_or(date);
+
+    match text.as_str() {
+        "today" => return day_period(today, today + Days::new(1)),
+        "yesterday" => return day_period(today - Days::new(1), today),
+        "this week" => {
+            let start = today - Days::new(today.weekday().num_days_from_monday() as u64);
+            return day_period(start, start + Days::new(7));
+        }
+        "this month" => {
+            let start = month_start(today);
+            return day_period(start, start + Months::new(1));
+        }
+        "last month" => {
+            let start = month_start(today) - Months::new(1);
+            return day_period(start, start + Months::new(1));
+        }
+        "this year" => {
+            let start = NaiveDate::from_ymd_opt(today.year(), 1, 1).unwrap_or(today);
+            return day_period(start, start + Months::new(12));
+        }
+        "last year" => {
+            let start = NaiveDate::from_ymd_opt(today.year() - 1, 1, 1).unwrap_or(today);
+            return day_period(start, start + Months::new(12));
+        }
+        _ => {}
+    }
+
+    // "last 30 days", "last 2 weeks", "last 6 months": up to now
+    if let Some(rest) = text.strip_prefix("last ") {
+        let mut parts = rest.split_whitespace();
+        if let (Some(count), Some(unit), None) = (parts.next(), parts.next(), parts.next())
+            && let Ok(count) = count.parse::<u32>()
+        {
+            let start = match unit.trim_end_matches('s') {
+                "hour" => now - chrono::Duration::hours(count as i64),
+                "day" => now - chrono::Duration::days(count as i64),
+                "week" => now - chrono::Duration::weeks(count as i64),
+                "month" => midnight(today - Months::new(count))?,
+                "year" => midnight(today - Months::new(count * 12))?,
+                _ => return Err(anyhow!("Unknown unit '{}' in '{}'", unit, input)),
+            };
+            return Ok((start, Some(now)));
+        }
+    }
+
+    if let Ok(date) = NaiveDate::parse_from_str(&text, "%Y-%m-%d") {
+        return day_period(date, date + Days::new(1));
+    }
+    if let Ok(date) = NaiveDate::parse_from_str(&format!("{}-01", text), "%Y-%m-%d") {
+        return day_period(date, date + Months::new(1));
+    }
+    for format in ["%Y-%m-%d %H:%M:%S", "%Y-%m-%d %H:%M", "%Y-%m-%dt%H:%M:%S", "%Y-%m-%dt%H:%M"] {
+        if let Ok(datetime) = NaiveDateTime::parse_from_str(&text, format) {
+            return Ok((zone.to_utc(datetime)?, None));
+        }
+    }
+    if let Ok(datetime) = DateTime::parse_from_rfc3339(input.trim()) {
+        return Ok((datetime.to_utc(), None));
+    }
+
+    Err(anyhow!(
+        "Invalid date '{}': use YYYY-MM-DD, YYYY-MM, 'last 30 days', 'last month' or similar",
+        input
+    ))
+}
+
+/// Maps timestamps to block numbers by binary search over block headers.
+/// Timestamps seen are cached on disk per network.
+pub struct BlockResolver {
+    provider: PoolProvider,
+    cache_path: PathBuf,
+    cache: BTreeMap<u64, u64>,
+}
+
+impl BlockResolver {
+    pub fn new(provider: PoolProvider, network: &str) -> Self {
+        let cache_path = constants::history_dir(network).join("block_timestamps.json");
+        let cache = fs::read_to_string(&cache_path)
+            .ok()
+            .and_then(|data| serde_json::from_str(&data).ok())
+            .unwrap_or_default();
+        Self {
+            provider,
+            cache_path,
+            cache,
+        }
+    }
+
+    /// Resolves a time range to the blocks mined within it
+    pub async fn resolve(&mut self, range: &DateRange) -> Result<BlockRange> {
+        let latest = self
+            .provider
+            .get_block_number()
+            .await
+            .map_err(|e| anyhow!("Failed to get latest block: {}", e))?;
+
+        let from_block = match range.start {
+            Some(start) => Some(self.first_block_at_or_after(start.timestamp() as u64, latest).await?),
+            None => None,
+        };
+        let to_block = match range.end {
+            Some(end) => Some(
+                self.first_block_at_or_after(end.timestamp() as u64, latest)
+                    .await?
+                    .saturating_sub(1),
+            ),
+            None => None,
+        };
+
+        self.save_cache();
+        Ok(BlockRange { from_block, to_block })
+    }
+
+    /// First block with a timestamp at or after `timestamp`, or `latest + 1`
+    /// if no such block has been mined yet
+    async fn first_block_at_or_after(&mut self, timestamp: u64, latest: u64) -> Result<u64> {
+        if self.block_timestamp(latest).await? < timestamp {
+            return Ok(latest + 1);
+        }
+
+        // Narrow the search with cached blocks on either side of the target
+        let mut low = self
+            .cache
+            .iter()
+            .filter(|(block, ts)| **block <= latest && **ts < timestamp)
+            .map(|(block, _)| block + 1)
+            .max()
+            .unwrap_or(0);
+        let mut high = self
+            .cache
+            .iter()
+            .filter(|(block, ts)| **block <= latest && **ts >= timestamp)
+            .map(|(block, _)| *block)
+            .min()
+            .unwrap_or(latest);
+
+        while low < high {
+            let mid = low + (high - low) / 2;
+            if self.block_timestamp(mid).await? < timestamp {
+                low = mid + 1;
+            } else {
+                high = mid;
+            }
+        }
+        Ok(low)
+    }
+
+    async fn block_timestamp(&mut self, number: u64) -> Result<u64> {
+        if let Some(timestamp) = self.cache.get(&number) {
+            return Ok(*timestamp);
+        }
+        let block = self
+            .provider
+            .raw_request::<_, Value>(
+                "eth_getBlockByNumber".into(),
+                (format!("0x{:x}", number), false),
+            )
+            .await
+            .map_err(|e| anyhow!("Failed to get block {}: {}", number, e))?;
+        let timestamp = block["timestamp"]
+            .as_str()
+            .and_then(|s| u64::from_str_radix(s.trim_start_matches("0x"), 16).ok())
+            .ok_or_else(|| anyhow!("Block {} has no timestamp", number))?;
+        self.cache.insert(number, timestamp);
+        Ok(timestamp)
+    }
+
+    fn save_cache(&self) {
+        if let Ok(json) = serde_json::to_string(&self.cache) {
+            let _ = secure_fs::write_secure(&self.cache_path, &json);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utc(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().to_utc()
+    }
+
+    #[test]
+    fn test_month_covers_whole_month() {
+        let zone = DateZone::parse("UTC").unwrap();
+        let now = utc("2024-05-15T12:00:00Z");
+        let range = parse_date_range_at(Some("2024-03"), Some("2024-03"), zone, now).unwrap();
+        assert_eq!(range.start, Some(utc("2024-03-01T00:00:00Z")));
+        assert_eq!(range.end, Some(utc("2024-04-01T00:00:00Z")));
+
+        let range = parse_date_range_at(Some("last month"), Some("last month"), zone, now).unwrap();
+        assert_eq!(range.start, Some(utc("2024-04-01T00:00:00Z")));
+        assert_eq!(range.end, Some(utc("2024-05-01T00:00:00Z")));
+    }
+
+    #[test]
+    fn test_from_alone_leaves_end_open() {
+        let zone = DateZone::parse("UTC").unwrap();
+        let now = utc("2024-05-15T12:00:00Z");
+        let range = parse_date_range_at(Some("2024-03-01"), None, zone, now).unwrap();
+        assert_eq!(range.start, Some(utc("2024-03-01T00:00:00Z")));
+        assert_eq!(range.end, None);
+
+        let range = parse_date_range_at(Some("2024-03"), None, zone, now).unwrap();
+        assert_eq!(range.end, None);
+    }
+
+    #[test]
+    fn test_dates_use_timezone_and_include_end_day() {
+        let zone = DateZone::parse("+02:00").unwrap();
+        let now = utc("2024-05-15T12:00:00Z");
+        let range = parse_date_range_at(Some("2024-03-01"), Some("2024-03-31"), zone, now).unwrap();
+        assert_eq!(range.start, Some(utc("2024-02-29T22:00:00Z")));
+        assert_eq!(range.end, Some(utc("2024-03-31T22:00:00Z")));
+    }
+
+    #[test]
+    fn test_relative_range_ends_now() {
+        let zone = DateZone::parse("UTC").unwrap();
+        let now = utc("2024-05-15T12:00:00Z");
+        let range = parse_date_range_at(Some("last 30 days"), None, zone, now).unwrap();
+        assert_eq!(range.start, Some(utc("2024-04-15T12:00:00Z")));
+        assert_eq!(range.end, Some(now));
+    }
+
+    #[test]
+    fn test_invalid_input() {
+        assert!(DateZone::parse("Mars/Olympus").is_err());
+        let zone = DateZone::parse("local").unwrap();
+        assert!(parse_date_range(Some("not a date"), None, zone).is_err());
+        assert!(parse_date_range(Some("2024-03-02"), Some("2024-03-01"), zone).is_err());
+    }
+}
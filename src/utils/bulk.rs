@@ -0,0 +1,701 @@
+use crate::commands::tokens::TokenRegistry;
+use crate::types::contacts::Contact;
+use crate::utils::eth::{EthClient, IERC20};
+use crate::utils::rns;
+use crate::utils::rpc::ChainRpc;
+use alloy::network::TransactionBuilder;
+use alloy::primitives::{Address, U256, utils::format_units, utils::parse_units};
+use alloy::providers::Provider;
+use alloy::rpc::types::TransactionRequest;
+use alloy::sol_types::SolCall;
+use anyhow::{Result, anyhow};
+use console::style;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+use std::str::FromStr;
+
+/// One transfer as written in the input, before anything is resolved
+#[derive(Debug, Clone, PartialEq)]
+pub struct BulkRow {
+    /// Line in the input file, or position for other sources
+    pub line: usize,
+    /// Address, contact name or RNS name
+    pub recipient: String,
+    pub amount: String,
+    /// Token symbol or address; `None` uses the token picked for the run
+    pub token: Option<String>,
+}
+
+/// JSON input: an array of `{ "to", "value", "token" }`
+#[derive(Debug, Deserialize)]
+struct TransferInput {
+    to: String,
+    value: String,
+    token: Option<String>,
+}
+
+pub fn read_json(path: &Path) -> Result<Vec<BulkRow>> {
+    let content = std::fs::read_to_string(path).map_err(|e| anyhow!("Failed to read file: {}", e))?;
+    let inputs: Vec<TransferInput> =
+        serde_json::from_str(&content).map_err(|e| anyhow!("Failed to parse JSON: {}", e))?;
+    Ok(inputs
+        .into_iter()
+        .enumerate()
+        .map(|(i, input)| BulkRow {
+            line: i + 1,
+            recipient: input.to,
+            amount: input.value,
+            token: input.token,
+        })
+        .collect())
+}
+
+/// Which CSV columns hold the recipient, amount and token
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CsvMapping {
+    pub recipient: usize,
+    pub amount: usize,
+    pub token: Option<usize>,
+}
+
+impl CsvMapping {
+    /// Maps columns by their usual header names; `None` when the recipient
+    /// or amount column cannot be told apart. Names are tried in order, so
+    /// an address column wins over a name column that would have to be
+    /// looked up in the contacts.
+    pub fn detect(headers: &[String]) -> Option<Self> {
+        let find = |names: &[&str]| {
+            names.iter().find_map(|name| {
+                headers
+                    .iter()
+                    .position(|h| h.trim().eq_ignore_ascii_case(name))
+            })
+        };
+        Some(Self {
+            recipient: find(&["address", "wallet", "recipient", "to", "rns", "payee", "name"])?,
+            amount: find(&["amount", "value", "net pay", "pay", "salary"])?,
+            token: find(&["token", "asset", "currency", "symbol"]),
+        })
+    }
+}
+
+pub fn csv_headers(path: &Path) -> Result<Vec<String>> {
+    let mut reader = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .from_path(path)
+        .map_err(|e| anyhow!("Failed to read file: {}", e))?;
+    Ok(reader.headers()?.iter().map(str::to_string).collect())
+}
+
+/// Reads transfer rows from a CSV file; rows with no recipient and no
+/// amount are skipped as blank
+pub fn read_csv(path: &Path, mapping: CsvMapping) -> Result<Vec<BulkRow>> {
+    let mut reader = csv::ReaderBuilder::new()
+        .flexible(true)
+        .trim(csv::Trim::All)
+        .from_path(path)
+        .map_err(|e| anyhow!("Failed to read file: {}", e))?;
+    let mut rows = Vec::new();
+    for (i, record) in reader.records().enumerate() {
+        // Line 1 is the header
+        let line = i + 2;
+        let record = record.map_err(|e| anyhow!("Line {}: {}", line, e))?;
+        let field = |index: usize| record.get(index).unwrap_or("").to_string();
+        let (recipient, amount) = (field(mapping.recipient), field(mapping.amount));
+        if recipient.is_empty() && amount.is_empty() {
+            continue;
+        }
+        rows.push(BulkRow {
+            line,
+            recipient,
+            amount,
+            token: mapping.token.map(field).filter(|t| !t.is_empty()),
+        });
+    }
+    Ok(rows)
+}
+
+/// RBTC (`address` of `None`) or a token
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Asset {
+    pub address: Option<Address>,
+    pub symbol: String,
+    pub decimals: u8,
+}
+
+impl Asset {
+    pub fn rbtc() -> Self {
+        Self {
+            address: None,
+            symbol: "RBTC".to_string(),
+            decimals: 18,
+        }
+    }
+
+    pub fn format(&self, amount: U256) -> String {
+        format!("{} {}", format_units(amount, self.decimals).unwrap_or_default(), self.symbol)
+    }
+}
+
+/// A row that passed validation, ready to be built and sent
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PlannedTransfer {
+    pub line: usize,
+    pub recipient: String,
+    pub to: Address,
+    pub asset: Asset,
+    pub amount: U256,
+    pub gas: u64,
+}
+
+impl PlannedTransfer {
+    /// Unsigned transaction for this transfer
+    pub fn request(&self, from: Address, nonce: u64, gas_price: u128, chain_id: u64) -> TransactionRequest {
+        let tx = match self.asset.address {
+            Some(token) => TransactionRequest::default()
+                .with_to(token)
+                .with_value(U256::ZERO)
+                .with_input(IERC20::transferCall { recipient: self.to, amount: self.amount }.abi_encode()),
+            None => TransactionRequest::default().with_to(self.to).with_value(self.amount),
+        };
+        tx.with_from(from)
+            .with_nonce(nonce)
+            .with_gas_price(gas_price)
+            .with_gas_limit(self.gas)
+            .with_chain_id(chain_id)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+/// Validation result of one input row
+#[derive(Debug, Clone)]
+pub struct RowCheck {
+    pub row: BulkRow,
+    pub to: Option<Address>,
+    pub asset: Option<Asset>,
+    pub amount: Option<U256>,
+    pub gas: Option<u64>,
+    pub issues: Vec<(Severity, String)>,
+}
+
+impl RowCheck {
+    pub fn has_errors(&self) -> bool {
+        self.issues.iter().any(|(severity, _)| *severity == Severity::Error)
+    }
+
+    fn error(&mut self, message: impl Into<String>) {
+        self.issues.push((Severity::Error, message.into()));
+    }
+
+    fn warning(&mut self, message: impl Into<String>) {
+        self.issues.push((Severity::Warning, message.into()));
+    }
+}
+
+/// Total sent of one asset against what the sender holds
+#[derive(Debug, Clone)]
+pub struct AssetTotal {
+    pub asset: Asset,
+    pub total: U256,
+    pub balance: Option<U256>,
+}
+
+/// Outcome of checking every row before anything is sent
+#[derive(Debug, Clone)]
+pub struct ValidationReport {
+    pub rows: Vec<RowCheck>,
+    pub totals: Vec<AssetTotal>,
+    pub gas_price: u128,
+    /// Fee for every valid row at the current gas price
+    pub gas_cost: U256,
+    /// Problems with the batch as a whole, such as an insufficient balance
+    pub problems: Vec<String>,
+}
+
+impl ValidationReport {
+    /// Rows that passed, in input order
+    pub fn transfers(&self) -> Vec<PlannedTransfer> {
+        self.rows
+            .iter()
+            .filter(|check| !check.has_errors())
+            .filter_map(|check| {
+                Some(PlannedTransfer {
+                    line: check.row.line,
+                    recipient: check.row.recipient.clone(),
+                    to: check.to?,
+                    asset: check.asset.clone()?,
+                    amount: check.amount?,
+                    gas: check.gas?,
+                })
+            })
+            .collect()
+    }
+
+    pub fn error_count(&self) -> usize {
+        self.rows.iter().filter(|check| check.has_errors()).count()
+    }
+
+    pub fn warning_count(&self) -> usize {
+        self.rows
+            .iter()
+            .flat_map(|check| &check.issues)
+            .filter(|(severity, _)| *severity == Severity::Warning)
+            .count()
+    }
+
+    /// No row errors and enough funds for all of it
+    pub fn is_clean(&self) -> bool {
+        self.error_count() == 0 && self.problems.is_empty()
+    }
+
+    pub fn print(&self) {
+        println!("\n📋 Validation Report");
+        println!("====================");
+        for check in &self.rows {
+            let status = if check.has_errors() {
+                style("✗").red()
+            } else if check.issues.is_empty() {
+                style("✓").green()
+            } else {
+                style("!").yellow()
+            };
+            let recipient = match check.to {
+                Some(to) if !check.row.recipient.eq_ignore_ascii_case(&to.to_string()) => {
+                    format!("{} → {}", check.row.recipient, to)
+                }
+                _ => check.row.recipient.clone(),
+            };
+            let amount = match (&check.asset, check.amount) {
+                (Some(asset), Some(amount)) => asset.format(amount),
+                _ => format!("{} {}", check.row.amount, check.row.token.as_deref().unwrap_or("")),
+            };
+            let gas = check.gas.map(|g| format!("gas {}", g)).unwrap_or_default();
+            println!(
+                "{} line {:>4}  {}  {}  {}",
+                status,
+                check.row.line,
+                recipient,
+                style(amount).bold(),
+                style(gas).dim()
+            );
+            for (severity, message) in &check.issues {
+                match severity {
+                    Severity::Error => println!("             {}", style(message).red()),
+                    Severity::Warning => println!("             {}", style(message).yellow()),
+                }
+            }
+        }
+
+        println!("\nTotals:");
+        for total in &self.totals {
+            let balance = total
+                .balance
+                .map(|b| total.asset.format(b))
+                .unwrap_or_else(|| "unknown".to_string());
+            println!("  {} (balance {})", total.asset.format(total.total), balance);
+        }
+        println!("  {} estimated gas", Asset::rbtc().format(self.gas_cost));
+        for problem in &self.problems {
+            println!("{}", style(format!("✗ {}", problem)).red());
+        }
+        println!(
+            "\n{} rows: {} valid, {} with errors, {} warnings",
+            self.rows.len(),
+            self.rows.len() - self.error_count(),
+            self.error_count(),
+            self.warning_count()
+        );
+    }
+}
+
+/// Builds the unsigned transaction of every valid row, with consecutive
+/// nonces from the sender's pending count, and prints them. Nothing is
+/// signed or sent.
+pub async fn dry_run(client: &EthClient, sender: Address, report: &ValidationReport) -> Result<Vec<TransactionRequest>> {
+    let chain_id = client.chain_id().await?;
+    let nonce = client
+        .provider()
+        .get_transaction_count(sender)
+        .pending()
+        .await
+        .map_err(|e| anyhow!("Failed to get nonce: {}", e))?;
+
+    println!("\n🧪 Dry Run (nothing is sent)");
+    println!("============================");
+    let mut requests = Vec::new();
+    for (i, transfer) in report.transfers().iter().enumerate() {
+        let request = transfer.request(sender, nonce + i as u64, report.gas_price, chain_id);
+        let fee = U256::from(report.gas_price) * U256::from(transfer.gas);
+        println!(
+            "line {:>4}  nonce {}  {} to {}  gas {}  fee {}",
+            transfer.line,
+            nonce + i as u64,
+            style(transfer.asset.format(transfer.amount)).bold(),
+            transfer.to,
+            transfer.gas,
+            Asset::rbtc().format(fee)
+        );
+        requests.push(request);
+    }
+    println!(
+        "\n{} transaction(s), {} in fees at {} wei per gas",
+        requests.len(),
+        Asset::rbtc().format(report.gas_cost),
+        report.gas_price
+    );
+    Ok(requests)
+}
+
+/// Checks bulk transfer rows against the chain before anything is sent
+pub struct Validator<'a> {
+    pub client: &'a EthClient,
+    pub sender: Address,
+    pub network: String,
+    pub registry: &'a TokenRegistry,
+    pub contacts: &'a [Contact],
+    /// Asset for rows that name no token
+    pub default_asset: Asset,
+}
+
+impl Validator<'_> {
+    /// Resolves recipients, tokens and amounts, estimates gas and checks
+    /// the totals against the sender's balances. Every row is checked;
+    /// problems are reported rather than returned as errors.
+    pub async fn validate(&self, rows: Vec<BulkRow>) -> Result<ValidationReport> {
+        let chain_id = self.client.chain_id().await?;
+        let mut token_cache: HashMap<Address, Result<Asset, String>> = HashMap::new();
+        let mut seen: HashMap<(Address, Option<Address>), usize> = HashMap::new();
+        let mut checks = Vec::with_capacity(rows.len());
+
+        for row in rows {
+            let mut check = RowCheck {
+                row,
+                to: None,
+                asset: None,
+                amount: None,
+                gas: None,
+                issues: Vec::new(),
+            };
+
+            match self.recipient(&check.row.recipient, chain_id).await {
+                Ok(to) => check.to = Some(to),
+                Err(e) => check.error(e),
+            }
+            match self.asset(check.row.token.as_deref(), &mut token_cache).await {
+                Ok(asset) => check.asset = Some(asset),
+                Err(e) => check.error(e),
+            }
+            if let Some(asset) = &check.asset {
+                match parse_amount(&check.row.amount, asset.decimals) {
+                    Ok(amount) => check.amount = Some(amount),
+                    Err(e) => check.error(e),
+                }
+            }
+
+            if let (Some(to), Some(token)) = (check.to, check.asset.as_ref().map(|a| a.address)) {
+                if to == self.sender {
+                    check.warning("sends to the sending wallet itself");
+                }
+                if let Some(line) = seen.insert((to, token), check.row.line) {
+                    check.warning(format!("same recipient and token as line {}", line));
+                }
+            }
+
+            if let (false, Some(to), Some(asset), Some(amount)) =
+                (check.has_errors(), check.to, &check.asset, check.amount)
+            {
+                match self
+                    .client
+                    .estimate_gas(Some(self.sender), to, amount, asset.address)
+                    .await
+                {
+                    Ok(gas) => check.gas = Some(gas.to::<u64>()),
+                    Err(e) => check.error(format!("would fail: {}", e)),
+                }
+            }
+            checks.push(check);
+        }
+
+        self.totals(checks).await
+    }
+
+    async fn totals(&self, rows: Vec<RowCheck>) -> Result<ValidationReport> {
+        let gas_price = self.client.gas_price().await?;
+        let mut totals: Vec<AssetTotal> = Vec::new();
+        let mut gas: u64 = 0;
+        for check in rows.iter().filter(|check| !check.has_errors()) {
+            let (Some(asset), Some(amount)) = (&check.asset, check.amount) else {
+                continue;
+            };
+            gas += check.gas.unwrap_or_default();
+            match totals.iter_mut().find(|t| t.asset.address == asset.address) {
+                Some(total) => total.total += amount,
+                None => totals.push(AssetTotal {
+                    asset: asset.clone(),
+                    total: amount,
+                    balance: None,
+                }),
+            }
+        }
+        if !totals.iter().any(|t| t.asset.address.is_none()) {
+            totals.insert(
+                0,
+                AssetTotal {
+                    asset: Asset::rbtc(),
+                    total: U256::ZERO,
+                    balance: None,
+                },
+            );
+        }
+
+        let assets: Vec<Option<Address>> = totals.iter().map(|t| t.asset.address).collect();
+        let balances = self.client.get_balances(&[self.sender], &assets).await?;
+        for (total, balance) in totals.iter_mut().zip(balances.into_iter().flatten()) {
+            total.balance = balance;
+        }
+
+        let gas_cost = U256::from(gas_price) * U256::from(gas);
+        let mut problems = Vec::new();
+        for total in &totals {
+            let needed = match total.asset.address {
+                None => total.total + gas_cost,
+                Some(_) => total.total,
+            };
+            match total.balance {
+                Some(balance) if balance < needed => problems.push(format!(
+                    "Needs {} but the wallet holds {}",
+                    total.asset.format(needed),
+                    total.asset.format(balance)
+                )),
+                None => problems.push(format!("Could not read the {} balance", total.asset.symbol)),
+                _ => {}
+            }
+        }
+
+        Ok(ValidationReport {
+            rows,
+            totals,
+            gas_price,
+            gas_cost,
+            problems,
+        })
+    }
+
+    /// An address (with a valid checksum if it has one), contact name or RNS name
+    async fn recipient(&self, recipient: &str, chain_id: u64) -> Result<Address, String> {
+        let recipient = recipient.trim();
+        if recipient.is_empty() {
+            return Err("no recipient".to_string());
+        }
+        let address = if let Some(hex) = recipient.strip_prefix("0x") {
+            let address = Address::from_str(recipient).map_err(|_| format!("invalid address '{}'", recipient))?;
+            let mixed_case = hex.chars().any(|c| c.is_ascii_uppercase()) && hex.chars().any(|c| c.is_ascii_lowercase());
+            // Rootstock wallets checksum with the chain id (EIP-1191), others without (EIP-55)
+            if mixed_case
+                && Address::parse_checksummed(recipient, Some(chain_id)).is_err()
+                && Address::parse_checksummed(recipient, None).is_err()
+            {
+                return Err(format!("checksum mismatch in '{}'; check for a typo", recipient));
+            }
+            address
+        } else if let Some(contact) = self.contacts.iter().find(|c| c.name.eq_ignore_ascii_case(recipient)) {
+            contact.address
+        } else if rns::is_rns_name(recipient) {
+            rns::resolve(self.client, recipient)
+                .await
+                .map_err(|e| format!("RNS: {}", e))?
+        } else {
+            return Err(format!("'{}' is not an address, contact or RNS name", recipient));
+        };
+        if address == Address::ZERO {
+            return Err("recipient is the zero address".to_string());
+        }
+        Ok(address)
+    }
+
+    async fn asset(
+        &self,
+        token: Option<&str>,
+        cache: &mut HashMap<Address, Result<Asset, String>>,
+    ) -> Result<Asset, String> {
+        let token = match token.map(str::trim).filter(|t| !t.is_empty()) {
+            None => return Ok(self.default_asset.clone()),
+            Some(token) => token,
+        };
+        if token.eq_ignore_ascii_case("rbtc") {
+            return Ok(Asset::rbtc());
+        }
+        let registered = self.registry.tokens(&self.network);
+        if let Some(info) = registered.get(&token.to_uppercase()) {
+            let address = Address::from_str(&info.address).map_err(|_| format!("registry entry for {} is invalid", token))?;
+            return Ok(Asset {
+                address: Some(address),
+                symbol: token.to_uppercase(),
+                decimals: info.decimals,
+            });
+        }
+        let address = Address::from_str(token).map_err(|_| format!("unknown token '{}'", token))?;
+        if address == Address::ZERO {
+            return Ok(Asset::rbtc());
+        }
+        if let Some((symbol, info)) = registered
+            .iter()
+            .find(|(_, info)| info.address.eq_ignore_ascii_case(token))
+        {
+            return Ok(Asset {
+                address: Some(address),
+                symbol: symbol.clone(),
+                decimals: info.decimals,
+            });
+        }
+        if let Some(asset) = cache.get(&address) {
+            return asset.clone();
+        }
+        let asset = self
+            .client
+            .get_token_info(address)
+            .await
+            .map(|(decimals, symbol)| Asset {
+                address: Some(address),
+                symbol,
+                decimals,
+            })
+            .map_err(|_| format!("{} is not an ERC20 token", token));
+        cache.insert(address, asset.clone());
+        asset
+    }
+}
+
+/// Parses a positive amount with at most `decimals` decimal places
+pub fn parse_amount(amount: &str, decimals: u8) -> Result<U256, String> {
+    let amount = amount.trim();
+    if amount.is_empty() {
+        return Err("no amount".to_string());
+    }
+    if amount.starts_with('-') {
+        return Err("amount must be positive".to_string());
+    }
+    if amount.split_once('.').is_some_and(|(_, fraction)| fraction.len() > decimals as usize) {
+        return Err(format!("'{}' has more than {} decimal places", amount, decimals));
+    }
+    let value: U256 = parse_units(amount, decimals)
+        .map_err(|_| format!("invalid amount '{}'", amount))?
+        .into();
+    if value.is_zero() {
+        return Err("amount is zero".to_string());
+    }
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::tokens::TokenInfo;
+    use crate::utils::mock_node::{Fixtures, MockToken, TestEnv};
+
+    #[test]
+    fn test_csv_mapping_prefers_address_over_name() {
+        let headers = |names: &[&str]| names.iter().map(|n| n.to_string()).collect::<Vec<_>>();
+        let mapping = CsvMapping::detect(&headers(&["Name", "Address", "Amount"])).unwrap();
+        assert_eq!((mapping.recipient, mapping.amount, mapping.token), (1, 2, None));
+        let mapping = CsvMapping::detect(&headers(&["Name", "Amount"])).unwrap();
+        assert_eq!(mapping.recipient, 0);
+        assert!(CsvMapping::detect(&headers(&["Employee", "Amount"])).is_none());
+    }
+
+    #[test]
+    fn test_parse_amount_rejects_negative_and_zero() {
+        let one_and_a_half = U256::from(15u64) * U256::from(10u64).pow(U256::from(17u64));
+        assert_eq!(parse_amount("1.5", 18), Ok(one_and_a_half));
+        assert_eq!(parse_amount("-1", 18), Err("amount must be positive".to_string()));
+        assert_eq!(parse_amount(" -0.5", 18), Err("amount must be positive".to_string()));
+        assert_eq!(parse_amount("0", 18), Err("amount is zero".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_every_row_is_checked_before_sending() {
+        let sender = Address::with_last_byte(0xaa);
+        let token = Address::with_last_byte(0x70);
+        let one = U256::from(10u64).pow(U256::from(18));
+        let mut fixtures = Fixtures::default();
+        fixtures.balances.insert(sender, one);
+        fixtures.tokens.insert(
+            token,
+            MockToken {
+                symbol: "USDX".to_string(),
+                decimals: 6,
+                balances: HashMap::from([(sender, U256::from(5_000_000))]),
+            },
+        );
+        let env = TestEnv::start(fixtures).await;
+        let (_, client) = crate::utils::helper::Helper::init_eth_client("testnet").await.unwrap();
+
+        let mut registry = TokenRegistry::default();
+        registry.testnet.insert(
+            "USDX".to_string(),
+            TokenInfo {
+                address: token.to_string(),
+                decimals: 6,
+                ..Default::default()
+            },
+        );
+        let carol = Contact::new("Carol".to_string(), Address::with_last_byte(0xc3), None, vec![]);
+        let validator = Validator {
+            client: &client,
+            sender,
+            network: "testnet".to_string(),
+            registry: &registry,
+            contacts: std::slice::from_ref(&carol),
+            default_asset: Asset::rbtc(),
+        };
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("payroll.csv");
+        let bob = Address::with_last_byte(0xb2).to_string();
+        let bad_checksum = bob.replacen('B', "b", 1).replacen("0x0", "0xA", 1);
+        std::fs::write(
+            &path,
+            format!(
+                "Employee,Wallet,Net Pay,Currency\n\
+                 Bob,{bob},0.1,\n\
+                 Carol,carol,2.5,usdx\n\
+                 Dan,{bad_checksum},1,\n\
+                 Bob again,{bob},0.2,RBTC\n\
+                 Eve,0x00000000000000000000000000000000000000e5,1.0000001,USDX\n\
+                 ,,,\n\
+                 Frank,0x00000000000000000000000000000000000000f6,4,USDX\n"
+            ),
+        )
+        .unwrap();
+        let headers = csv_headers(&path).unwrap();
+        let mapping = CsvMapping::detect(&headers).unwrap();
+        assert_eq!((mapping.recipient, mapping.amount, mapping.token), (1, 2, Some(3)));
+        let rows = read_csv(&path, mapping).unwrap();
+        assert_eq!(rows.len(), 6);
+
+        let report = validator.validate(rows).await.unwrap();
+        let lines_with_errors: Vec<usize> =
+            report.rows.iter().filter(|c| c.has_errors()).map(|c| c.row.line).collect();
+        assert_eq!(lines_with_errors, vec![4, 6]);
+        assert!(report.rows[3].issues[0].1.contains("line 2"));
+        assert_eq!(report.rows[1].to, Some(carol.address));
+
+        // 2.5 + 4 USDX is more than the 5 held
+        assert_eq!(report.transfers().len(), 4);
+        assert_eq!(report.problems.len(), 1);
+        assert!(report.problems[0].contains("USDX"));
+        assert!(!report.is_clean());
+
+        let requests = dry_run(&client, sender, &report).await.unwrap();
+        assert_eq!(requests.len(), 4);
+        assert_eq!(requests[1].to, Some(token.into()));
+        assert_eq!(requests[1].nonce.map(|n| n + 1), requests[2].nonce);
+        assert!(env.node.sent().is_empty());
+    }
+}
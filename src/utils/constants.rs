@@ -1,15 +1,35 @@
 use std::path::PathBuf;
+use crate::utils::paths::AppPaths;
 use crate::utils::secure_fs;
 
-pub fn wallet_file_path() -> PathBuf {
-    let dir = dirs::data_local_dir()
-        .expect("Failed to get data directory")
-        .join("rsk-rust-cli");
+/// Directory holding the wallet file and other local application data of
+/// the active profile
+pub fn data_dir() -> PathBuf {
+    let dir = AppPaths::current().data_dir();
 
     // Ensure the directory exists with secure permissions
     secure_fs::create_dir_secure(&dir).expect("Failed to create wallet directory");
 
-    dir.join("rsk-rust-cli.json")
+    dir
+}
+
+/// Directory holding the config file and API keys of the active profile
+pub fn config_dir() -> PathBuf {
+    AppPaths::current().config_dir()
+}
+
+pub fn wallet_file_path() -> PathBuf {
+    data_dir().join("rsk-rust-cli.json")
+}
+
+/// Directory holding locally indexed transaction history for a network
+pub fn history_dir(network: &str) -> PathBuf {
+    data_dir().join("history").join(network.to_lowercase())
+}
+
+/// Directory holding the journals of bulk transfer batches
+pub fn batches_dir() -> PathBuf {
+    data_dir().join("batches")
 }
 
 pub const METHOD_TYPES: &str = "read";
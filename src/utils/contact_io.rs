@@ -0,0 +1,487 @@
+use crate::types::contacts::Contact;
+use alloy::primitives::Address;
+use anyhow::{Result, anyhow};
+use clap::ValueEnum;
+use console::style;
+use std::path::Path;
+use std::str::FromStr;
+
+/// File formats contacts can be exported to and imported from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ContactFormat {
+    /// The app's own format, with transaction statistics
+    Json,
+    /// One contact per row: name, address, notes and `;`-separated tags
+    Csv,
+    /// vCard 3.0, with the address as `X-RSK-ADDRESS`
+    Vcard,
+}
+
+impl ContactFormat {
+    /// Picks the format matching a file extension
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?.to_lowercase();
+        match extension.as_str() {
+            "json" => Some(Self::Json),
+            "csv" => Some(Self::Csv),
+            "vcf" | "vcard" => Some(Self::Vcard),
+            _ => None,
+        }
+    }
+}
+
+/// What to do with an imported contact whose name or address is taken
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ConflictPolicy {
+    /// Leave the saved contact as it is
+    Keep,
+    /// Replace the saved contact, keeping its history. A contact whose name
+    /// and address match two different saved contacts is kept out.
+    Overwrite,
+    /// Import under a free name; address clashes are kept
+    Rename,
+}
+
+/// vCard properties an address may be stored in
+const VCARD_ADDRESS_FIELDS: &[&str] = &["X-RSK-ADDRESS", "X-ETHEREUM-ADDRESS", "X-ETH-ADDRESS", "X-CRYPTO-ADDRESS"];
+
+/// Contacts read from a file, and the entries that were rejected
+#[derive(Debug, Default)]
+pub struct ParsedContacts {
+    pub contacts: Vec<Contact>,
+    /// Where and why an entry was rejected
+    pub invalid: Vec<String>,
+}
+
+impl ParsedContacts {
+    fn push(&mut self, location: String, name: &str, address: &str, notes: &str, tags: Vec<String>) {
+        match new_contact(name, address, notes, tags) {
+            Ok(contact) => self.contacts.push(contact),
+            Err(e) => self.invalid.push(format!("{}: {}", location, e)),
+        }
+    }
+}
+
+fn new_contact(name: &str, address: &str, notes: &str, tags: Vec<String>) -> Result<Contact> {
+    let address = Address::from_str(address.trim())
+        .map_err(|_| anyhow!("invalid address '{}'", address.trim()))?;
+    let notes = Some(notes.trim().to_string()).filter(|n| !n.is_empty());
+    let contact = Contact::new(name.trim().to_string(), address, notes, tags);
+    contact.validate()?;
+    Ok(contact)
+}
+
+fn split_tags(tags: &str, separator: char) -> Vec<String> {
+    tags.split(separator)
+        .map(|t| t.trim().to_string())
+        .filter(|t| !t.is_empty())
+        .collect()
+}
+
+pub fn read_contacts(path: &Path, format: ContactFormat) -> Result<ParsedContacts> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| anyhow!("Cannot read {}: {}", path.display(), e))?;
+    match format {
+        ContactFormat::Json => parse_json(&content),
+        ContactFormat::Csv => parse_csv(&content),
+        ContactFormat::Vcard => Ok(parse_vcard(&content)),
+    }
+}
+
+pub fn write_contacts(path: &Path, format: ContactFormat, contacts: &[Contact]) -> Result<()> {
+    let content = match format {
+        ContactFormat::Json => serde_json::to_string_pretty(contacts)?,
+        ContactFormat::Csv => to_csv(contacts)?,
+        ContactFormat::Vcard => to_vcard(contacts),
+    };
+    std::fs::write(path, content).map_err(|e| anyhow!("Cannot write {}: {}", path.display(), e))
+}
+
+fn parse_json(content: &str) -> Result<ParsedContacts> {
+    let contacts: Vec<Contact> =
+        serde_json::from_str(content).map_err(|e| anyhow!("Failed to parse contacts: {}", e))?;
+    let mut parsed = ParsedContacts::default();
+    for (i, contact) in contacts.into_iter().enumerate() {
+        // Saved contacts keep their history; only the imported fields are checked
+        match new_contact(&contact.name, &contact.address.to_string(), contact.notes.as_deref().unwrap_or(""), contact.tags.clone()) {
+            Ok(_) => parsed.contacts.push(contact),
+            Err(e) => parsed.invalid.push(format!("entry {}: {}", i + 1, e)),
+        }
+    }
+    Ok(parsed)
+}
+
+/// Column of a spreadsheet header, matched loosely since exports from
+/// different tools name them differently
+fn column(headers: &csv::StringRecord, names: &[&str]) -> Option<usize> {
+    headers
+        .iter()
+        .position(|h| names.contains(&h.trim().to_lowercase().as_str()))
+}
+
+fn parse_csv(content: &str) -> Result<ParsedContacts> {
+    let mut reader = csv::ReaderBuilder::new()
+        .flexible(true)
+        .trim(csv::Trim::All)
+        .from_reader(content.as_bytes());
+    let headers = reader.headers()?.clone();
+    let name = column(&headers, &["name", "label", "contact"])
+        .ok_or_else(|| anyhow!("CSV has no 'name' column"))?;
+    let address = column(&headers, &["address", "wallet", "account"])
+        .ok_or_else(|| anyhow!("CSV has no 'address' column"))?;
+    let notes = column(&headers, &["notes", "note", "memo", "description"]);
+    let tags = column(&headers, &["tags", "tag", "groups"]);
+
+    let mut parsed = ParsedContacts::default();
+    for (i, record) in reader.records().enumerate() {
+        // Line 1 is the header
+        let location = format!("line {}", i + 2);
+        let record = match record {
+            Ok(record) => record,
+            Err(e) => {
+                parsed.invalid.push(format!("{}: {}", location, e));
+                continue;
+            }
+        };
+        let field = |index: Option<usize>| index.and_then(|i| record.get(i)).unwrap_or("");
+        parsed.push(
+            location,
+            field(Some(name)),
+            field(Some(address)),
+            field(notes),
+            split_tags(field(tags), ';'),
+        );
+    }
+    Ok(parsed)
+}
+
+fn to_csv(contacts: &[Contact]) -> Result<String> {
+    let mut wtr = csv::Writer::from_writer(Vec::new());
+    wtr.write_record(["name", "address", "notes", "tags"])?;
+    for contact in contacts {
+        wtr.write_record([
+            contact.name.as_str(),
+            &contact.address.to_checksum(None),
+            contact.notes.as_deref().unwrap_or(""),
+            &contact.tags.join(";"),
+        ])?;
+    }
+    Ok(String::from_utf8(wtr.into_inner()?)?)
+}
+
+fn vcard_escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('\n', "\\n")
+        .replace(',', "\\,")
+        .replace(';', "\\;")
+}
+
+fn vcard_unescape(value: &str) -> String {
+    let mut out = String::new();
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        match (c, c == '\\') {
+            (_, true) => match chars.next() {
+                Some('n') | Some('N') => out.push('\n'),
+                Some(other) => out.push(other),
+                None => {}
+            },
+            (c, false) => out.push(c),
+        }
+    }
+    out
+}
+
+/// Splits on separators that are not escaped
+fn vcard_split(value: &str, separator: char) -> Vec<String> {
+    let mut parts = vec![String::new()];
+    let mut escaped = false;
+    for c in value.chars() {
+        match c {
+            _ if escaped => {
+                parts.last_mut().unwrap().extend(['\\', c]);
+                escaped = false;
+            }
+            '\\' => escaped = true,
+            c if c == separator => parts.push(String::new()),
+            c => parts.last_mut().unwrap().push(c),
+        }
+    }
+    parts.iter().map(|p| vcard_unescape(p)).collect()
+}
+
+fn parse_vcard(content: &str) -> ParsedContacts {
+    // Long lines are folded onto continuation lines starting with whitespace
+    let mut lines: Vec<String> = Vec::new();
+    for line in content.lines() {
+        match (line.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(rest), Some(previous)) => previous.push_str(rest),
+            _ => lines.push(line.trim_end().to_string()),
+        }
+    }
+
+    let mut parsed = ParsedContacts::default();
+    let mut card: Option<Vec<(String, String)>> = None;
+    let mut count = 0;
+    for line in lines {
+        let Some((key, value)) = line.split_once(':') else {
+            continue;
+        };
+        // Parameters such as `FN;CHARSET=UTF-8` do not change the meaning
+        let property = key.split(';').next().unwrap_or_default().to_uppercase();
+        match (property.as_str(), value.trim().to_uppercase().as_str()) {
+            ("BEGIN", "VCARD") => card = Some(Vec::new()),
+            ("END", "VCARD") => {
+                count += 1;
+                if let Some(fields) = card.take() {
+                    let get = |name: &str| fields.iter().find(|(k, _)| k == name).map(|(_, v)| v.as_str());
+                    let name = get("FN")
+                        .map(vcard_unescape)
+                        .or_else(|| {
+                            // `N` is family;given;additional;prefix;suffix
+                            get("N").map(|n| {
+                                let parts = vcard_split(n, ';');
+                                let given = parts.get(1).map(String::as_str).unwrap_or("");
+                                format!("{} {}", given, parts[0]).trim().to_string()
+                            })
+                        })
+                        .unwrap_or_default();
+                    let address = VCARD_ADDRESS_FIELDS.iter().find_map(|f| get(f)).unwrap_or("");
+                    let notes = get("NOTE").map(vcard_unescape).unwrap_or_default();
+                    let tags = get("CATEGORIES")
+                        .map(|c| vcard_split(c, ',').into_iter().filter(|t| !t.trim().is_empty()).collect())
+                        .unwrap_or_default();
+                    parsed.push(format!("card {}", count), &name, address, &notes, tags);
+                }
+            }
+            _ => {
+                if let Some(fields) = card.as_mut() {
+                    fields.push((property, value.to_string()));
+                }
+            }
+        }
+    }
+    parsed
+}
+
+fn to_vcard(contacts: &[Contact]) -> String {
+    let mut out = String::new();
+    for contact in contacts {
+        out.push_str("BEGIN:VCARD\r\nVERSION:3.0\r\n");
+        out.push_str(&format!("FN:{}\r\n", vcard_escape(&contact.name)));
+        out.push_str(&format!("N:{};;;;\r\n", vcard_escape(&contact.name)));
+        out.push_str(&format!("X-RSK-ADDRESS:{}\r\n", contact.address.to_checksum(None)));
+        if let Some(notes) = &contact.notes {
+            out.push_str(&format!("NOTE:{}\r\n", vcard_escape(notes)));
+        }
+        if !contact.tags.is_empty() {
+            let tags: Vec<String> = contact.tags.iter().map(|t| vcard_escape(t)).collect();
+            out.push_str(&format!("CATEGORIES:{}\r\n", tags.join(",")));
+        }
+        out.push_str("END:VCARD\r\n");
+    }
+    out
+}
+
+/// Outcome of merging imported contacts into the saved ones
+#[derive(Debug, Default)]
+pub struct MergeSummary {
+    pub added: Vec<String>,
+    /// Saved contact names that were replaced
+    pub overwritten: Vec<String>,
+    /// Imported name and the name it was saved under
+    pub renamed: Vec<(String, String)>,
+    /// Imported names left out, and why
+    pub kept: Vec<(String, String)>,
+    /// Imported contacts identical to a saved one
+    pub unchanged: usize,
+}
+
+impl MergeSummary {
+    pub fn print(&self, invalid: &[String]) {
+        for name in &self.added {
+            println!("  {} {}", style("+").green(), name);
+        }
+        for name in &self.overwritten {
+            println!("  {} {} {}", style("~").yellow(), name, style("(overwritten)").dim());
+        }
+        for (from, to) in &self.renamed {
+            println!("  {} {} → {}", style("+").green(), from, to);
+        }
+        for (name, reason) in &self.kept {
+            println!("  {} {} {}", style("=").dim(), name, style(format!("({})", reason)).dim());
+        }
+        for entry in invalid {
+            println!("  {} {}", style("✗").red(), style(entry).red());
+        }
+        println!(
+            "\n{} added, {} overwritten, {} renamed, {} kept, {} unchanged, {} invalid",
+            self.added.len(),
+            self.overwritten.len(),
+            self.renamed.len(),
+            self.kept.len(),
+            self.unchanged,
+            invalid.len()
+        );
+    }
+
+    pub fn changes(&self) -> usize {
+        self.added.len() + self.overwritten.len() + self.renamed.len()
+    }
+}
+
+fn same_contact(a: &Contact, b: &Contact) -> bool {
+    a.name == b.name && a.address == b.address && a.notes == b.notes && a.tags == b.tags
+}
+
+/// Merges `incoming` into `existing`. A contact conflicts when its name
+/// (ignoring case) or address is already saved, including by an earlier
+/// entry of the same import.
+pub fn merge(existing: &mut Vec<Contact>, incoming: Vec<Contact>, policy: ConflictPolicy) -> MergeSummary {
+    let mut summary = MergeSummary::default();
+    for contact in incoming {
+        let name_clash = existing.iter().position(|c| c.name.eq_ignore_ascii_case(&contact.name));
+        let address_clash = existing.iter().position(|c| c.address == contact.address);
+        if name_clash.is_none() && address_clash.is_none() {
+            summary.added.push(contact.name.clone());
+            existing.push(contact);
+            continue;
+        }
+        if let Some(i) = name_clash.or(address_clash)
+            && same_contact(&existing[i], &contact)
+        {
+            summary.unchanged += 1;
+            continue;
+        }
+
+        match policy {
+            ConflictPolicy::Keep => {
+                let reason = match address_clash {
+                    Some(i) => format!("address saved as {}", existing[i].name),
+                    None => "name taken".to_string(),
+                };
+                summary.kept.push((contact.name, reason));
+            }
+            ConflictPolicy::Overwrite => {
+                // The name belongs to a different saved contact than the
+                // address; overwriting either would lose the other one
+                if let (Some(n), Some(a)) = (name_clash, address_clash)
+                    && n != a
+                {
+                    let reason = format!(
+                        "address saved as {}, name used by {:#x}",
+                        existing[a].name, existing[n].address
+                    );
+                    summary.kept.push((contact.name, reason));
+                    continue;
+                }
+                // The contact with the same address keeps its history
+                let target = address_clash.or(name_clash).unwrap();
+                let replaced = existing[target].name.clone();
+                let saved = &mut existing[target];
+                saved.name = contact.name;
+                saved.address = contact.address;
+                saved.notes = contact.notes;
+                saved.tags = contact.tags;
+                summary.overwritten.push(replaced);
+            }
+            ConflictPolicy::Rename => {
+                if let Some(i) = address_clash {
+                    summary.kept.push((contact.name, format!("address saved as {}", existing[i].name)));
+                    continue;
+                }
+                let name = (2..)
+                    .map(|n| format!("{} ({})", contact.name, n))
+                    .find(|candidate| !existing.iter().any(|c| c.name.eq_ignore_ascii_case(candidate)))
+                    .unwrap();
+                summary.renamed.push((contact.name.clone(), name.clone()));
+                existing.push(Contact { name, ..contact });
+            }
+        }
+    }
+    summary
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_csv_and_vcard_round_trip() {
+        let contacts = vec![
+            new_contact("Acme, Inc.", "0x00000000000000000000000000000000000000a1", "Pays; monthly\nnet 30", vec!["client".into(), "usd".into()]).unwrap(),
+            new_contact("Bob", "0x00000000000000000000000000000000000000b2", "", vec![]).unwrap(),
+        ];
+        for (format, content) in [
+            (ContactFormat::Csv, to_csv(&contacts).unwrap()),
+            (ContactFormat::Vcard, to_vcard(&contacts)),
+        ] {
+            let parsed = match format {
+                ContactFormat::Csv => parse_csv(&content).unwrap(),
+                _ => parse_vcard(&content),
+            };
+            assert!(parsed.invalid.is_empty(), "{:?}", parsed.invalid);
+            assert_eq!(parsed.contacts.len(), 2);
+            assert!(same_contact(&parsed.contacts[0], &contacts[0]), "{:?}", format);
+            assert!(same_contact(&parsed.contacts[1], &contacts[1]), "{:?}", format);
+        }
+
+        let spreadsheet = "Label,Wallet,Memo\nCarol,0x00000000000000000000000000000000000000c3,\nDave,0x123,bad\n";
+        let parsed = parse_csv(spreadsheet).unwrap();
+        assert_eq!(parsed.contacts[0].name, "Carol");
+        assert_eq!(parsed.invalid, vec!["line 3: invalid address '0x123'".to_string()]);
+    }
+
+    #[test]
+    fn test_merge_policies() {
+        let saved = || {
+            vec![
+                new_contact("Alice", "0x00000000000000000000000000000000000000a1", "", vec![]).unwrap(),
+                new_contact("Bob", "0x00000000000000000000000000000000000000b2", "", vec![]).unwrap(),
+            ]
+        };
+        let incoming = || {
+            vec![
+                new_contact("alice", "0x00000000000000000000000000000000000000c3", "", vec![]).unwrap(),
+                new_contact("Robert", "0x00000000000000000000000000000000000000b2", "", vec![]).unwrap(),
+                new_contact("Bob", "0x00000000000000000000000000000000000000b2", "", vec![]).unwrap(),
+                new_contact("Erin", "0x00000000000000000000000000000000000000e5", "", vec![]).unwrap(),
+            ]
+        };
+
+        let mut contacts = saved();
+        let summary = merge(&mut contacts, incoming(), ConflictPolicy::Keep);
+        assert_eq!((summary.added.len(), summary.kept.len(), summary.unchanged), (1, 2, 1));
+        assert_eq!(contacts.len(), 3);
+
+        let mut contacts = saved();
+        let summary = merge(&mut contacts, incoming(), ConflictPolicy::Rename);
+        assert_eq!(summary.renamed, vec![("alice".to_string(), "alice (2)".to_string())]);
+        assert_eq!(summary.kept.len(), 1);
+        assert_eq!(contacts.len(), 4);
+
+        let mut contacts = saved();
+        let summary = merge(&mut contacts, incoming(), ConflictPolicy::Overwrite);
+        assert_eq!(summary.overwritten, vec!["Alice".to_string(), "Bob".to_string(), "Robert".to_string()]);
+        let names: Vec<&str> = contacts.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, vec!["alice", "Bob", "Erin"]);
+    }
+
+    #[test]
+    fn test_overwrite_keeps_contact_whose_name_is_reused() {
+        let mut contacts = vec![
+            new_contact("Alice", "0x00000000000000000000000000000000000000a1", "", vec![]).unwrap(),
+            new_contact("Bob", "0x00000000000000000000000000000000000000b2", "", vec![]).unwrap(),
+        ];
+        // Bob's address under Alice's name
+        let incoming = vec![new_contact("Alice", "0x00000000000000000000000000000000000000b2", "", vec![]).unwrap()];
+        let summary = merge(&mut contacts, incoming, ConflictPolicy::Overwrite);
+        assert!(summary.overwritten.is_empty());
+        assert_eq!(summary.kept.len(), 1);
+        assert!(summary.kept[0].1.contains("address saved as Bob"));
+        let names: Vec<&str> = contacts.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, vec!["Alice", "Bob"]);
+        assert_eq!(contacts[0].address, Address::with_last_byte(0xa1));
+    }
+}
@@ -1,15 +1,24 @@
 use crate::types::wallet::WalletData;
 use crate::utils::constants;
 use crate::utils::helper::Config;
+use crate::utils::nonce_manager::{ConfirmationTracker, NonceManager};
+use crate::utils::rpc::ChainRpc;
+use crate::utils::rpc_pool::{PoolProvider, RpcPool};
 use anyhow::anyhow;
-use alloy::primitives::{Address, B256, U256};
-use alloy::providers::{Provider, ProviderBuilder, RootProvider};
+use alloy::consensus::TxEnvelope;
+use alloy::eips::BlockId;
+use alloy::eips::eip2718::Encodable2718;
+use alloy::primitives::{Address, B256, U256, address};
+use alloy::providers::Provider;
+use alloy::rpc::types::TransactionRequest;
+use alloy::sol_types::SolCall;
 use alloy::signers::local::PrivateKeySigner;
-use alloy::transports::http::{Client, Http};
 use alloy::network::{EthereumWallet, TransactionBuilder};
 use alloy::sol;
 use std::fs;
 use std::sync::Arc;
+use tokio::sync::OnceCell;
+use tokio::task::JoinSet;
 
 // Define ERC20 interface using alloy's sol! macro
 sol! {
@@ -20,12 +29,150 @@ sol! {
         function transfer(address recipient, uint256 amount) external returns (bool);
         function decimals() external view returns (uint8);
         function symbol() external view returns (string);
+        function allowance(address owner, address spender) external view returns (uint256);
+
+        event Transfer(address indexed from, address indexed to, uint256 value);
+    }
+}
+
+// Multicall3, deployed at the same address on most EVM chains including
+// Rootstock mainnet and testnet
+sol! {
+    #[allow(missing_docs)]
+    contract IMulticall3 {
+        struct Call3 {
+            address target;
+            bool allowFailure;
+            bytes callData;
+        }
+
+        struct Call3Result {
+            bool success;
+            bytes returnData;
+        }
+
+        function aggregate3(Call3[] calldata calls) external payable returns (Call3Result[] memory returnData);
+        function getEthBalance(address addr) external view returns (uint256 balance);
+    }
+}
+
+/// Address of the Multicall3 contract
+pub const MULTICALL3_ADDRESS: Address = address!("cA11bde05977b3631167028862bE2a173976CA11");
+
+/// Reads aggregated into a single `eth_call`; keeps each call well within
+/// the node's gas limit for calls
+const MULTICALL_BATCH_SIZE: usize = 200;
+
+/// Calls in flight at once when Multicall3 is not available
+const PARALLEL_READS: usize = 8;
+
+/// A read-only call that can be batched through Multicall3
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReadCall {
+    /// RBTC balance of an address
+    Balance { holder: Address },
+    TokenBalance { token: Address, holder: Address },
+    Decimals { token: Address },
+    Symbol { token: Address },
+    Allowance { token: Address, owner: Address, spender: Address },
+}
+
+/// Decoded result of a [`ReadCall`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReadValue {
+    Uint(U256),
+    Decimals(u8),
+    Text(String),
+}
+
+impl ReadValue {
+    pub fn as_u256(&self) -> Option<U256> {
+        match self {
+            Self::Uint(value) => Some(*value),
+            _ => None,
+        }
+    }
+}
+
+impl ReadCall {
+    /// Contract and calldata of the call when made through Multicall3
+    fn encode(&self) -> (Address, Vec<u8>) {
+        match *self {
+            Self::Balance { holder } => (
+                MULTICALL3_ADDRESS,
+                IMulticall3::getEthBalanceCall { addr: holder }.abi_encode(),
+            ),
+            Self::TokenBalance { token, holder } => {
+                (token, IERC20::balanceOfCall { account: holder }.abi_encode())
+            }
+            Self::Decimals { token } => (token, IERC20::decimalsCall {}.abi_encode()),
+            Self::Symbol { token } => (token, IERC20::symbolCall {}.abi_encode()),
+            Self::Allowance { token, owner, spender } => {
+                (token, IERC20::allowanceCall { owner, spender }.abi_encode())
+            }
+        }
+    }
+
+    fn decode(&self, data: &[u8]) -> Option<ReadValue> {
+        match self {
+            Self::Balance { .. } => IMulticall3::getEthBalanceCall::abi_decode_returns(data, true)
+                .ok()
+                .map(|r| ReadValue::Uint(r.balance)),
+            Self::TokenBalance { .. } => IERC20::balanceOfCall::abi_decode_returns(data, true)
+                .ok()
+                .map(|r| ReadValue::Uint(r._0)),
+            Self::Decimals { .. } => IERC20::decimalsCall::abi_decode_returns(data, true)
+                .ok()
+                .map(|r| ReadValue::Decimals(r._0)),
+            Self::Symbol { .. } => IERC20::symbolCall::abi_decode_returns(data, true)
+                .ok()
+                .map(|r| ReadValue::Text(r._0)),
+            Self::Allowance { .. } => IERC20::allowanceCall::abi_decode_returns(data, true)
+                .ok()
+                .map(|r| ReadValue::Uint(r._0)),
+        }
     }
+
+    /// Makes the call on its own, without Multicall3
+    async fn call_direct(self, provider: &PoolProvider) -> Option<ReadValue> {
+        match self {
+            Self::Balance { holder } => provider.balance(holder).await.ok().map(ReadValue::Uint),
+            _ => {
+                let (target, data) = self.encode();
+                let output = provider.eth_call(target, data.into()).await.ok()?;
+                self.decode(&output)
+            }
+        }
+    }
+}
+
+/// Expected change of one balance if a simulated transfer is mined
+#[derive(Debug, Clone)]
+pub struct BalanceChange {
+    pub holder: Address,
+    pub asset: String,
+    pub decimals: u8,
+    pub before: U256,
+    pub after: U256,
+}
+
+/// Outcome of running a transfer through `eth_call` against the pending block
+#[derive(Debug, Clone)]
+pub struct TransferSimulation {
+    pub success: bool,
+    pub revert_reason: Option<String>,
+    pub recipient_is_contract: bool,
+    pub balance_changes: Vec<BalanceChange>,
 }
 
 pub struct EthClient {
-    provider: Arc<RootProvider<Http<Client>>>,
+    provider: Arc<PoolProvider>,
+    pool: Arc<RpcPool>,
     wallet: Option<PrivateKeySigner>,
+    /// Whether Multicall3 is deployed on the connected chain, checked once
+    multicall: OnceCell<bool>,
+    /// Local nonces of the signing key for pipelined sends
+    nonces: NonceManager,
 }
 
 impl EthClient {
@@ -47,9 +194,9 @@ impl EthClient {
             wallet_data.api_key.as_ref().map(|k| k.expose().clone())
         };
 
-        // Use the RPC URL from config (which defaults to public nodes)
-        let provider = ProviderBuilder::new()
-            .on_http(config.network.rpc_url.parse()?);
+        // The configured RPC URL first, then its fallbacks
+        let pool = RpcPool::shared(&config.network.rpc_urls());
+        let provider = pool.provider();
         let wallet = config
             .wallet
             .private_key
@@ -61,7 +208,10 @@ impl EthClient {
             .transpose()?;
         Ok(Self {
             provider: Arc::new(provider),
+            pool,
             wallet,
+            multicall: OnceCell::new(),
+            nonces: NonceManager::default(),
         })
     }
 
@@ -71,18 +221,12 @@ impl EthClient {
         token_address: &Option<Address>,
     ) -> Result<U256, anyhow::Error> {
         match token_address {
-            Some(token_addr) => {
-                let contract = IERC20::new(*token_addr, &self.provider);
-                let balance = contract
-                    .balanceOf(*address)
-                    .call()
-                    .await
-                    .map_err(|e| anyhow!("Failed to get token balance: {}", e))?;
-                Ok(balance._0)
-            }
+            Some(token_addr) => self
+                .token_balance(*token_addr, *address)
+                .await
+                .map_err(|e| anyhow!("Failed to get token balance: {}", e)),
             None => self
-                .provider
-                .get_balance(*address)
+                .balance(*address)
                 .await
                 .map_err(|e| anyhow!("Failed to get RBTC balance: {}", e)),
         }
@@ -104,13 +248,11 @@ impl EthClient {
             .await
             .map_err(|e| anyhow!("Failed to get nonce: {}", e))?;
         let gas_price = self
-            .provider
-            .get_gas_price()
+            .gas_price()
             .await
             .map_err(|e| anyhow!("Failed to get gas price: {}", e))?;
         let rbtc_balance = self
-            .provider
-            .get_balance(wallet.address())
+            .balance(wallet.address())
             .await
             .map_err(|e| anyhow!("Failed to get RBTC balance: {}", e))?;
         let estimated_gas_cost = U256::from(gas_price) * U256::from(100_000);
@@ -122,18 +264,16 @@ impl EthClient {
                 balance_rbtc, gas_cost_rbtc
             ));
         }
-        let chain_id = self.provider.get_chain_id().await?;
+        let chain_id = self.chain_id().await?;
 
         match token_address {
             Some(token_addr) => {
-                let contract = IERC20::new(token_addr, &self.provider);
-                let token_balance = contract
-                    .balanceOf(wallet.address())
-                    .call()
+                let token_balance = self
+                    .token_balance(token_addr, wallet.address())
                     .await
                     .map_err(|e| anyhow!("Failed to get token balance: {}", e))?;
-                if token_balance._0 < amount {
-                    let balance_f64 = token_balance._0.to::<u128>() as f64 / 1e18;
+                if token_balance < amount {
+                    let balance_f64 = token_balance.to::<u128>() as f64 / 1e18;
                     let amount_f64 = amount.to::<u128>() as f64 / 1e18;
                     return Err(anyhow!(
                         "Insufficient token balance. Balance: {:.6}, Required: {:.6}", 
@@ -141,8 +281,7 @@ impl EthClient {
                     ));
                 }
                 
-                use alloy::rpc::types::TransactionRequest;
-                let call_data = contract.transfer(to, amount).calldata().clone();
+                let call_data = IERC20::transferCall { recipient: to, amount }.abi_encode();
                 let tx = TransactionRequest::default()
                     .with_to(token_addr)
                     .with_from(wallet.address())
@@ -153,8 +292,7 @@ impl EthClient {
                     .with_chain_id(chain_id);
                 
                 let gas_estimate = self
-                    .provider
-                    .estimate_gas(&tx)
+                    .estimate(&tx)
                     .await
                     .map_err(|e| anyhow!("Failed to estimate gas for token transfer: {}", e))?;
                 
@@ -175,7 +313,6 @@ impl EthClient {
                     return Err(anyhow!("Insufficient RBTC for transfer and gas"));
                 }
                 
-                use alloy::rpc::types::TransactionRequest;
                 let tx = TransactionRequest::default()
                     .with_to(to)
                     .with_value(amount)
@@ -185,8 +322,7 @@ impl EthClient {
                     .with_chain_id(chain_id);
                 
                 let gas_estimate = self
-                    .provider
-                    .estimate_gas(&tx)
+                    .estimate(&tx)
                     .await
                     .map_err(|e| anyhow!("Failed to estimate gas for RBTC transfer: {}", e))?;
                 
@@ -205,58 +341,366 @@ impl EthClient {
         }
     }
 
+    /// Address of the configured signing key
+    pub fn signer_address(&self) -> Option<Address> {
+        self.wallet.as_ref().map(|wallet| wallet.address())
+    }
+
+    /// Signs a fully built transaction without sending it
+    pub async fn sign_transaction(&self, tx: TransactionRequest) -> Result<TxEnvelope, anyhow::Error> {
+        let wallet = self
+            .wallet
+            .as_ref()
+            .ok_or_else(|| anyhow!("No wallet configured"))?;
+        tx.build(&EthereumWallet::from(wallet.clone()))
+            .await
+            .map_err(|e| anyhow!("Failed to sign transaction: {}", e))
+    }
+
+    /// Broadcasts a signed transaction. Sending one the node already holds
+    /// is not an error, so the same transaction can safely be sent again.
+    pub async fn broadcast(&self, tx: &TxEnvelope) -> Result<B256, anyhow::Error> {
+        broadcast_with(&self.provider, tx).await
+    }
+
+    /// Broadcasts transactions concurrently; results are in input order
+    pub async fn broadcast_all(&self, txs: &[TxEnvelope]) -> Vec<Result<B256, anyhow::Error>> {
+        let mut tasks = JoinSet::new();
+        for (index, tx) in txs.iter().cloned().enumerate() {
+            let provider = self.provider.clone();
+            tasks.spawn(async move { (index, broadcast_with(&provider, &tx).await) });
+        }
+        let mut results: Vec<Result<B256, anyhow::Error>> =
+            txs.iter().map(|_| Err(anyhow!("Broadcast task failed"))).collect();
+        while let Some(joined) = tasks.join_next().await {
+            if let Ok((index, result)) = joined {
+                results[index] = result;
+            }
+        }
+        results
+    }
+
+    /// Next nonce of the signing key, handed out locally after the first
+    pub async fn next_nonce(&self) -> Result<u64, anyhow::Error> {
+        let address = self.signer_address().ok_or_else(|| anyhow!("No wallet configured"))?;
+        self.nonces.next(&self.provider, address).await
+    }
+
+    /// Keeps the signing key's local nonces at or above `nonce`
+    pub async fn advance_nonce_to(&self, nonce: u64) -> Result<(), anyhow::Error> {
+        let address = self.signer_address().ok_or_else(|| anyhow!("No wallet configured"))?;
+        self.nonces.advance_to(&self.provider, address, nonce).await
+    }
+
+    /// Restarts the signing key's local nonces from the node's pending count
+    pub async fn resync_nonce(&self) -> Result<u64, anyhow::Error> {
+        let address = self.signer_address().ok_or_else(|| anyhow!("No wallet configured"))?;
+        self.nonces.resync(&self.provider, address).await
+    }
+
+    /// Background receipt polling for sent transactions
+    pub fn track_confirmations(&self) -> ConfirmationTracker {
+        ConfirmationTracker::spawn(self.provider.clone())
+    }
+
     /// Get transaction receipt by hash
     pub async fn get_transaction_receipt(
         &self,
         tx_hash: B256,
     ) -> Result<alloy::rpc::types::TransactionReceipt, anyhow::Error> {
-        self.provider
-            .get_transaction_receipt(tx_hash)
+        self.receipt(tx_hash)
             .await
             .map_err(|e| anyhow!("Failed to get transaction receipt: {}", e))
             .and_then(|receipt| receipt.ok_or_else(|| anyhow!("Transaction receipt not found")))
     }
 
+    /// Simulate a transfer with `eth_call` against the pending block and work
+    /// out the balance changes it would cause. `fee` is the expected gas cost
+    /// in wei and is charged to the sender's RBTC balance.
+    pub async fn simulate_transfer(
+        &self,
+        from: Address,
+        to: Address,
+        amount: U256,
+        token_address: Option<Address>,
+        fee: U256,
+    ) -> Result<TransferSimulation, anyhow::Error> {
+        let pending = BlockId::pending();
+        let tx = match token_address {
+            Some(token_addr) => TransactionRequest::default()
+                .with_from(from)
+                .with_to(token_addr)
+                .with_input(IERC20::transferCall { recipient: to, amount }.abi_encode()),
+            None => TransactionRequest::default()
+                .with_from(from)
+                .with_to(to)
+                .with_value(amount),
+        };
+
+        let code = self
+            .provider
+            .get_code_at(to)
+            .block_id(pending)
+            .await
+            .map_err(|e| anyhow!("Failed to get recipient code: {}", e))?;
+        let recipient_is_contract = !code.is_empty();
+
+        let (success, revert_reason) = match self.provider.call(&tx).block(pending).await {
+            Ok(output) => match token_address {
+                // Tokens that return `false` instead of reverting still fail the transfer
+                Some(_) => match IERC20::transferCall::abi_decode_returns(&output, false) {
+                    Ok(ret) if !ret._0 => (false, Some("token transfer returned false".to_string())),
+                    _ => (true, None),
+                },
+                None => (true, None),
+            },
+            Err(e) => match e.as_error_resp() {
+                Some(payload) => {
+                    let reason = payload
+                        .as_revert_data()
+                        .and_then(|data| alloy::sol_types::decode_revert_reason(&data))
+                        .unwrap_or_else(|| payload.message.to_string());
+                    (false, Some(reason))
+                }
+                None => return Err(anyhow!("Failed to simulate transaction: {}", e)),
+            },
+        };
+
+        let rbtc_balance = |address: Address| async move {
+            self.provider
+                .get_balance(address)
+                .block_id(pending)
+                .await
+                .map_err(|e| anyhow!("Failed to get RBTC balance: {}", e))
+        };
+        let sender_rbtc = rbtc_balance(from).await?;
+        let recipient_rbtc = rbtc_balance(to).await?;
+
+        let mut balance_changes = Vec::new();
+        match token_address {
+            Some(token_addr) => {
+                let (decimals, symbol) = self.get_token_info(token_addr).await?;
+                let contract = IERC20::new(token_addr, &self.provider);
+                let sender_tokens = contract.balanceOf(from).block(pending).call().await?._0;
+                let recipient_tokens = contract.balanceOf(to).block(pending).call().await?._0;
+                balance_changes.push(BalanceChange {
+                    holder: from,
+                    asset: symbol.clone(),
+                    decimals,
+                    before: sender_tokens,
+                    after: sender_tokens.saturating_sub(amount),
+                });
+                balance_changes.push(BalanceChange {
+                    holder: to,
+                    asset: symbol,
+                    decimals,
+                    before: recipient_tokens,
+                    after: recipient_tokens.saturating_add(amount),
+                });
+                balance_changes.push(BalanceChange {
+                    holder: from,
+                    asset: "RBTC".to_string(),
+                    decimals: 18,
+                    before: sender_rbtc,
+                    after: sender_rbtc.saturating_sub(fee),
+                });
+            }
+            None => {
+                balance_changes.push(BalanceChange {
+                    holder: from,
+                    asset: "RBTC".to_string(),
+                    decimals: 18,
+                    before: sender_rbtc,
+                    after: sender_rbtc.saturating_sub(amount).saturating_sub(fee),
+                });
+                balance_changes.push(BalanceChange {
+                    holder: to,
+                    asset: "RBTC".to_string(),
+                    decimals: 18,
+                    before: recipient_rbtc,
+                    after: recipient_rbtc.saturating_add(amount),
+                });
+            }
+        }
+
+        Ok(TransferSimulation {
+            success,
+            revert_reason,
+            recipient_is_contract,
+            balance_changes,
+        })
+    }
+
     pub async fn get_token_info(
         &self,
         token_address: Address,
     ) -> Result<(u8, String), anyhow::Error> {
-        let contract = IERC20::new(token_address, &self.provider);
-        let decimals = contract.decimals().call().await?._0;
-        let symbol = contract.symbol().call().await?._0;
-        Ok((decimals, symbol))
+        let results = self
+            .batch_read(&[
+                ReadCall::Decimals { token: token_address },
+                ReadCall::Symbol { token: token_address },
+            ])
+            .await?;
+        match (&results[0], &results[1]) {
+            (Some(ReadValue::Decimals(decimals)), Some(ReadValue::Text(symbol))) => {
+                Ok((*decimals, symbol.clone()))
+            }
+            _ => Err(anyhow!("Failed to read token info for {:#x}", token_address)),
+        }
+    }
+
+    /// Balances of every holder in every asset (`None` being RBTC), indexed
+    /// as `[holder][asset]`. A balance that could not be read is `None`.
+    pub async fn get_balances(
+        &self,
+        holders: &[Address],
+        tokens: &[Option<Address>],
+    ) -> Result<Vec<Vec<Option<U256>>>, anyhow::Error> {
+        let calls: Vec<ReadCall> = holders
+            .iter()
+            .flat_map(|&holder| {
+                tokens.iter().map(move |token| match *token {
+                    Some(token) => ReadCall::TokenBalance { token, holder },
+                    None => ReadCall::Balance { holder },
+                })
+            })
+            .collect();
+        let results = self.batch_read(&calls).await?;
+        Ok(results
+            .chunks(tokens.len().max(1))
+            .map(|row| row.iter().map(|r| r.as_ref().and_then(ReadValue::as_u256)).collect())
+            .collect())
+    }
+
+    /// Runs read-only calls, aggregated through Multicall3 when it is
+    /// deployed and as parallel calls otherwise. Results keep the order of
+    /// `calls`; a call that fails or returns garbage yields `None`.
+    pub async fn batch_read(&self, calls: &[ReadCall]) -> Result<Vec<Option<ReadValue>>, anyhow::Error> {
+        if calls.is_empty() {
+            return Ok(Vec::new());
+        }
+        if self.multicall_available().await {
+            let mut results = Vec::with_capacity(calls.len());
+            for chunk in calls.chunks(MULTICALL_BATCH_SIZE) {
+                match self.aggregate(chunk).await {
+                    Ok(chunk_results) => results.extend(chunk_results),
+                    Err(e) => {
+                        log::warn!("Multicall failed, falling back to single calls: {}", e);
+                        results.extend(self.read_parallel(chunk).await);
+                    }
+                }
+            }
+            return Ok(results);
+        }
+        Ok(self.read_parallel(calls).await)
+    }
+
+    async fn multicall_available(&self) -> bool {
+        *self
+            .multicall
+            .get_or_init(|| async {
+                self.code(MULTICALL3_ADDRESS)
+                    .await
+                    .map(|code| !code.is_empty())
+                    .unwrap_or(false)
+            })
+            .await
+    }
+
+    async fn aggregate(&self, calls: &[ReadCall]) -> Result<Vec<Option<ReadValue>>, anyhow::Error> {
+        let call3s = calls
+            .iter()
+            .map(|call| {
+                let (target, data) = call.encode();
+                IMulticall3::Call3 {
+                    target,
+                    allowFailure: true,
+                    callData: data.into(),
+                }
+            })
+            .collect();
+        let data = IMulticall3::aggregate3Call { calls: call3s }.abi_encode();
+        let output = self
+            .eth_call(MULTICALL3_ADDRESS, data.into())
+            .await
+            .map_err(|e| anyhow!("Multicall failed: {}", e))?;
+        let returned = IMulticall3::aggregate3Call::abi_decode_returns(&output, true)
+            .map_err(|e| anyhow!("Invalid multicall response: {}", e))?
+            .returnData;
+        if returned.len() != calls.len() {
+            return Err(anyhow!("Multicall returned {} results for {} calls", returned.len(), calls.len()));
+        }
+        Ok(calls
+            .iter()
+            .zip(returned)
+            .map(|(call, result)| result.success.then(|| call.decode(&result.returnData)).flatten())
+            .collect())
+    }
+
+    async fn read_parallel(&self, calls: &[ReadCall]) -> Vec<Option<ReadValue>> {
+        let mut results = vec![None; calls.len()];
+        for (batch, chunk) in calls.chunks(PARALLEL_READS).enumerate() {
+            let mut tasks = JoinSet::new();
+            for (offset, call) in chunk.iter().copied().enumerate() {
+                let provider = self.provider.clone();
+                let index = batch * PARALLEL_READS + offset;
+                tasks.spawn(async move { (index, call.call_direct(&provider).await) });
+            }
+            while let Some(joined) = tasks.join_next().await {
+                if let Ok((index, value)) = joined {
+                    results[index] = value;
+                }
+            }
+        }
+        results
     }
 
     /// Get a reference to the underlying provider
-    pub fn provider(&self) -> &RootProvider<Http<Client>> {
+    pub fn provider(&self) -> &PoolProvider {
         &self.provider
     }
 
+    /// The endpoint pool requests are sent through
+    pub fn rpc_pool(&self) -> &Arc<RpcPool> {
+        &self.pool
+    }
+
+    /// Gas a transfer would use. Token transfers need the sender, since
+    /// they revert when `from` lacks the balance.
     pub async fn estimate_gas(
         &self,
+        from: Option<Address>,
         to: Address,
         amount: U256,
         token_address: Option<Address>,
     ) -> Result<U256, anyhow::Error> {
-        match token_address {
-            Some(token_addr) => {
-                let contract = IERC20::new(token_addr, &self.provider);
-                let call = contract.transfer(to, amount);
-                call.estimate_gas()
-                    .await
-                    .map(|gas| U256::from(gas))
-                    .map_err(|e| anyhow!("Failed to estimate gas for token transfer: {}", e))
-            }
-            None => {
-                use alloy::rpc::types::TransactionRequest;
-                let tx = TransactionRequest::default()
-                    .with_to(to)
-                    .with_value(amount);
-                self.provider
-                    .estimate_gas(&tx)
-                    .await
-                    .map(U256::from)
-                    .map_err(|e| anyhow!("Failed to estimate gas for RBTC transfer: {}", e))
+        let tx = match token_address {
+            Some(token_addr) => TransactionRequest::default()
+                .with_to(token_addr)
+                .with_input(IERC20::transferCall { recipient: to, amount }.abi_encode()),
+            None => TransactionRequest::default().with_to(to).with_value(amount),
+        };
+        let tx = match from {
+            Some(from) => tx.with_from(from),
+            None => tx,
+        };
+        let kind = if token_address.is_some() { "token" } else { "RBTC" };
+        self.estimate(&tx)
+            .await
+            .map(U256::from)
+            .map_err(|e| anyhow!("Failed to estimate gas for {} transfer: {}", kind, e))
+    }
+}
+
+async fn broadcast_with(provider: &PoolProvider, tx: &TxEnvelope) -> Result<B256, anyhow::Error> {
+    match provider.send_raw_transaction(&tx.encoded_2718()).await {
+        Ok(pending) => Ok(*pending.tx_hash()),
+        Err(e) => {
+            let message = e.to_string().to_lowercase();
+            if message.contains("already known") || message.contains("known transaction") {
+                Ok(*tx.tx_hash())
+            } else {
+                Err(anyhow!("Failed to broadcast transaction: {}", e))
             }
         }
     }
@@ -270,3 +714,96 @@ pub fn get_explorer_url(tx_hash: &str, is_testnet: bool) -> String {
         format!("https://explorer.rsk.co/tx/{}", tx_hash)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_call_encoding() {
+        let holder = Address::with_last_byte(1);
+        let (target, data) = ReadCall::Balance { holder }.encode();
+        assert_eq!(target, MULTICALL3_ADDRESS);
+        assert_eq!(data[..4], IMulticall3::getEthBalanceCall::SELECTOR);
+
+        let token = Address::with_last_byte(2);
+        let call = ReadCall::TokenBalance { token, holder };
+        assert_eq!(call.encode().0, token);
+        let returned = U256::from(42).to_be_bytes::<32>();
+        assert_eq!(call.decode(&returned), Some(ReadValue::Uint(U256::from(42))));
+        assert_eq!(call.decode(&[]), None);
+    }
+
+    #[tokio::test]
+    async fn test_balances_without_multicall() {
+        use crate::utils::mock_node::{Fixtures, MockToken, TestEnv};
+        use std::collections::HashMap;
+
+        let (holder, token) = (Address::with_last_byte(1), Address::with_last_byte(2));
+        let mut fixtures = Fixtures::default();
+        fixtures.balances.insert(holder, U256::from(7));
+        fixtures.tokens.insert(
+            token,
+            MockToken {
+                symbol: "RIF".to_string(),
+                decimals: 18,
+                balances: HashMap::from([(holder, U256::from(9))]),
+            },
+        );
+        let env = TestEnv::start(fixtures).await;
+        let mut config = Config::default();
+        config.network.rpc_url = env.node.url().to_string();
+        let client = EthClient::new(&config, None).await.unwrap();
+
+        let balances = client.get_balances(&[holder], &[None, Some(token)]).await.unwrap();
+        assert_eq!(balances, vec![vec![Some(U256::from(7)), Some(U256::from(9))]]);
+        assert_eq!(client.get_token_info(token).await.unwrap(), (18, "RIF".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_simulated_token_transfer_uses_token_decimals() {
+        use crate::utils::mock_node::{Fixtures, MockToken, TestEnv};
+        use alloy::primitives::utils::{format_units, parse_units};
+        use std::collections::HashMap;
+
+        let (sender, recipient, token) =
+            (Address::with_last_byte(1), Address::with_last_byte(3), Address::with_last_byte(2));
+        let mut fixtures = Fixtures::default();
+        fixtures.balances.insert(sender, U256::from(10u64).pow(U256::from(18)));
+        fixtures.tokens.insert(
+            token,
+            MockToken {
+                symbol: "USDT".to_string(),
+                decimals: 6,
+                balances: HashMap::from([(sender, U256::from(5_000_000))]),
+            },
+        );
+        let env = TestEnv::start(fixtures).await;
+        let mut config = Config::default();
+        config.network.rpc_url = env.node.url().to_string();
+        let client = EthClient::new(&config, None).await.unwrap();
+
+        let amount: U256 = parse_units("1.5", 6).unwrap().into();
+        let fee = U256::from(21_000u64 * 60_000_000);
+        let simulation = client.simulate_transfer(sender, recipient, amount, Some(token), fee).await.unwrap();
+        assert!(simulation.success);
+        let changes: Vec<(Address, &str, String)> = simulation
+            .balance_changes
+            .iter()
+            .map(|c| (c.holder, c.asset.as_str(), format_units(c.after, c.decimals).unwrap()))
+            .collect();
+        assert_eq!(
+            changes,
+            vec![
+                (sender, "USDT", "3.500000".to_string()),
+                (recipient, "USDT", "1.500000".to_string()),
+                (sender, "RBTC", "0.999998740000000000".to_string()),
+            ]
+        );
+
+        // More than the sender holds reverts
+        let too_much: U256 = parse_units("5.000001", 6).unwrap().into();
+        let simulation = client.simulate_transfer(sender, recipient, too_much, Some(token), fee).await.unwrap();
+        assert!(!simulation.success);
+    }
+}
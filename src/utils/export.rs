@@ -0,0 +1,657 @@
+use crate::commands::contacts::{ContactsAction, ContactsCommand};
+use crate::commands::tokens::TokenRegistry;
+use crate::types::transaction::{RskTransaction, TransactionStatus};
+use crate::utils::block_resolver::DateZone;
+use alloy::primitives::{Address, B256, I256, U256, utils::format_units};
+use anyhow::{Result, anyhow};
+use chrono::{DateTime, Utc};
+use clap::ValueEnum;
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use std::str::FromStr;
+
+/// File formats the transaction history can be exported to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ExportFormat {
+    /// CSV with decimal amounts, fees, counterparties and running balances
+    Statement,
+    /// One JSON object per line
+    Jsonl,
+    /// Open Financial Exchange, one statement per asset
+    Ofx,
+    /// Quicken Interchange Format, one account per asset
+    Qif,
+    /// The original CSV layout with raw wei values
+    Raw,
+}
+
+impl ExportFormat {
+    /// Picks the format matching a file extension
+    pub fn from_path(path: &str) -> Option<Self> {
+        let extension = Path::new(path).extension()?.to_str()?.to_lowercase();
+        match extension.as_str() {
+            "csv" => Some(Self::Statement),
+            "jsonl" | "ndjson" | "json" => Some(Self::Jsonl),
+            "ofx" => Some(Self::Ofx),
+            "qif" => Some(Self::Qif),
+            _ => None,
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Statement | Self::Raw => "csv",
+            Self::Jsonl => "jsonl",
+            Self::Ofx => "ofx",
+            Self::Qif => "qif",
+        }
+    }
+}
+
+/// Direction of a transfer as seen from the exported address
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Incoming,
+    Outgoing,
+    SelfTransfer,
+}
+
+impl std::fmt::Display for Direction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Incoming => write!(f, "in"),
+            Self::Outgoing => write!(f, "out"),
+            Self::SelfTransfer => write!(f, "self"),
+        }
+    }
+}
+
+/// Symbol and decimals used to format an asset's amounts
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Asset {
+    pub symbol: String,
+    pub decimals: u8,
+}
+
+impl Asset {
+    fn rbtc() -> Self {
+        Self {
+            symbol: "RBTC".to_string(),
+            decimals: 18,
+        }
+    }
+}
+
+/// One transfer as it appears on a statement
+#[derive(Debug, Clone)]
+pub struct StatementLine {
+    pub hash: B256,
+    pub time: DateTime<Utc>,
+    pub block: Option<u64>,
+    pub status: TransactionStatus,
+    pub direction: Direction,
+    pub token: Option<Address>,
+    pub asset: Asset,
+    /// Change to the address's balance of the asset, negative when sent
+    pub amount: I256,
+    /// RBTC network fee in wei, only set on the first line of a transaction
+    /// the address sent
+    pub fee: U256,
+    pub counterparty: Option<Address>,
+    pub counterparty_name: Option<String>,
+    /// Balance of the asset after this line; fees count towards RBTC
+    pub balance: I256,
+}
+
+/// Exported transfers in chronological order with closing balances
+#[derive(Debug, Clone, Default)]
+pub struct Statement {
+    pub lines: Vec<StatementLine>,
+    pub closing: HashMap<Option<Address>, I256>,
+}
+
+/// Token symbols and contact names used to make exports readable
+pub struct ExportContext {
+    pub address: Address,
+    pub zone: DateZone,
+    assets: HashMap<Address, Asset>,
+    contacts: HashMap<Address, String>,
+}
+
+impl ExportContext {
+    /// Loads the token registry for the network and the saved contacts
+    pub fn load(address: Address, network: &str, zone: DateZone) -> Result<Self> {
+        let registry = TokenRegistry::load()
+            .map_err(|e| anyhow!("Failed to load token registry: {}", e))?;
+        let assets = registry
+            .list_tokens(Some(network))
+            .into_iter()
+            .filter_map(|(symbol, info)| {
+                Address::from_str(&info.address).ok().map(|token| {
+                    (
+                        token,
+                        Asset {
+                            symbol,
+                            decimals: info.decimals,
+                        },
+                    )
+                })
+            })
+            .collect();
+        let contacts = ContactsCommand {
+            action: ContactsAction::List,
+        }
+        .load_contacts()?
+        .into_iter()
+        .map(|contact| (contact.address, contact.name))
+        .collect();
+
+        Ok(Self {
+            address,
+            zone,
+            assets,
+            contacts,
+        })
+    }
+
+    /// Asset details for a token, `None` being RBTC. Tokens missing from
+    /// the registry are shown by address with 18 decimals.
+    pub fn asset(&self, token: Option<Address>) -> Asset {
+        match token {
+            None => Asset::rbtc(),
+            Some(token) => self.assets.get(&token).cloned().unwrap_or_else(|| Asset {
+                symbol: format!("{:#x}", token),
+                decimals: 18,
+            }),
+        }
+    }
+
+    /// Tokens in `txs` that are not in the registry, so their decimals are guessed
+    pub fn unknown_tokens(&self, txs: &[RskTransaction]) -> Vec<Address> {
+        let mut unknown: Vec<Address> = txs
+            .iter()
+            .filter_map(|tx| tx.token_address)
+            .filter(|token| !self.assets.contains_key(token))
+            .collect();
+        unknown.sort();
+        unknown.dedup();
+        unknown
+    }
+
+    /// Builds the statement for `txs`, starting from `opening` balances.
+    ///
+    /// Successful transfers move their amount; failed ones only cost the
+    /// fee and pending ones do not change any balance yet.
+    pub fn statement(&self, txs: &[RskTransaction], opening: &HashMap<Option<Address>, I256>) -> Statement {
+        self.statement_where(txs, opening, |_| true)
+    }
+
+    /// Like [`Self::statement`], but only lists the transfers `keep`
+    /// accepts. Balances still count every transfer in `txs`.
+    pub fn statement_where(
+        &self,
+        txs: &[RskTransaction],
+        opening: &HashMap<Option<Address>, I256>,
+        keep: impl Fn(&RskTransaction) -> bool,
+    ) -> Statement {
+        let mut txs: Vec<&RskTransaction> = txs.iter().collect();
+        txs.sort_by_key(|tx| (tx.timestamp, tx.block_number, tx.transaction_index));
+
+        let mut balances = opening.clone();
+        let mut fees_charged = HashSet::new();
+        let mut lines = Vec::with_capacity(txs.len());
+
+        for tx in txs {
+            let sent = tx.from == self.address;
+            let received = tx.to == Some(self.address);
+            let (direction, counterparty) = match (sent, received) {
+                (true, true) => (Direction::SelfTransfer, Some(self.address)),
+                (true, false) => (Direction::Outgoing, tx.to),
+                (false, _) => (Direction::Incoming, Some(tx.from)),
+            };
+            let value = I256::from_raw(tx.value);
+            let amount = match direction {
+                Direction::Incoming => value,
+                Direction::Outgoing => -value,
+                Direction::SelfTransfer => I256::ZERO,
+            };
+
+            // A transaction can list several transfers; its fee is paid once
+            let fee = if sent && tx.status != TransactionStatus::Pending && fees_charged.insert(tx.hash) {
+                tx.gas.saturating_mul(tx.gas_price)
+            } else {
+                U256::ZERO
+            };
+
+            if tx.status == TransactionStatus::Success {
+                *balances.entry(tx.token_address).or_default() += amount;
+            }
+            if !fee.is_zero() {
+                *balances.entry(None).or_default() -= I256::from_raw(fee);
+            }
+            if !keep(tx) {
+                continue;
+            }
+
+            let time = DateTime::<Utc>::from(tx.timestamp);
+            lines.push(StatementLine {
+                hash: tx.hash,
+                time,
+                block: tx.block_number.map(|b| b.to::<u64>()),
+                status: tx.status,
+                direction,
+                token: tx.token_address,
+                asset: self.asset(tx.token_address),
+                amount,
+                fee,
+                counterparty,
+                counterparty_name: counterparty.and_then(|a| self.contacts.get(&a).cloned()),
+                balance: balances.get(&tx.token_address).copied().unwrap_or_default(),
+            });
+        }
+
+        Statement {
+            lines,
+            closing: balances,
+        }
+    }
+}
+
+/// Formats a signed amount in base units as a decimal without trailing zeros
+pub fn format_amount(amount: I256, decimals: u8) -> String {
+    let formatted = format_units(amount.unsigned_abs(), decimals).unwrap_or_else(|_| amount.to_string());
+    let trimmed = if formatted.contains('.') {
+        formatted.trim_end_matches('0').trim_end_matches('.')
+    } else {
+        formatted.as_str()
+    };
+    if amount.is_negative() && trimmed != "0" {
+        format!("-{}", trimmed)
+    } else {
+        trimmed.to_string()
+    }
+}
+
+/// Writes the transfers in `txs` that `keep` accepts to `path` in the
+/// chosen format and returns the number of records written. `txs` is every
+/// transfer in the exported period, so running balances stay correct when
+/// `keep` leaves some of them out.
+pub fn write_export(
+    context: &ExportContext,
+    format: ExportFormat,
+    path: &str,
+    txs: &[RskTransaction],
+    opening: &HashMap<Option<Address>, I256>,
+    keep: impl Fn(&RskTransaction) -> bool,
+) -> Result<usize> {
+    let file = File::create(path).map_err(|e| anyhow!("Failed to create {}: {}", path, e))?;
+    let mut out = BufWriter::new(file);
+
+    if format == ExportFormat::Raw {
+        let kept: Vec<RskTransaction> = txs.iter().filter(|tx| keep(tx)).cloned().collect();
+        write_raw_csv(&mut out, &kept)?;
+        return Ok(kept.len());
+    }
+
+    let statement = context.statement_where(txs, opening, keep);
+    match format {
+        ExportFormat::Statement => write_statement_csv(&mut out, context, &statement)?,
+        ExportFormat::Jsonl => write_jsonl(&mut out, context, &statement)?,
+        ExportFormat::Ofx => write_ofx(&mut out, context, &statement)?,
+        ExportFormat::Qif => write_qif(&mut out, context, &statement)?,
+        ExportFormat::Raw => unreachable!(),
+    }
+    out.flush()?;
+    Ok(statement.lines.len())
+}
+
+fn write_raw_csv(out: &mut impl Write, txs: &[RskTransaction]) -> Result<()> {
+    let mut wtr = csv::Writer::from_writer(out);
+    wtr.write_record([
+        "Transaction Hash",
+        "Timestamp",
+        "From",
+        "To",
+        "Value (wei)",
+        "Token Address",
+        "Gas Price (wei)",
+        "Gas Used",
+        "Status",
+        "Block Number",
+    ])?;
+    for tx in txs {
+        wtr.write_record(&tx.to_csv_record())?;
+    }
+    wtr.flush()?;
+    Ok(())
+}
+
+fn write_statement_csv(out: &mut impl Write, context: &ExportContext, statement: &Statement) -> Result<()> {
+    let mut wtr = csv::Writer::from_writer(out);
+    wtr.write_record([
+        "Date",
+        "Transaction Hash",
+        "Block",
+        "Direction",
+        "Asset",
+        "Amount",
+        "Fee (RBTC)",
+        "Counterparty",
+        "Counterparty Address",
+        "Status",
+        "Running Balance",
+    ])?;
+    for line in &statement.lines {
+        wtr.write_record([
+            context.zone.format(line.time, "%Y-%m-%d %H:%M:%S %:z"),
+            format!("{:#x}", line.hash),
+            line.block.map(|b| b.to_string()).unwrap_or_default(),
+            line.direction.to_string(),
+            line.asset.symbol.clone(),
+            format_amount(line.amount, line.asset.decimals),
+            format_amount(I256::from_raw(line.fee), 18),
+            line.counterparty_name.clone().unwrap_or_default(),
+            line.counterparty.map(|a| format!("{:#x}", a)).unwrap_or_default(),
+            line.status.to_string(),
+            format_amount(line.balance, line.asset.decimals),
+        ])?;
+    }
+    wtr.flush()?;
+    Ok(())
+}
+
+fn write_jsonl(out: &mut impl Write, context: &ExportContext, statement: &Statement) -> Result<()> {
+    for line in &statement.lines {
+        let record = serde_json::json!({
+            "hash": format!("{:#x}", line.hash),
+            "timestamp": context.zone.format(line.time, "%Y-%m-%dT%H:%M:%S%:z"),
+            "block": line.block,
+            "status": line.status,
+            "direction": line.direction.to_string(),
+            "asset": line.asset.symbol,
+            "token_address": line.token.map(|a| format!("{:#x}", a)),
+            "amount": format_amount(line.amount, line.asset.decimals),
+            "amount_raw": line.amount.to_string(),
+            "fee_rbtc": format_amount(I256::from_raw(line.fee), 18),
+            "counterparty": line.counterparty.map(|a| format!("{:#x}", a)),
+            "counterparty_name": line.counterparty_name,
+            "balance": format_amount(line.balance, line.asset.decimals),
+        });
+        writeln!(out, "{}", record)?;
+    }
+    Ok(())
+}
+
+/// A booked movement in one asset's account, used by OFX and QIF
+struct Posting {
+    time: DateTime<Utc>,
+    amount: I256,
+    id: String,
+    payee: String,
+    memo: String,
+    is_fee: bool,
+}
+
+/// Splits the statement into one account per asset. Fees become separate
+/// postings in the RBTC account and pending transfers are left out.
+fn postings_by_asset(statement: &Statement) -> Vec<(Option<Address>, Asset, Vec<Posting>)> {
+    let mut accounts: Vec<(Option<Address>, Asset, Vec<Posting>)> = Vec::new();
+    let mut account = |token: Option<Address>, asset: &Asset| -> usize {
+        match accounts.iter().position(|(t, _, _)| *t == token) {
+            Some(index) => index,
+            None => {
+                accounts.push((token, asset.clone(), Vec::new()));
+                accounts.len() - 1
+            }
+        }
+    };
+    let mut postings: Vec<(usize, Posting)> = Vec::new();
+
+    for line in &statement.lines {
+        let hash = format!("{:#x}", line.hash);
+        let payee = line
+            .counterparty_name
+            .clone()
+            .or_else(|| line.counterparty.map(|a| format!("{:#x}", a)))
+            .unwrap_or_else(|| "Contract creation".to_string());
+
+        if line.status == TransactionStatus::Success && !line.amount.is_zero() {
+            let index = account(line.token, &line.asset);
+            postings.push((
+                index,
+                Posting {
+                    time: line.time,
+                    amount: line.amount,
+                    id: format!("{}-{}", hash, postings.len()),
+                    payee: payee.clone(),
+                    memo: format!("{} transfer {}", line.asset.symbol, hash),
+                    is_fee: false,
+                },
+            ));
+        }
+        if !line.fee.is_zero() {
+            let index = account(None, &Asset::rbtc());
+            postings.push((
+                index,
+                Posting {
+                    time: line.time,
+                    amount: -I256::from_raw(line.fee),
+                    id: format!("{}-fee", hash),
+                    payee: "Rootstock network fee".to_string(),
+                    memo: format!("Network fee {}", hash),
+                    is_fee: true,
+                },
+            ));
+        }
+    }
+
+    for (index, posting) in postings {
+        accounts[index].2.push(posting);
+    }
+    accounts
+}
+
+fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+fn write_ofx(out: &mut impl Write, context: &ExportContext, statement: &Statement) -> Result<()> {
+    let ofx_time = |time: DateTime<Utc>| time.format("%Y%m%d%H%M%S[0:GMT]").to_string();
+    let now = ofx_time(Utc::now());
+    let start = statement.lines.first().map(|l| ofx_time(l.time)).unwrap_or_else(|| now.clone());
+    let end = statement.lines.last().map(|l| ofx_time(l.time)).unwrap_or_else(|| now.clone());
+
+    writeln!(out, r#"<?xml version="1.0" encoding="UTF-8" standalone="no"?>"#)?;
+    writeln!(
+        out,
+        r#"<?OFX OFXHEADER="200" VERSION="220" SECURITY="NONE" OLDFILEUID="NONE" NEWFILEUID="NONE"?>"#
+    )?;
+    writeln!(out, "<OFX>")?;
+    writeln!(out, "<SIGNONMSGSRSV1><SONRS>")?;
+    writeln!(out, "<STATUS><CODE>0</CODE><SEVERITY>INFO</SEVERITY></STATUS>")?;
+    writeln!(out, "<DTSERVER>{}</DTSERVER><LANGUAGE>ENG</LANGUAGE>", now)?;
+    writeln!(out, "</SONRS></SIGNONMSGSRSV1>")?;
+    writeln!(out, "<BANKMSGSRSV1>")?;
+
+    for (number, (token, asset, postings)) in postings_by_asset(statement).into_iter().enumerate() {
+        writeln!(out, "<STMTTRNRS>")?;
+        writeln!(out, "<TRNUID>{}</TRNUID>", number + 1)?;
+        writeln!(out, "<STATUS><CODE>0</CODE><SEVERITY>INFO</SEVERITY></STATUS>")?;
+        writeln!(out, "<STMTRS>")?;
+        // Crypto assets have no ISO 4217 code; the account id names the asset
+        writeln!(out, "<CURDEF>XXX</CURDEF>")?;
+        writeln!(
+            out,
+            "<BANKACCTFROM><BANKID>ROOTSTOCK</BANKID><ACCTID>{}:{:#x}</ACCTID><ACCTTYPE>CHECKING</ACCTTYPE></BANKACCTFROM>",
+            xml_escape(&asset.symbol),
+            context.address
+        )?;
+        writeln!(out, "<BANKTRANLIST><DTSTART>{}</DTSTART><DTEND>{}</DTEND>", start, end)?;
+        for posting in &postings {
+            let kind = match (posting.is_fee, posting.amount.is_negative()) {
+                (true, _) => "FEE",
+                (false, true) => "DEBIT",
+                (false, false) => "CREDIT",
+            };
+            writeln!(out, "<STMTTRN>")?;
+            writeln!(out, "<TRNTYPE>{}</TRNTYPE>", kind)?;
+            writeln!(out, "<DTPOSTED>{}</DTPOSTED>", ofx_time(posting.time))?;
+            writeln!(out, "<TRNAMT>{}</TRNAMT>", format_amount(posting.amount, asset.decimals))?;
+            writeln!(out, "<FITID>{}</FITID>", posting.id)?;
+            writeln!(out, "<NAME>{}</NAME>", xml_escape(&posting.payee))?;
+            writeln!(out, "<MEMO>{}</MEMO>", xml_escape(&posting.memo))?;
+            writeln!(out, "</STMTTRN>")?;
+        }
+        writeln!(out, "</BANKTRANLIST>")?;
+        writeln!(
+            out,
+            "<LEDGERBAL><BALAMT>{}</BALAMT><DTASOF>{}</DTASOF></LEDGERBAL>",
+            format_amount(statement.closing.get(&token).copied().unwrap_or_default(), asset.decimals),
+            end
+        )?;
+        writeln!(out, "</STMTRS>")?;
+        writeln!(out, "</STMTTRNRS>")?;
+    }
+
+    writeln!(out, "</BANKMSGSRSV1>")?;
+    writeln!(out, "</OFX>")?;
+    Ok(())
+}
+
+/// QIF fields end at the line break, so any inside a value would start a
+/// new field
+fn qif_field(text: &str) -> String {
+    text.split(['\r', '\n']).filter(|part| !part.is_empty()).collect::<Vec<_>>().join(" ")
+}
+
+fn write_qif(out: &mut impl Write, context: &ExportContext, statement: &Statement) -> Result<()> {
+    writeln!(out, "!Option:AutoSwitch")?;
+    for (_, asset, postings) in postings_by_asset(statement) {
+        writeln!(out, "!Account")?;
+        writeln!(out, "N{} {:#x}", asset.symbol, context.address)?;
+        writeln!(out, "TBank")?;
+        writeln!(out, "^")?;
+        writeln!(out, "!Type:Bank")?;
+        for posting in &postings {
+            writeln!(out, "D{}", context.zone.format(posting.time, "%m/%d/%Y"))?;
+            writeln!(out, "T{}", format_amount(posting.amount, asset.decimals))?;
+            writeln!(out, "P{}", qif_field(&posting.payee))?;
+            writeln!(out, "M{}", qif_field(&posting.memo))?;
+            writeln!(out, "^")?;
+        }
+    }
+    writeln!(out, "!Clear:AutoSwitch")?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy::primitives::U64;
+    use chrono::FixedOffset;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    fn transfer(hash: u8, from: Address, to: Address, value: u64, token: Option<Address>, status: TransactionStatus) -> RskTransaction {
+        RskTransaction {
+            hash: B256::with_last_byte(hash),
+            from,
+            to: Some(to),
+            value: U256::from(value),
+            gas_price: U256::from(10),
+            gas: U256::from(21_000),
+            nonce: U256::ZERO,
+            input: None,
+            block_number: Some(U64::from(hash)),
+            transaction_index: None,
+            timestamp: UNIX_EPOCH + Duration::from_secs(hash as u64 * 60),
+            status,
+            token_address: token,
+            confirms: None,
+            cumulative_gas_used: None,
+            logs: None,
+        }
+    }
+
+    #[test]
+    fn test_statement_running_balances() {
+        let me = Address::with_last_byte(1);
+        let other = Address::with_last_byte(2);
+        let token = Address::with_last_byte(9);
+        let context = ExportContext {
+            address: me,
+            zone: DateZone::Fixed(FixedOffset::east_opt(0).unwrap()),
+            assets: HashMap::from([(token, Asset { symbol: "RIF".into(), decimals: 2 })]),
+            contacts: HashMap::from([(other, "Alice".to_string())]),
+        };
+        let txs = vec![
+            transfer(3, me, other, 250, Some(token), TransactionStatus::Success),
+            transfer(1, other, me, 1_000_000, None, TransactionStatus::Success),
+            transfer(2, me, other, 400_000, None, TransactionStatus::Failed),
+        ];
+        let opening = HashMap::from([(Some(token), I256::try_from(1_000).unwrap())]);
+
+        let statement = context.statement(&txs, &opening);
+        let fee = 210_000;
+        let balances: Vec<I256> = statement.lines.iter().map(|l| l.balance).collect();
+        assert_eq!(
+            balances,
+            vec![
+                I256::try_from(1_000_000).unwrap(),
+                // A failed transfer only costs the fee
+                I256::try_from(1_000_000 - fee).unwrap(),
+                I256::try_from(750).unwrap(),
+            ]
+        );
+        assert_eq!(statement.lines[2].asset.symbol, "RIF");
+        assert_eq!(statement.lines[2].direction, Direction::Outgoing);
+        assert_eq!(statement.lines[2].counterparty_name.as_deref(), Some("Alice"));
+        assert_eq!(statement.closing[&None], I256::try_from(1_000_000 - 2 * fee).unwrap());
+        assert_eq!(format_amount(statement.lines[2].amount, 2), "-2.5");
+    }
+
+    #[test]
+    fn test_filtered_statement_keeps_full_balances() {
+        let me = Address::with_last_byte(1);
+        let other = Address::with_last_byte(2);
+        let context = ExportContext {
+            address: me,
+            zone: DateZone::Fixed(FixedOffset::east_opt(0).unwrap()),
+            assets: HashMap::new(),
+            contacts: HashMap::from([(other, "Line\nbreak".to_string())]),
+        };
+        let txs = vec![
+            transfer(1, other, me, 1_000_000, None, TransactionStatus::Success),
+            transfer(2, me, other, 400_000, None, TransactionStatus::Success),
+            transfer(3, other, me, 50_000, None, TransactionStatus::Success),
+        ];
+
+        // Only incoming transfers are listed, but the outgoing one still counts
+        let statement = context.statement_where(&txs, &HashMap::new(), |tx| tx.to == Some(me));
+        let balances: Vec<I256> = statement.lines.iter().map(|l| l.balance).collect();
+        let fee = 210_000;
+        assert_eq!(
+            balances,
+            vec![
+                I256::try_from(1_000_000).unwrap(),
+                I256::try_from(1_000_000 - 400_000 - fee + 50_000).unwrap(),
+            ]
+        );
+
+        let mut qif = Vec::new();
+        write_qif(&mut qif, &context, &context.statement(&txs, &HashMap::new())).unwrap();
+        let qif = String::from_utf8(qif).unwrap();
+        assert!(qif.lines().any(|line| line == "PLine break"));
+        assert!(!qif.lines().any(|line| line == "break"));
+    }
+
+    #[test]
+    fn test_format_amount() {
+        let wei = I256::try_from(1_500_000_000_000_000_000u128).unwrap();
+        assert_eq!(format_amount(wei, 18), "1.5");
+        assert_eq!(format_amount(-wei, 18), "-1.5");
+        assert_eq!(format_amount(I256::ZERO, 18), "0");
+        assert_eq!(format_amount(I256::try_from(42).unwrap(), 0), "42");
+    }
+}
@@ -1,6 +1,8 @@
+use crate::api::ApiProvider;
 use crate::config::ConfigManager;
 use crate::types::network::{Network, NetworkConfig};
 use crate::utils::eth::EthClient;
+use crate::utils::rpc_pool::redact_url;
 use anyhow::Result;
 use colored::Colorize;
 use alloy::primitives::Address;
@@ -27,6 +29,7 @@ impl Default for Config {
                 name: "RSK Mainnet".to_string(),
                 rpc_url: "https://public-node.rsk.co".to_string(),
                 explorer_url: "https://explorer.rsk.co".to_string(),
+                fallback_rpc_urls: Vec::new(),
             },
             wallet: WalletConfig {
                 current_wallet_address: None,
@@ -41,40 +44,54 @@ pub struct Helper;
 
 impl Helper {
     pub async fn init_eth_client(network: &str) -> Result<(Config, EthClient)> {
-        let network_enum = Network::from_str(network).unwrap_or(Network::Mainnet);
-
         // Load configuration to get API keys
         let config_manager = ConfigManager::new()?;
         let app_config = config_manager.load()?;
 
-        // Get API keys from config
-        let rsk_api_key = app_config.get_rsk_rpc_key();
-        let alchemy_api_key = app_config.get_alchemy_key();
-
-        // Get the appropriate RPC URL with API key preference
-        let rpc_url = network_enum.get_rpc_url_with_key(rsk_api_key, alchemy_api_key);
-
-        // Create network config with the selected RPC URL
-        let mut net_cfg = network_enum.get_config();
-        net_cfg.rpc_url = rpc_url.clone();
-
-        let mut config = Config::default();
-        config.network = net_cfg.clone();
-
-        // Log which RPC endpoint is being used
-        let rpc_type = if rsk_api_key.is_some() {
-            "RSK RPC API"
-        } else if alchemy_api_key.is_some() {
-            "Alchemy API"
+        // User-defined networks carry their own endpoints
+        let (config, rpc_type) = if let Some(custom) = app_config.custom_network(network) {
+            let config = Config {
+                network: custom.get_config(),
+                ..Default::default()
+            };
+            (config, "Custom RPC")
         } else {
-            "Public Node"
+            let network_enum = Network::from_str(network).unwrap_or(Network::Mainnet);
+
+            // RPC URLs with API key preference; the rest are failover endpoints
+            let mut rpc_urls = app_config.rpc_urls_for(&network_enum, None).into_iter();
+
+            // Create network config with the selected RPC URL
+            let mut net_cfg = network_enum.get_config();
+            net_cfg.rpc_url = rpc_urls.next().unwrap_or_default();
+            net_cfg.fallback_rpc_urls = rpc_urls.collect();
+
+            let config = Config {
+                network: net_cfg,
+                ..Default::default()
+            };
+
+            // Log which RPC endpoint is being used
+            let rpc_type = if app_config.api_key_for(&ApiProvider::RskRpc, &network_enum).is_some() {
+                "RSK RPC API"
+            } else if app_config.api_key_for(&ApiProvider::Alchemy, &network_enum).is_some() {
+                "Alchemy API"
+            } else {
+                "Public Node"
+            };
+            (config, rpc_type)
         };
 
+        let fallbacks = match config.network.fallback_rpc_urls.len() {
+            0 => String::new(),
+            1 => ", 1 fallback".to_string(),
+            n => format!(", {} fallbacks", n),
+        };
         println!(
             "[rsk-rust-cli] Connected to {} at {} ({})",
             config.network.name,
-            config.network.rpc_url,
-            rpc_type.dimmed()
+            redact_url(&config.network.rpc_url),
+            format!("{}{}", rpc_type, fallbacks).dimmed()
         );
 
         let eth_client = EthClient::new(&config, None).await?;
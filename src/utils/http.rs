@@ -0,0 +1,502 @@
+use crate::config::ConfigManager;
+use crate::utils::rpc_pool::redact_url;
+use anyhow::{Result, anyhow};
+use chrono::{DateTime, Utc};
+use console::style;
+use rand::Rng;
+use reqwest::header::RETRY_AFTER;
+use reqwest::{Client, Proxy, RequestBuilder, Response, StatusCode};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// JSON-RPC error codes providers use for rate limiting
+pub const RATE_LIMIT_CODES: &[i64] = &[-32005, 429];
+
+/// Retry, timeout and rate-limit settings for every HTTP and RPC call,
+/// stored as `http` in the config file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HttpPolicy {
+    /// Seconds allowed to establish a connection
+    pub connect_timeout_secs: u64,
+    /// Seconds allowed for a whole request, including the response body
+    pub request_timeout_secs: u64,
+    /// Retries after the first attempt
+    pub max_retries: u32,
+    /// First backoff delay, doubled on every retry
+    pub initial_backoff_ms: u64,
+    pub max_backoff_ms: u64,
+    /// Longest `Retry-After` honoured; a server asking for more fails the
+    /// request instead of stalling it
+    pub max_retry_after_secs: u64,
+    pub rate_limits: RateLimits,
+    /// Proxy for all outbound calls, e.g. `http://proxy:8080` or
+    /// `socks5h://127.0.0.1:1080`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub proxy: Option<String>,
+}
+
+impl Default for HttpPolicy {
+    fn default() -> Self {
+        Self {
+            connect_timeout_secs: 5,
+            request_timeout_secs: 30,
+            max_retries: 4,
+            initial_backoff_ms: 500,
+            max_backoff_ms: 30_000,
+            max_retry_after_secs: 120,
+            rate_limits: RateLimits::default(),
+            proxy: None,
+        }
+    }
+}
+
+impl HttpPolicy {
+    pub fn connect_timeout(&self) -> Duration {
+        Duration::from_secs(self.connect_timeout_secs)
+    }
+
+    pub fn request_timeout(&self) -> Duration {
+        Duration::from_secs(self.request_timeout_secs)
+    }
+
+    /// Checks a proxy URL; HTTP, HTTPS and SOCKS5 proxies are supported
+    pub fn validate_proxy(url: &str) -> Result<()> {
+        let parsed =
+            url::Url::parse(url).map_err(|e| anyhow!("Invalid proxy URL '{}': {}", url, e))?;
+        if !matches!(parsed.scheme(), "http" | "https" | "socks5" | "socks5h") {
+            return Err(anyhow!(
+                "Unsupported proxy scheme '{}'; use http, https, socks5 or socks5h",
+                parsed.scheme()
+            ));
+        }
+        Proxy::all(url).map_err(|e| anyhow!("Invalid proxy URL '{}': {}", url, e))?;
+        Ok(())
+    }
+
+    /// Delay before retry number `attempt` (from 0), with up to 20% jitter
+    /// so parallel requests do not retry in lockstep
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let base = self
+            .initial_backoff_ms
+            .saturating_mul(1 << attempt.min(16))
+            .min(self.max_backoff_ms);
+        let jitter = rand::thread_rng().gen_range(0..=base / 5);
+        Duration::from_millis(base - jitter)
+    }
+}
+
+/// Token-bucket limits for each kind of provider
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RateLimits {
+    pub alchemy: RateLimit,
+    pub rsk_rpc: RateLimit,
+    pub public_node: RateLimit,
+    pub other: RateLimit,
+}
+
+impl Default for RateLimits {
+    fn default() -> Self {
+        Self {
+            alchemy: RateLimit::new(10.0, 20),
+            rsk_rpc: RateLimit::new(20.0, 40),
+            public_node: RateLimit::new(5.0, 10),
+            other: RateLimit::new(0.0, 0),
+        }
+    }
+}
+
+impl RateLimits {
+    pub fn get(&self, provider: Provider) -> RateLimit {
+        match provider {
+            Provider::Alchemy => self.alchemy,
+            Provider::RskRpc => self.rsk_rpc,
+            Provider::PublicNode => self.public_node,
+            Provider::Other => self.other,
+        }
+    }
+
+    pub fn get_mut(&mut self, provider: Provider) -> &mut RateLimit {
+        match provider {
+            Provider::Alchemy => &mut self.alchemy,
+            Provider::RskRpc => &mut self.rsk_rpc,
+            Provider::PublicNode => &mut self.public_node,
+            Provider::Other => &mut self.other,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct RateLimit {
+    /// Sustained requests per second; 0 disables the limit
+    pub requests_per_second: f64,
+    /// Requests that may be sent at once before the rate applies
+    pub burst: u32,
+}
+
+impl RateLimit {
+    pub const fn new(requests_per_second: f64, burst: u32) -> Self {
+        Self {
+            requests_per_second,
+            burst,
+        }
+    }
+}
+
+/// Kind of service behind a URL, each with its own rate limit
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Provider {
+    Alchemy,
+    RskRpc,
+    PublicNode,
+    Other,
+}
+
+impl Provider {
+    pub const ALL: [Provider; 4] = [Self::Alchemy, Self::RskRpc, Self::PublicNode, Self::Other];
+
+    pub fn from_url(url: &str) -> Self {
+        let host = url::Url::parse(url)
+            .ok()
+            .and_then(|u| u.host_str().map(str::to_lowercase))
+            .unwrap_or_default();
+        if host.ends_with(".alchemy.com") {
+            Self::Alchemy
+        } else if host.starts_with("rpc.") && host.ends_with(".rootstock.io") {
+            Self::RskRpc
+        } else if host.starts_with("public-node.") && host.ends_with("rsk.co") {
+            Self::PublicNode
+        } else {
+            Self::Other
+        }
+    }
+}
+
+impl std::fmt::Display for Provider {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Alchemy => write!(f, "Alchemy"),
+            Self::RskRpc => write!(f, "RSK RPC"),
+            Self::PublicNode => write!(f, "Public node"),
+            Self::Other => write!(f, "Other"),
+        }
+    }
+}
+
+/// Which failures a request may be retried after
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Retry {
+    /// Timeouts, failed connections, 502/503/504 and 429 responses. Only
+    /// for requests that are safe to repeat.
+    Transient,
+    /// Only 429 responses, which the server rejected without acting on
+    RateLimited,
+}
+
+#[derive(Debug)]
+struct TokenBucket {
+    limit: RateLimit,
+    tokens: f64,
+    updated: Instant,
+}
+
+impl TokenBucket {
+    fn new(limit: RateLimit, now: Instant) -> Self {
+        Self {
+            limit,
+            tokens: limit.burst.max(1) as f64,
+            updated: now,
+        }
+    }
+
+    /// Takes a token, or returns how long until one is available
+    fn take(&mut self, now: Instant) -> Option<Duration> {
+        let rate = self.limit.requests_per_second;
+        if rate <= 0.0 {
+            return None;
+        }
+        let capacity = self.limit.burst.max(1) as f64;
+        let elapsed = now.saturating_duration_since(self.updated).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * rate).min(capacity);
+        self.updated = now;
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            None
+        } else {
+            Some(Duration::from_secs_f64((1.0 - self.tokens) / rate))
+        }
+    }
+
+    /// Empties the bucket after the provider reported throttling, so other
+    /// requests to it slow down as well
+    fn drain(&mut self) {
+        self.tokens = 0.0;
+    }
+}
+
+/// HTTP client shared by every network call, applying the [`HttpPolicy`]
+#[derive(Debug)]
+pub struct HttpClient {
+    client: Client,
+    policy: HttpPolicy,
+    buckets: Mutex<HashMap<Provider, TokenBucket>>,
+}
+
+static SHARED: Mutex<Option<Arc<HttpClient>>> = Mutex::new(None);
+
+/// The shared client, built from the config on first use
+pub fn client() -> Arc<HttpClient> {
+    let mut shared = SHARED.lock().unwrap_or_else(|e| e.into_inner());
+    shared
+        .get_or_insert_with(|| {
+            let policy = ConfigManager::new()
+                .and_then(|manager| manager.load())
+                .map(|config| config.http)
+                .unwrap_or_default();
+            Arc::new(HttpClient::new(policy))
+        })
+        .clone()
+}
+
+/// Drops the shared client so the next call picks up a changed policy
+pub fn reload() {
+    *SHARED.lock().unwrap_or_else(|e| e.into_inner()) = None;
+}
+
+impl HttpClient {
+    pub fn new(policy: HttpPolicy) -> Self {
+        let mut builder = Client::builder()
+            .connect_timeout(policy.connect_timeout())
+            .timeout(policy.request_timeout())
+            .use_rustls_tls();
+        if let Some(proxy) = &policy.proxy {
+            match HttpPolicy::validate_proxy(proxy).and_then(|_| Ok(Proxy::all(proxy)?)) {
+                Ok(proxy) => builder = builder.proxy(proxy),
+                Err(e) => eprintln!("{}", style(format!("⚠️  Ignoring proxy setting: {}", e)).yellow()),
+            }
+        }
+        // Fall back to a plain client, but never to one without timeouts
+        let client = builder.build().unwrap_or_else(|e| {
+            eprintln!(
+                "{}",
+                style(format!("⚠️  Could not set up the HTTP client ({}); connecting without a proxy", e)).yellow()
+            );
+            Client::builder()
+                .connect_timeout(policy.connect_timeout())
+                .timeout(policy.request_timeout())
+                .build()
+                .expect("HTTP client without a proxy")
+        });
+        Self {
+            client,
+            policy,
+            buckets: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn policy(&self) -> &HttpPolicy {
+        &self.policy
+    }
+
+    /// The underlying client, for one-off requests that need neither
+    /// retries nor rate limiting
+    pub fn inner(&self) -> &Client {
+        &self.client
+    }
+
+    /// Waits for the provider's rate limit to allow another request
+    async fn throttle(&self, provider: Provider) {
+        loop {
+            let wait = {
+                let now = Instant::now();
+                let mut buckets = self.buckets.lock().unwrap_or_else(|e| e.into_inner());
+                buckets
+                    .entry(provider)
+                    .or_insert_with(|| TokenBucket::new(self.policy.rate_limits.get(provider), now))
+                    .take(now)
+            };
+            match wait {
+                Some(wait) => tokio::time::sleep(wait).await,
+                None => return,
+            }
+        }
+    }
+
+    fn drain(&self, provider: Provider) {
+        if let Some(bucket) = self
+            .buckets
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get_mut(&provider)
+        {
+            bucket.drain();
+        }
+    }
+
+    /// Sends the request made by `build` to `url`, rate limited for the
+    /// URL's provider and retried according to `retry`. Responses with
+    /// error statuses are returned once retries are used up.
+    pub async fn send(
+        &self,
+        url: &str,
+        retry: Retry,
+        build: impl Fn(&Client) -> RequestBuilder,
+    ) -> Result<Response> {
+        let provider = Provider::from_url(url);
+        let mut attempt = 0;
+        loop {
+            self.throttle(provider).await;
+            let result = build(&self.client).send().await;
+
+            let wait = match &result {
+                Ok(response) if response.status() == StatusCode::TOO_MANY_REQUESTS => {
+                    self.drain(provider);
+                    Some(retry_after(response, Utc::now()).unwrap_or_else(|| self.policy.backoff(attempt)))
+                }
+                Ok(response)
+                    if retry == Retry::Transient
+                        && matches!(
+                            response.status(),
+                            StatusCode::BAD_GATEWAY
+                                | StatusCode::SERVICE_UNAVAILABLE
+                                | StatusCode::GATEWAY_TIMEOUT
+                        ) =>
+                {
+                    Some(self.policy.backoff(attempt))
+                }
+                Err(e) if retry == Retry::Transient && (e.is_timeout() || e.is_connect()) => {
+                    Some(self.policy.backoff(attempt))
+                }
+                _ => None,
+            };
+
+            match wait {
+                Some(wait)
+                    if attempt < self.policy.max_retries
+                        && wait <= Duration::from_secs(self.policy.max_retry_after_secs) =>
+                {
+                    log::warn!(
+                        "Request to {} failed, retrying in {:.1}s",
+                        redact_url(url),
+                        wait.as_secs_f64()
+                    );
+                    tokio::time::sleep(wait).await;
+                    attempt += 1;
+                }
+                _ => {
+                    return result.map_err(|e| anyhow!("Request to {} failed: {}", redact_url(url), e));
+                }
+            }
+        }
+    }
+
+    /// Posts a JSON-RPC request and returns the response object, retrying
+    /// rate-limit errors reported in the body as well as in the status.
+    /// `bearer` is sent as an `Authorization` header.
+    pub async fn post_json(
+        &self,
+        url: &str,
+        body: &Value,
+        bearer: Option<&str>,
+        retry: Retry,
+    ) -> Result<Value> {
+        let provider = Provider::from_url(url);
+        let mut attempt = 0;
+        loop {
+            let response = self
+                .send(url, retry, |client| {
+                    let request = client.post(url).json(body);
+                    match bearer {
+                        Some(token) => request.bearer_auth(token),
+                        None => request,
+                    }
+                })
+                .await?;
+            let status = response.status();
+            let text = response
+                .text()
+                .await
+                .map_err(|e| anyhow!("Failed to read response from {}: {}", redact_url(url), e))?;
+            let value: Value = serde_json::from_str(&text).map_err(|_| {
+                anyhow!(
+                    "Unexpected response from {} (HTTP {}): {}",
+                    redact_url(url),
+                    status,
+                    text.chars().take(200).collect::<String>()
+                )
+            })?;
+
+            let rate_limited = value["error"]["code"]
+                .as_i64()
+                .is_some_and(|code| RATE_LIMIT_CODES.contains(&code));
+            if rate_limited && attempt < self.policy.max_retries {
+                self.drain(provider);
+                tokio::time::sleep(self.policy.backoff(attempt)).await;
+                attempt += 1;
+                continue;
+            }
+            return Ok(value);
+        }
+    }
+}
+
+/// Delay asked for by a `Retry-After` header, in seconds or as an HTTP date
+fn retry_after(response: &Response, now: DateTime<Utc>) -> Option<Duration> {
+    parse_retry_after(response.headers().get(RETRY_AFTER)?.to_str().ok()?, now)
+}
+
+fn parse_retry_after(value: &str, now: DateTime<Utc>) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(secs) = value.parse::<u64>() {
+        return Some(Duration::from_secs(secs));
+    }
+    let at = DateTime::parse_from_rfc2822(value).ok()?.to_utc();
+    Some((at - now).to_std().unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_token_bucket_allows_burst_then_rate() {
+        let start = Instant::now();
+        let mut bucket = TokenBucket::new(RateLimit::new(2.0, 3), start);
+        for _ in 0..3 {
+            assert_eq!(bucket.take(start), None);
+        }
+        assert_eq!(bucket.take(start), Some(Duration::from_millis(500)));
+        assert_eq!(bucket.take(start + Duration::from_millis(500)), None);
+
+        bucket.drain();
+        assert!(bucket.take(start + Duration::from_millis(500)).is_some());
+
+        let mut unlimited = TokenBucket::new(RateLimit::new(0.0, 0), start);
+        assert!((0..100).all(|_| unlimited.take(start).is_none()));
+    }
+
+    #[test]
+    fn test_retry_after_and_backoff() {
+        let now = DateTime::parse_from_rfc3339("2015-10-21T07:27:30Z").unwrap().to_utc();
+        assert_eq!(parse_retry_after("12", now), Some(Duration::from_secs(12)));
+        assert_eq!(
+            parse_retry_after("Wed, 21 Oct 2015 07:28:00 GMT", now),
+            Some(Duration::from_secs(30))
+        );
+        assert_eq!(parse_retry_after("soon", now), None);
+
+        let policy = HttpPolicy::default();
+        let first = policy.backoff(0);
+        assert!(first <= Duration::from_millis(500) && first >= Duration::from_millis(400));
+        assert!(policy.backoff(30) <= Duration::from_millis(policy.max_backoff_ms));
+
+        assert_eq!(Provider::from_url("https://rootstock-mainnet.g.alchemy.com/v2/k"), Provider::Alchemy);
+        assert_eq!(Provider::from_url("https://rpc.testnet.rootstock.io/k"), Provider::RskRpc);
+        assert_eq!(Provider::from_url("https://public-node.rsk.co"), Provider::PublicNode);
+        assert_eq!(Provider::from_url("http://localhost:4444"), Provider::Other);
+    }
+}
@@ -0,0 +1,350 @@
+use crate::types::transaction::{RskTransaction, TransactionStatus};
+use crate::utils::eth::IERC20;
+use crate::utils::tx_store::TransactionStore;
+use alloy::primitives::{Address, B256, Bytes, U64, U256};
+use alloy::providers::Provider;
+use crate::utils::rpc::ChainRpc;
+use crate::utils::rpc_pool::PoolProvider;
+use alloy::rpc::types::Filter;
+use alloy::sol_types::SolEvent;
+use anyhow::{Result, anyhow};
+use console::style;
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::str::FromStr;
+use std::time::{Duration, UNIX_EPOCH};
+use tokio::task::JoinSet;
+
+/// How far back the first scan of an address goes when no start block is given
+pub const DEFAULT_LOOKBACK_BLOCKS: u64 = 20_000;
+
+/// Blocks covered by a single `eth_getLogs` request and checkpoint
+const CHUNK_SIZE: u64 = 2_000;
+
+/// Blocks fetched in parallel while looking for native RBTC transfers
+const BLOCK_SCAN_CONCURRENCY: usize = 8;
+
+/// Builds transaction history from plain JSON-RPC, without a vendor API
+pub struct HistoryIndexer {
+    provider: PoolProvider,
+    block_timestamps: HashMap<u64, u64>,
+}
+
+impl HistoryIndexer {
+    pub fn new(provider: PoolProvider) -> Self {
+        Self {
+            provider,
+            block_timestamps: HashMap::new(),
+        }
+    }
+
+    /// Scans the blocks after the store's checkpoint for ERC20 `Transfer`
+    /// events and native transfers involving its address, saving progress
+    /// after every chunk so an interrupted scan resumes where it stopped.
+    /// A `start_block` before the first scanned block is backfilled.
+    pub async fn sync(&mut self, store: &mut TransactionStore, start_block: Option<u64>) -> Result<()> {
+        let latest = self
+            .provider
+            .get_block_number()
+            .await
+            .map_err(|e| anyhow!("Failed to get latest block: {}", e))?;
+
+        if let (Some(start), Some(first)) = (start_block, store.first_synced_block)
+            && start < first
+        {
+            self.scan(store, start, first - 1, false).await?;
+            store.first_synced_block = Some(start);
+            store.save()?;
+        }
+
+        let from = match store.last_synced_block {
+            Some(block) => block + 1,
+            None => start_block.unwrap_or_else(|| latest.saturating_sub(DEFAULT_LOOKBACK_BLOCKS)),
+        };
+        if store.first_synced_block.is_none() {
+            store.first_synced_block = Some(from);
+        }
+        if from > latest {
+            return Ok(());
+        }
+
+        self.scan(store, from, latest, true).await
+    }
+
+    /// Scans a block range chunk by chunk, saving the store after each
+    /// chunk. With `checkpoint` the last synced block advances as it goes.
+    async fn scan(&mut self, store: &mut TransactionStore, from: u64, to: u64, checkpoint: bool) -> Result<()> {
+        let address = store.address;
+        println!(
+            "{}",
+            style(format!("⏳ Indexing blocks {} to {}...", from, to)).dim()
+        );
+
+        let mut chunk_start = from;
+        while chunk_start <= to {
+            let chunk_end = (chunk_start + CHUNK_SIZE - 1).min(to);
+
+            for tx in self.token_transfers(address, chunk_start, chunk_end).await? {
+                store.upsert(tx);
+            }
+            for tx in self.native_transfers(address, chunk_start, chunk_end).await? {
+                store.upsert(tx);
+            }
+
+            if checkpoint {
+                store.last_synced_block = Some(chunk_end);
+            }
+            store.save()?;
+            chunk_start = chunk_end + 1;
+        }
+
+        Ok(())
+    }
+
+    /// Contracts that emitted ERC20 `Transfer` events from or to `address`
+    /// in the block range
+    pub async fn token_contracts(&self, address: Address, from: u64, to: u64) -> Result<HashSet<Address>> {
+        let topic = B256::from(address.into_word());
+        let mut contracts = HashSet::new();
+        let mut chunk_start = from;
+        while chunk_start <= to {
+            let chunk_end = (chunk_start + CHUNK_SIZE - 1).min(to);
+            let base = Filter::new()
+                .event_signature(IERC20::Transfer::SIGNATURE_HASH)
+                .from_block(chunk_start)
+                .to_block(chunk_end);
+            for filter in [base.clone().topic1(topic), base.topic2(topic)] {
+                let logs = self
+                    .provider
+                    .get_logs(&filter)
+                    .await
+                    .map_err(|e| anyhow!("Failed to get Transfer logs: {}", e))?;
+                contracts.extend(logs.iter().map(|log| log.address()));
+            }
+            chunk_start = chunk_end + 1;
+        }
+        Ok(contracts)
+    }
+
+    /// ERC20 transfers from or to `address` in the block range
+    async fn token_transfers(&mut self, address: Address, from: u64, to: u64) -> Result<Vec<RskTransaction>> {
+        let topic = B256::from(address.into_word());
+        let base = Filter::new()
+            .event_signature(IERC20::Transfer::SIGNATURE_HASH)
+            .from_block(from)
+            .to_block(to);
+
+        let mut logs = self
+            .provider
+            .get_logs(&base.clone().topic1(topic))
+            .await
+            .map_err(|e| anyhow!("Failed to get Transfer logs: {}", e))?;
+        logs.extend(
+            self.provider
+                .get_logs(&base.topic2(topic))
+                .await
+                .map_err(|e| anyhow!("Failed to get Transfer logs: {}", e))?,
+        );
+
+        let mut txs = Vec::new();
+        for log in logs {
+            let Ok(decoded) = log.log_decode::<IERC20::Transfer>() else {
+                continue;
+            };
+            let (Some(hash), Some(block_number)) = (log.transaction_hash, log.block_number) else {
+                continue;
+            };
+            let event = &decoded.inner.data;
+            let details = self.transaction_details(hash).await?;
+            let timestamp = self.block_timestamp(block_number).await?;
+            // The transaction only carries the gas limit; fees need gas used
+            let receipt = self.provider.receipt(hash).await?;
+            let (gas_used, gas_price) = match &receipt {
+                Some(r) => (U256::from(r.gas_used), U256::from(r.effective_gas_price)),
+                None => (hex_u256(&details["gas"]), hex_u256(&details["gasPrice"])),
+            };
+
+            txs.push(RskTransaction {
+                hash,
+                from: event.from,
+                to: Some(event.to),
+                value: event.value,
+                gas_price,
+                gas: gas_used,
+                nonce: hex_u256(&details["nonce"]),
+                input: None,
+                block_number: Some(U64::from(block_number)),
+                transaction_index: log.transaction_index.map(U64::from),
+                timestamp: UNIX_EPOCH + Duration::from_secs(timestamp),
+                // Logs are only emitted by successful transactions
+                status: TransactionStatus::Success,
+                token_address: Some(log.address()),
+                confirms: None,
+                cumulative_gas_used: None,
+                logs: None,
+            });
+        }
+        Ok(txs)
+    }
+
+    /// Native transactions sent from or to `address` in the block range
+    async fn native_transfers(&mut self, address: Address, from: u64, to: u64) -> Result<Vec<RskTransaction>> {
+        let mut txs = Vec::new();
+        let mut block = from;
+        while block <= to {
+            let batch_end = (block + BLOCK_SCAN_CONCURRENCY as u64 - 1).min(to);
+            let mut tasks = JoinSet::new();
+            for number in block..=batch_end {
+                let provider = self.provider.clone();
+                tasks.spawn(async move {
+                    provider
+                        .raw_request::<_, Value>(
+                            "eth_getBlockByNumber".into(),
+                            (format!("0x{:x}", number), true),
+                        )
+                        .await
+                });
+            }
+
+            while let Some(result) = tasks.join_next().await {
+                let block_json = result?.map_err(|e| anyhow!("Failed to get block: {}", e))?;
+                if block_json.is_null() {
+                    continue;
+                }
+                let number = hex_u64(&block_json["number"]);
+                let timestamp = hex_u64(&block_json["timestamp"]);
+                self.block_timestamps.insert(number, timestamp);
+
+                for tx in block_json["transactions"].as_array().into_iter().flatten() {
+                    let tx_from = tx["from"].as_str().and_then(|s| Address::from_str(s).ok());
+                    let tx_to = tx["to"].as_str().and_then(|s| Address::from_str(s).ok());
+                    if tx_from != Some(address) && tx_to != Some(address) {
+                        continue;
+                    }
+                    let Some(hash) = tx["hash"].as_str().and_then(|s| B256::from_str(s).ok()) else {
+                        continue;
+                    };
+
+                    let receipt = self.provider.receipt(hash).await?;
+                    let (status, gas_used) = match &receipt {
+                        Some(r) if r.status() => (TransactionStatus::Success, U256::from(r.gas_used)),
+                        Some(r) => (TransactionStatus::Failed, U256::from(r.gas_used)),
+                        None => (TransactionStatus::Pending, U256::ZERO),
+                    };
+
+                    txs.push(RskTransaction {
+                        hash,
+                        from: tx_from.unwrap_or_default(),
+                        to: tx_to,
+                        value: hex_u256(&tx["value"]),
+                        gas_price: hex_u256(&tx["gasPrice"]),
+                        gas: gas_used,
+                        nonce: hex_u256(&tx["nonce"]),
+                        input: tx["input"].as_str().and_then(|s| Bytes::from_str(s).ok()),
+                        block_number: Some(U64::from(number)),
+                        transaction_index: Some(U64::from(hex_u64(&tx["transactionIndex"]))),
+                        timestamp: UNIX_EPOCH + Duration::from_secs(timestamp),
+                        status,
+                        token_address: None,
+                        confirms: None,
+                        cumulative_gas_used: Some(gas_used),
+                        logs: None,
+                    });
+                }
+            }
+            block = batch_end + 1;
+        }
+        Ok(txs)
+    }
+
+    async fn transaction_details(&self, hash: B256) -> Result<Value> {
+        self.provider
+            .raw_request::<_, Value>("eth_getTransactionByHash".into(), (hash,))
+            .await
+            .map_err(|e| anyhow!("Failed to get transaction {}: {}", hash, e))
+    }
+
+    async fn block_timestamp(&mut self, number: u64) -> Result<u64> {
+        if let Some(timestamp) = self.block_timestamps.get(&number) {
+            return Ok(*timestamp);
+        }
+        let block = self
+            .provider
+            .raw_request::<_, Value>(
+                "eth_getBlockByNumber".into(),
+                (format!("0x{:x}", number), false),
+            )
+            .await
+            .map_err(|e| anyhow!("Failed to get block {}: {}", number, e))?;
+        let timestamp = hex_u64(&block["timestamp"]);
+        self.block_timestamps.insert(number, timestamp);
+        Ok(timestamp)
+    }
+}
+
+fn hex_u256(value: &Value) -> U256 {
+    value
+        .as_str()
+        .and_then(|s| U256::from_str_radix(s.trim_start_matches("0x"), 16).ok())
+        .unwrap_or_default()
+}
+
+fn hex_u64(value: &Value) -> u64 {
+    value
+        .as_str()
+        .and_then(|s| u64::from_str_radix(s.trim_start_matches("0x"), 16).ok())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::mock_node::{Fixtures, TestEnv};
+    use crate::utils::rpc_pool::RpcPool;
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn test_interrupted_sync_resumes_from_checkpoint() {
+        let holder = Address::with_last_byte(0xaa);
+        let sender = Address::with_last_byte(0xbb);
+        let (early, late) = (Address::with_last_byte(0x71), Address::with_last_byte(0x72));
+        let mut fixtures = Fixtures {
+            block_number: 2_100,
+            ..Fixtures::default()
+        };
+        fixtures.logs.push(Fixtures::transfer_log(early, sender, holder, U256::from(1), 20));
+        // Only the second chunk (2050..=2100) has a log to look up
+        fixtures.logs.push(Fixtures::transfer_log(late, holder, sender, U256::from(2), 2_080));
+        let env = TestEnv::start(fixtures).await;
+        let pool = Arc::new(RpcPool::new(&[env.node.url().to_string()]));
+        let mut indexer = HistoryIndexer::new(pool.provider());
+
+        env.node.fail("eth_getTransactionByHash", true);
+        let mut store = TransactionStore::load("testnet", &holder).unwrap();
+        assert!(indexer.sync(&mut store, Some(50)).await.is_err());
+        let store = TransactionStore::load("testnet", &holder).unwrap();
+        assert_eq!(store.first_synced_block, Some(50));
+        assert_eq!(store.last_synced_block, Some(2_049));
+        assert!(store.transactions.is_empty());
+
+        env.node.fail("eth_getTransactionByHash", false);
+        let mut store = TransactionStore::load("testnet", &holder).unwrap();
+        let logs_before = env.node.methods().iter().filter(|m| *m == "eth_getLogs").count();
+        indexer.sync(&mut store, None).await.unwrap();
+        // Resumed with the remaining chunk only: one query per topic
+        let logs_after = env.node.methods().iter().filter(|m| *m == "eth_getLogs").count();
+        assert_eq!(logs_after - logs_before, 2);
+        assert_eq!(store.last_synced_block, Some(2_100));
+        assert_eq!(store.transactions.len(), 1);
+        assert_eq!(store.transactions[0].token_address, Some(late));
+
+        // An earlier start block backfills without moving the checkpoint
+        indexer.sync(&mut store, Some(0)).await.unwrap();
+        let store = TransactionStore::load("testnet", &holder).unwrap();
+        assert_eq!((store.first_synced_block, store.last_synced_block), (Some(0), Some(2_100)));
+        assert_eq!(store.transactions.len(), 2);
+
+        let contracts = indexer.token_contracts(holder, 0, 2_100).await.unwrap();
+        assert_eq!(contracts, HashSet::from([early, late]));
+    }
+}
@@ -0,0 +1,540 @@
+//! In-process JSON-RPC node and throwaway app directories for running
+//! commands end to end in tests
+
+use crate::config::{Config, ConfigManager};
+use crate::types::network::{EndpointOverride, Network};
+use crate::types::wallet::{Wallet, WalletData};
+use crate::utils::{constants, http};
+use crate::utils::eth::IERC20;
+use crate::utils::paths::AppPaths;
+use crate::utils::secrets::SecretPassword;
+use alloy::consensus::{Transaction, TxEnvelope};
+use alloy::eips::eip2718::Decodable2718;
+use alloy::primitives::{Address, B256, Bytes, U256, keccak256};
+use alloy::signers::local::PrivateKeySigner;
+use alloy::sol_types::{SolCall, SolEvent, SolValue};
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use tempfile::TempDir;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+/// An ERC20 token deployed on the mock chain
+#[derive(Debug, Clone)]
+pub struct MockToken {
+    pub symbol: String,
+    pub decimals: u8,
+    pub balances: HashMap<Address, U256>,
+}
+
+/// Chain state the mock node serves
+#[derive(Debug, Clone)]
+pub struct Fixtures {
+    pub chain_id: u64,
+    pub block_number: u64,
+    pub gas_price: u128,
+    pub balances: HashMap<Address, U256>,
+    pub tokens: HashMap<Address, MockToken>,
+    /// `eth_getTransactionByHash` results
+    pub transactions: HashMap<B256, Value>,
+    /// `eth_getTransactionReceipt` results
+    pub receipts: HashMap<B256, Value>,
+    /// Transfers returned by `alchemy_getAssetTransfers`, filtered by the
+    /// requested sender or recipient
+    pub asset_transfers: Vec<Value>,
+    /// Logs returned by `eth_getLogs`, filtered by block range and topics
+    pub logs: Vec<Value>,
+}
+
+impl Default for Fixtures {
+    fn default() -> Self {
+        Self {
+            chain_id: 31,
+            block_number: 100,
+            gas_price: 60_000_000,
+            balances: HashMap::new(),
+            tokens: HashMap::new(),
+            transactions: HashMap::new(),
+            receipts: HashMap::new(),
+            asset_transfers: Vec::new(),
+            logs: Vec::new(),
+        }
+    }
+}
+
+impl Fixtures {
+    /// ERC20 `Transfer` log emitted by `token` at `block`, in node format
+    pub fn transfer_log(token: Address, from: Address, to: Address, value: U256, block: u64) -> Value {
+        json!({
+            "address": token,
+            "topics": [
+                IERC20::Transfer::SIGNATURE_HASH,
+                B256::from(from.into_word()),
+                B256::from(to.into_word()),
+            ],
+            "data": Bytes::from(value.to_be_bytes::<32>().to_vec()),
+            "blockHash": keccak256(block.to_be_bytes()),
+            "blockNumber": format!("0x{:x}", block),
+            "transactionHash": keccak256([token.as_slice(), &block.to_be_bytes()].concat()),
+            "transactionIndex": "0x0",
+            "logIndex": "0x0",
+            "removed": false
+        })
+    }
+
+    /// Receipt of a successful transaction mined at `block`, in node format
+    pub fn receipt(hash: B256, from: Address, to: Address, block: u64) -> Value {
+        json!({
+            "transactionHash": hash,
+            "transactionIndex": "0x0",
+            "blockHash": keccak256(block.to_be_bytes()),
+            "blockNumber": format!("0x{:x}", block),
+            "from": from,
+            "to": to,
+            "contractAddress": null,
+            "gasUsed": "0x5208",
+            "cumulativeGasUsed": "0x5208",
+            "effectiveGasPrice": "0x3938700",
+            "logs": [],
+            "logsBloom": format!("0x{}", "0".repeat(512)),
+            "status": "0x1",
+            "type": "0x0"
+        })
+    }
+}
+
+#[derive(Debug, Default)]
+struct State {
+    fixtures: Fixtures,
+    sent: Vec<TxEnvelope>,
+    methods: Vec<String>,
+    failing: Vec<String>,
+    /// Receipts of transactions accepted while mining is held
+    held: Vec<(B256, Value)>,
+    holding: bool,
+}
+
+/// JSON-RPC node on a local port that answers from [`Fixtures`] and applies
+/// the transactions sent to it
+#[derive(Clone)]
+pub struct MockNode {
+    url: String,
+    state: Arc<Mutex<State>>,
+}
+
+impl MockNode {
+    pub async fn start(fixtures: Fixtures) -> Self {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let state = Arc::new(Mutex::new(State {
+            fixtures,
+            ..State::default()
+        }));
+        let shared = state.clone();
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                let state = shared.clone();
+                tokio::spawn(async move {
+                    let Some(body) = read_request(&mut socket).await else {
+                        return;
+                    };
+                    let reply = match serde_json::from_str::<Value>(&body) {
+                        Ok(Value::Array(batch)) => {
+                            Value::Array(batch.iter().map(|r| handle(&state, r)).collect())
+                        }
+                        Ok(request) => handle(&state, &request),
+                        Err(_) => json!({"jsonrpc": "2.0", "id": null, "error": {"code": -32700, "message": "parse error"}}),
+                    }
+                    .to_string();
+                    let response = format!(
+                        "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+                        reply.len(),
+                        reply
+                    );
+                    let _ = socket.write_all(response.as_bytes()).await;
+                });
+            }
+        });
+        Self { url, state }
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Methods called so far, in order
+    pub fn methods(&self) -> Vec<String> {
+        self.state.lock().unwrap().methods.clone()
+    }
+
+    /// Answers every later call to `method` with an internal error, or
+    /// stops doing so when `failing` is false
+    pub fn fail(&self, method: &str, failing: bool) {
+        let mut state = self.state.lock().unwrap();
+        state.failing.retain(|m| m != method);
+        if failing {
+            state.failing.push(method.to_string());
+        }
+    }
+
+    /// Keeps later transactions pending: they are accepted and known, but
+    /// have no receipt and do not count towards the latest nonce until
+    /// mining is released, which mines them all in one block
+    pub fn hold_mining(&self, holding: bool) {
+        let mut state = self.state.lock().unwrap();
+        state.holding = holding;
+        if !holding && !state.held.is_empty() {
+            state.fixtures.block_number += 1;
+            let held = std::mem::take(&mut state.held);
+            state.fixtures.receipts.extend(held);
+        }
+    }
+
+    /// Adds an empty block
+    pub fn mine(&self) {
+        self.state.lock().unwrap().fixtures.block_number += 1;
+    }
+
+    /// Transactions received through `eth_sendRawTransaction`
+    pub fn sent(&self) -> Vec<TxEnvelope> {
+        self.state.lock().unwrap().sent.clone()
+    }
+
+    pub fn balance(&self, holder: Address) -> U256 {
+        let state = self.state.lock().unwrap();
+        state.fixtures.balances.get(&holder).copied().unwrap_or_default()
+    }
+
+    pub fn token_balance(&self, token: Address, holder: Address) -> U256 {
+        let state = self.state.lock().unwrap();
+        state.fixtures.tokens[&token]
+            .balances
+            .get(&holder)
+            .copied()
+            .unwrap_or_default()
+    }
+}
+
+/// Reads one HTTP request and returns its body
+async fn read_request(socket: &mut tokio::net::TcpStream) -> Option<String> {
+    let mut data = Vec::new();
+    let mut buf = [0u8; 8192];
+    loop {
+        let n = socket.read(&mut buf).await.ok()?;
+        if n == 0 {
+            return None;
+        }
+        data.extend_from_slice(&buf[..n]);
+        let text = String::from_utf8_lossy(&data).to_string();
+        if let Some((head, body)) = text.split_once("\r\n\r\n") {
+            let length = head
+                .lines()
+                .find_map(|l| {
+                    l.to_lowercase()
+                        .strip_prefix("content-length:")
+                        .and_then(|v| v.trim().parse::<usize>().ok())
+                })
+                .unwrap_or(0);
+            if body.len() >= length {
+                return Some(body.to_string());
+            }
+        }
+    }
+}
+
+fn handle(state: &Mutex<State>, request: &Value) -> Value {
+    let method = request["method"].as_str().unwrap_or_default();
+    let mut state = state.lock().unwrap();
+    state.methods.push(method.to_string());
+    let result = if state.failing.iter().any(|m| m == method) {
+        Err((-32603, "internal error".to_string()))
+    } else {
+        answer(&mut state, method, &request["params"])
+    };
+    match result {
+        Ok(result) => json!({"jsonrpc": "2.0", "id": request["id"], "result": result}),
+        Err((code, message)) => json!({
+            "jsonrpc": "2.0",
+            "id": request["id"],
+            "error": {"code": code, "message": message}
+        }),
+    }
+}
+
+fn quantity(value: impl std::fmt::LowerHex) -> Value {
+    json!(format!("0x{:x}", value))
+}
+
+fn param_address(value: &Value) -> Address {
+    value.as_str().and_then(|s| Address::from_str(s).ok()).unwrap_or_default()
+}
+
+fn answer(state: &mut State, method: &str, params: &Value) -> Result<Value, (i64, String)> {
+    let fixtures = &mut state.fixtures;
+    Ok(match method {
+        "eth_chainId" => quantity(fixtures.chain_id),
+        "net_version" => json!(fixtures.chain_id.to_string()),
+        "eth_blockNumber" => quantity(fixtures.block_number),
+        "eth_gasPrice" => quantity(fixtures.gas_price),
+        "eth_getBalance" => {
+            let holder = param_address(&params[0]);
+            quantity(fixtures.balances.get(&holder).copied().unwrap_or_default())
+        }
+        "eth_getCode" => {
+            let target = param_address(&params[0]);
+            json!(if fixtures.tokens.contains_key(&target) { "0x6080" } else { "0x" })
+        }
+        "eth_getTransactionCount" => {
+            let holder = param_address(&params[0]);
+            let sent = state
+                .sent
+                .iter()
+                .filter(|tx| tx.recover_signer().ok() == Some(holder))
+                .count();
+            let pending = match params[1].as_str() {
+                Some("pending") => 0,
+                _ => state
+                    .held
+                    .iter()
+                    .filter(|(_, receipt)| param_address(&receipt["from"]) == holder)
+                    .count(),
+            };
+            quantity(sent - pending)
+        }
+        "eth_estimateGas" => {
+            let data = &params[0]["input"].as_str().or(params[0]["data"].as_str());
+            quantity(if data.is_some_and(|d| d.len() > 2) { 60_000 } else { 21_000 })
+        }
+        "eth_call" => {
+            let to = param_address(&params[0]["to"]);
+            let from = param_address(&params[0]["from"]);
+            let data = params[0]["input"]
+                .as_str()
+                .or(params[0]["data"].as_str())
+                .and_then(|d| Bytes::from_str(d).ok())
+                .unwrap_or_default();
+            let token = fixtures
+                .tokens
+                .get(&to)
+                .ok_or((3, "execution reverted".to_string()))?;
+            json!(Bytes::from(token_call(token, from, &data)?))
+        }
+        "eth_sendRawTransaction" => {
+            let raw = params[0].as_str().and_then(|d| Bytes::from_str(d).ok()).unwrap_or_default();
+            let tx = TxEnvelope::decode_2718(&mut raw.as_ref())
+                .map_err(|e| (-32602, format!("invalid transaction: {}", e)))?;
+            let from = tx
+                .recover_signer()
+                .map_err(|e| (-32602, format!("invalid signature: {}", e)))?;
+            let hash = *tx.tx_hash();
+            if let Some(existing) = state
+                .sent
+                .iter()
+                .find(|sent| sent.nonce() == tx.nonce() && sent.recover_signer().ok() == Some(from))
+            {
+                let message = if *existing.tx_hash() == hash { "already known" } else { "nonce too low" };
+                return Err((-32000, message.to_string()));
+            }
+            apply(fixtures, from, &tx)?;
+            fixtures.block_number += 1;
+            let to = tx.to().unwrap_or_default();
+            let receipt = Fixtures::receipt(hash, from, to, fixtures.block_number);
+            if state.holding {
+                state.held.push((hash, receipt));
+            } else {
+                fixtures.receipts.insert(hash, receipt);
+            }
+            fixtures.transactions.insert(
+                hash,
+                json!({
+                    "hash": hash,
+                    "from": from,
+                    "to": to,
+                    "value": quantity(tx.value()),
+                    "gas": quantity(tx.gas_limit()),
+                    "gasPrice": quantity(tx.gas_price().unwrap_or_default()),
+                    "nonce": quantity(tx.nonce()),
+                    "input": tx.input(),
+                    "blockNumber": quantity(fixtures.block_number),
+                }),
+            );
+            state.sent.push(tx);
+            json!(hash)
+        }
+        "eth_getTransactionByHash" => {
+            let hash = B256::from_str(params[0].as_str().unwrap_or_default()).unwrap_or_default();
+            fixtures.transactions.get(&hash).cloned().unwrap_or(Value::Null)
+        }
+        "eth_getTransactionReceipt" => {
+            let hash = B256::from_str(params[0].as_str().unwrap_or_default()).unwrap_or_default();
+            fixtures.receipts.get(&hash).cloned().unwrap_or(Value::Null)
+        }
+        "eth_getBlockByNumber" => {
+            let number = match params[0].as_str() {
+                Some("latest") | Some("pending") | None => fixtures.block_number,
+                Some(hex) => u64::from_str_radix(hex.trim_start_matches("0x"), 16).unwrap_or_default(),
+            };
+            if number > fixtures.block_number {
+                Value::Null
+            } else {
+                json!({
+                    "number": quantity(number),
+                    "hash": keccak256(number.to_be_bytes()),
+                    "timestamp": quantity(chrono::Utc::now().timestamp()),
+                    "transactions": [],
+                })
+            }
+        }
+        "eth_getLogs" => {
+            let filter = &params[0];
+            let block = |value: &Value, default: u64| {
+                value
+                    .as_str()
+                    .and_then(|b| u64::from_str_radix(b.trim_start_matches("0x"), 16).ok())
+                    .unwrap_or(default)
+            };
+            let (from, to) = (block(&filter["fromBlock"], 0), block(&filter["toBlock"], fixtures.block_number));
+            let same = |wanted: &Value, actual: &Value| {
+                wanted.as_str().zip(actual.as_str()).is_some_and(|(w, a)| w.eq_ignore_ascii_case(a))
+            };
+            let topic_matches = |log: &Value, i: usize| match &filter["topics"][i] {
+                Value::Null => true,
+                Value::Array(wanted) => wanted.iter().any(|w| same(w, &log["topics"][i])),
+                wanted => same(wanted, &log["topics"][i]),
+            };
+            let logs: Vec<Value> = fixtures
+                .logs
+                .iter()
+                .filter(|log| (from..=to).contains(&block(&log["blockNumber"], 0)))
+                .filter(|log| (0..4).all(|i| topic_matches(log, i)))
+                .cloned()
+                .collect();
+            json!(logs)
+        }
+        "alchemy_getAssetTransfers" => {
+            let query = &params[0];
+            let matches = |transfer: &Value, side: &str, key: &str| {
+                query[key].as_str().is_none_or(|address| {
+                    transfer[side].as_str().is_some_and(|a| a.eq_ignore_ascii_case(address))
+                })
+            };
+            let transfers: Vec<Value> = fixtures
+                .asset_transfers
+                .iter()
+                .filter(|t| matches(t, "from", "fromAddress") && matches(t, "to", "toAddress"))
+                .cloned()
+                .collect();
+            json!({ "transfers": transfers })
+        }
+        _ => return Err((-32601, format!("the method {} does not exist/is not available", method))),
+    })
+}
+
+/// Answers a read-only ERC20 call
+fn token_call(token: &MockToken, from: Address, data: &[u8]) -> Result<Vec<u8>, (i64, String)> {
+    let revert = || (3, "execution reverted".to_string());
+    let selector: [u8; 4] = data.get(..4).and_then(|s| s.try_into().ok()).ok_or_else(revert)?;
+    Ok(match selector {
+        IERC20::balanceOfCall::SELECTOR => {
+            let call = IERC20::balanceOfCall::abi_decode(data, true).map_err(|_| revert())?;
+            (token.balances.get(&call.account).copied().unwrap_or_default(),).abi_encode_params()
+        }
+        IERC20::decimalsCall::SELECTOR => (U256::from(token.decimals),).abi_encode_params(),
+        IERC20::symbolCall::SELECTOR => (token.symbol.clone(),).abi_encode_params(),
+        IERC20::transferCall::SELECTOR => {
+            let call = IERC20::transferCall::abi_decode(data, true).map_err(|_| revert())?;
+            let balance = token.balances.get(&from).copied().unwrap_or_default();
+            if balance < call.amount {
+                return Err((3, "execution reverted: insufficient balance".to_string()));
+            }
+            (true,).abi_encode_params()
+        }
+        _ => return Err(revert()),
+    })
+}
+
+/// Moves the value or tokens of a sent transaction
+fn apply(fixtures: &mut Fixtures, from: Address, tx: &TxEnvelope) -> Result<(), (i64, String)> {
+    let to = tx.to().ok_or((-32602, "contract creation is not supported".to_string()))?;
+    let fee = U256::from(tx.gas_price().unwrap_or_default()) * U256::from(21_000);
+    let sender = fixtures.balances.entry(from).or_default();
+    *sender = sender
+        .checked_sub(tx.value() + fee)
+        .ok_or((-32010, "insufficient funds for gas * price + value".to_string()))?;
+    *fixtures.balances.entry(to).or_default() += tx.value();
+
+    if let Some(token) = fixtures.tokens.get_mut(&to)
+        && let Ok(call) = IERC20::transferCall::abi_decode(tx.input(), true)
+    {
+        let sender = token.balances.entry(from).or_default();
+        *sender = sender
+            .checked_sub(call.amount)
+            .ok_or((3, "execution reverted: insufficient balance".to_string()))?;
+        *token.balances.entry(call.recipient).or_default() += call.amount;
+    }
+    Ok(())
+}
+
+/// Only one test environment exists at a time, since the installed paths
+/// are process wide
+static ENV_LOCK: tokio::sync::Mutex<()> = tokio::sync::Mutex::const_new(());
+
+/// Temporary data and config directories with a config that points mainnet
+/// and testnet, including Alchemy, at a [`MockNode`]. Tests holding one run
+/// one after another; dropping it restores the real directories.
+pub struct TestEnv {
+    pub node: MockNode,
+    pub home: TempDir,
+    _guard: tokio::sync::MutexGuard<'static, ()>,
+}
+
+impl TestEnv {
+    pub async fn start(fixtures: Fixtures) -> Self {
+        let guard = ENV_LOCK.lock().await;
+        let home = tempfile::tempdir().unwrap();
+        AppPaths::install(AppPaths::new(Some(home.path().to_path_buf()), None).unwrap());
+
+        let node = MockNode::start(fixtures).await;
+        let endpoints = EndpointOverride {
+            rpc_urls: vec![node.url().to_string()],
+            alchemy_url: Some(node.url().to_string()),
+        };
+        let mut config = Config {
+            default_network: Network::Testnet,
+            ..Config::default()
+        };
+        for network in ["mainnet", "testnet"] {
+            config.endpoint_overrides.insert(network.to_string(), endpoints.clone());
+        }
+        ConfigManager::new().unwrap().save(&config).unwrap();
+        http::reload();
+
+        Self {
+            node,
+            home,
+            _guard: guard,
+        }
+    }
+
+    /// Stores a wallet encrypted with `password` and makes it the current one
+    pub fn add_wallet(&self, signer: &PrivateKeySigner, password: &str) -> Address {
+        let path = constants::wallet_file_path();
+        let mut wallet_data = match std::fs::read_to_string(&path) {
+            Ok(data) => serde_json::from_str(&data).unwrap(),
+            Err(_) => WalletData::new(),
+        };
+        let wallet = Wallet::new(signer.clone(), "test", &SecretPassword::new(password.to_string()))
+            .unwrap();
+        wallet_data.add_wallet(wallet).unwrap();
+        std::fs::write(&path, serde_json::to_string_pretty(&wallet_data).unwrap()).unwrap();
+        signer.address()
+    }
+}
+
+impl Drop for TestEnv {
+    fn drop(&mut self) {
+        AppPaths::install(AppPaths::default());
+        http::reload();
+    }
+}
@@ -1,11 +1,28 @@
 pub mod alchemy;
 pub mod api;
 pub mod api_validator;
+pub mod batch_journal;
+pub mod block_resolver;
+pub mod bulk;
 pub mod constants;
+pub mod contact_io;
 pub mod eth;
+pub mod export;
 pub mod helper;
+pub mod http;
+pub mod indexer;
+#[cfg(test)]
+pub mod mock_node;
 pub mod network;
+pub mod nonce_manager;
+pub mod paths;
+pub mod prices;
+pub mod rns;
+pub mod rpc;
+pub mod rpc_pool;
 pub mod secure_fs;
 pub mod secrets;
 pub mod table;
 pub mod terminal;
+pub mod token_list;
+pub mod tx_store;
@@ -1,5 +1,9 @@
-use std::time::Duration;
-use tokio::time::timeout;
+use crate::config::ConfigManager;
+use crate::utils::http;
+use crate::utils::rpc_pool::redact_url;
+use serde_json::{Value, json};
+use std::time::{Duration, Instant};
+use tokio::task::JoinSet;
 
 /// Network connectivity status
 #[derive(Debug, Clone, PartialEq)]
@@ -8,36 +12,191 @@ pub enum NetworkStatus {
     Offline,
 }
 
+/// Result of checking one RPC endpoint of the active network
+#[derive(Debug, Clone, PartialEq)]
+pub enum EndpointCheck {
+    /// Reports the expected chain and a recent block
+    Healthy { block: u64, latency_ms: u128 },
+    /// Right chain, but the latest block is too old to trust
+    Stale { block: u64, age_secs: u64 },
+    WrongChain { expected: u64, actual: u64 },
+    Unreachable(String),
+}
+
+impl std::fmt::Display for EndpointCheck {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Healthy { block, latency_ms } => {
+                write!(f, "ok, block {} in {} ms", block, latency_ms)
+            }
+            Self::Stale { block, age_secs } => {
+                write!(f, "stale, latest block {} is {} min old", block, age_secs / 60)
+            }
+            Self::WrongChain { expected, actual } => {
+                write!(f, "wrong chain id {} (expected {})", actual, expected)
+            }
+            Self::Unreachable(error) => write!(f, "unreachable: {}", error),
+        }
+    }
+}
+
+/// Connectivity of the active network, endpoint by endpoint
+#[derive(Debug, Clone)]
+pub struct ConnectivityReport {
+    /// Offline mode is forced in the config; no endpoint was contacted
+    pub forced_offline: bool,
+    /// Endpoint URLs, with API keys removed, and their check results
+    pub endpoints: Vec<(String, EndpointCheck)>,
+}
+
+impl ConnectivityReport {
+    /// Online when at least one endpoint is healthy
+    pub fn status(&self) -> NetworkStatus {
+        if !self.forced_offline && self.healthy() > 0 {
+            NetworkStatus::Online
+        } else {
+            NetworkStatus::Offline
+        }
+    }
+
+    pub fn healthy(&self) -> usize {
+        self.endpoints
+            .iter()
+            .filter(|(_, check)| matches!(check, EndpointCheck::Healthy { .. }))
+            .count()
+    }
+}
+
 /// Check if network connectivity is available
 pub async fn check_connectivity() -> NetworkStatus {
-    // Try to make a simple HTTP request with a short timeout
-    let client = reqwest::Client::builder()
-        .timeout(Duration::from_secs(3))
-        .build()
-        .unwrap_or_default();
-
-    // Test with a reliable endpoint
-    let test_urls = [
-        "https://httpbin.org/status/200",
-        "https://www.google.com",
-        "https://public-node.testnet.rsk.co",
-    ];
-
-    for url in &test_urls {
-        if let Ok(Ok(response)) = timeout(Duration::from_secs(2), client.get(*url).send()).await {
-            if response.status().is_success() {
-                return NetworkStatus::Online;
-            }
+    check_network().await.status()
+}
+
+/// Checks every RPC endpoint of the configured network: it must answer
+/// `eth_chainId` with the network's chain id and have a recent latest block
+pub async fn check_network() -> ConnectivityReport {
+    let config = match ConfigManager::new().and_then(|manager| manager.load()) {
+        Ok(config) => config,
+        Err(e) => {
+            return ConnectivityReport {
+                forced_offline: false,
+                endpoints: vec![("config".to_string(), EndpointCheck::Unreachable(e.to_string()))],
+            };
         }
+    };
+    if config.force_offline {
+        return ConnectivityReport {
+            forced_offline: true,
+            endpoints: Vec::new(),
+        };
+    }
+
+    let expected_chain = config.expected_chain_id();
+    let max_age = match config.active_custom() {
+        Some(_) => None,
+        None => config.default_network.max_block_age(),
+    };
+
+    let urls = config.network_config().rpc_urls();
+    let mut tasks = JoinSet::new();
+    for (index, url) in urls.iter().cloned().enumerate() {
+        tasks.spawn(async move { (index, check_endpoint(&url, expected_chain, max_age).await) });
+    }
+    let mut results = vec![None; urls.len()];
+    while let Some(Ok((index, check))) = tasks.join_next().await {
+        results[index] = Some(check);
+    }
+
+    ConnectivityReport {
+        forced_offline: false,
+        endpoints: urls
+            .iter()
+            .zip(results)
+            .map(|(url, check)| {
+                (
+                    redact_url(url),
+                    check.unwrap_or_else(|| EndpointCheck::Unreachable("check did not finish".into())),
+                )
+            })
+            .collect(),
+    }
+}
+
+async fn check_endpoint(url: &str, expected_chain: Option<u64>, max_age: Option<Duration>) -> EndpointCheck {
+    let started = Instant::now();
+    let chain_id = match rpc_call(url, "eth_chainId", json!([])).await.and_then(|v| parse_quantity(&v)) {
+        Ok(chain_id) => chain_id,
+        Err(e) => return EndpointCheck::Unreachable(e),
+    };
+    if let Some(expected) = expected_chain
+        && chain_id != expected
+    {
+        return EndpointCheck::WrongChain {
+            expected,
+            actual: chain_id,
+        };
     }
 
-    NetworkStatus::Offline
+    let block = match rpc_call(url, "eth_getBlockByNumber", json!(["latest", false])).await {
+        Ok(block) => block,
+        Err(e) => return EndpointCheck::Unreachable(e),
+    };
+    let latency_ms = started.elapsed().as_millis();
+    let (Ok(number), Ok(timestamp)) = (parse_quantity(&block["number"]), parse_quantity(&block["timestamp"]))
+    else {
+        return EndpointCheck::Unreachable("invalid latest block".to_string());
+    };
+
+    let now = chrono::Utc::now().timestamp().max(0) as u64;
+    let age_secs = now.saturating_sub(timestamp);
+    match max_age {
+        Some(max_age) if age_secs > max_age.as_secs() => EndpointCheck::Stale {
+            block: number,
+            age_secs,
+        },
+        _ => EndpointCheck::Healthy {
+            block: number,
+            latency_ms,
+        },
+    }
+}
+
+/// One JSON-RPC call without retries, bounded by the connect timeout so
+/// startup is not held up by a dead endpoint
+async fn rpc_call(url: &str, method: &str, params: Value) -> Result<Value, String> {
+    let http = http::client();
+    let limit = http.policy().connect_timeout() * 2;
+    let body = json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params });
+    let response = http
+        .inner()
+        .post(url)
+        .json(&body)
+        .timeout(limit)
+        .send()
+        .await
+        .map_err(|e| if e.is_timeout() { "timed out".to_string() } else { e.to_string() })?;
+    if !response.status().is_success() {
+        return Err(format!("HTTP {}", response.status()));
+    }
+    let mut value: Value = response.json().await.map_err(|e| e.to_string())?;
+    if let Some(error) = value.get("error") {
+        return Err(format!("RPC error: {}", error));
+    }
+    Ok(value["result"].take())
+}
+
+fn parse_quantity(value: &Value) -> Result<u64, String> {
+    value
+        .as_str()
+        .and_then(|hex| u64::from_str_radix(hex.trim_start_matches("0x"), 16).ok())
+        .ok_or_else(|| format!("unexpected value {}", value))
 }
 
 /// Features available in offline mode
 pub fn get_offline_features() -> Vec<&'static str> {
     vec![
         "Wallet Management",
+        "Transaction History",
         "Contact Management", 
         "Token Management",
         "Configuration",
@@ -49,3 +208,35 @@ pub fn get_offline_features() -> Vec<&'static str> {
 pub fn is_offline_feature(feature: &str) -> bool {
     get_offline_features().contains(&feature)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_report_is_online_only_with_a_healthy_endpoint() {
+        let mut report = ConnectivityReport {
+            forced_offline: false,
+            endpoints: vec![
+                ("a".into(), EndpointCheck::WrongChain { expected: 30, actual: 31 }),
+                ("b".into(), EndpointCheck::Stale { block: 1, age_secs: 3600 }),
+            ],
+        };
+        assert_eq!(report.status(), NetworkStatus::Offline);
+
+        report
+            .endpoints
+            .push(("c".into(), EndpointCheck::Healthy { block: 2, latency_ms: 40 }));
+        assert_eq!(report.status(), NetworkStatus::Online);
+        assert_eq!(report.healthy(), 1);
+
+        report.forced_offline = true;
+        assert_eq!(report.status(), NetworkStatus::Offline);
+    }
+
+    #[test]
+    fn test_parse_quantity() {
+        assert_eq!(parse_quantity(&json!("0x1e")), Ok(30));
+        assert!(parse_quantity(&json!(null)).is_err());
+    }
+}
@@ -0,0 +1,130 @@
+use crate::utils::http;
+use crate::utils::rpc::ChainRpc;
+use crate::utils::rpc_pool::PoolProvider;
+use alloy::primitives::{Address, B256};
+use alloy::providers::Provider;
+use alloy::rpc::types::TransactionReceipt;
+use anyhow::{Result, anyhow};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::{Mutex, mpsc};
+use tokio::task::JoinHandle;
+
+/// Hands out consecutive nonces locally, so a batch can be signed and put
+/// in flight without asking the node before every transaction
+#[derive(Debug, Default)]
+pub struct NonceManager {
+    next: Mutex<HashMap<Address, u64>>,
+}
+
+impl NonceManager {
+    /// Next nonce of `address`, starting from its pending count on first use
+    pub async fn next(&self, provider: &PoolProvider, address: Address) -> Result<u64> {
+        let mut next = self.next.lock().await;
+        let nonce = match next.get(&address) {
+            Some(nonce) => *nonce,
+            None => pending_nonce(provider, address).await?,
+        };
+        next.insert(address, nonce + 1);
+        Ok(nonce)
+    }
+
+    /// Never hands out nonces below `nonce`, such as ones a journal holds
+    /// transactions for
+    pub async fn advance_to(&self, provider: &PoolProvider, address: Address, nonce: u64) -> Result<()> {
+        let mut next = self.next.lock().await;
+        let current = match next.get(&address) {
+            Some(current) => *current,
+            None => pending_nonce(provider, address).await?,
+        };
+        next.insert(address, current.max(nonce));
+        Ok(())
+    }
+
+    /// Starts again from the node's pending count, after another
+    /// transaction took a nonce or a rejected one left a gap
+    pub async fn resync(&self, provider: &PoolProvider, address: Address) -> Result<u64> {
+        let nonce = pending_nonce(provider, address).await?;
+        self.next.lock().await.insert(address, nonce);
+        Ok(nonce)
+    }
+}
+
+async fn pending_nonce(provider: &PoolProvider, address: Address) -> Result<u64> {
+    provider
+        .get_transaction_count(address)
+        .pending()
+        .await
+        .map_err(|e| anyhow!("Failed to get nonce: {}", e))
+}
+
+/// Whether a node refused a transaction because its nonce was already used
+pub fn is_nonce_too_low(error: &str) -> bool {
+    let error = error.to_lowercase();
+    error.contains("nonce too low") || error.contains("nonce is too low")
+}
+
+/// Polls receipts of watched transactions in a background task, so sending
+/// does not wait on confirmations
+pub struct ConfirmationTracker {
+    watch: mpsc::UnboundedSender<B256>,
+    receipts: mpsc::UnboundedReceiver<(B256, TransactionReceipt)>,
+    task: JoinHandle<()>,
+}
+
+impl ConfirmationTracker {
+    pub fn spawn(provider: Arc<PoolProvider>) -> Self {
+        let (watch, mut watched) = mpsc::unbounded_channel::<B256>();
+        let (found, receipts) = mpsc::unbounded_channel();
+        let task = tokio::spawn(async move {
+            let policy = http::client().policy().clone();
+            let mut pending: Vec<B256> = Vec::new();
+            loop {
+                if pending.is_empty() {
+                    match watched.recv().await {
+                        Some(hash) => pending.push(hash),
+                        None => return,
+                    }
+                }
+                while let Ok(hash) = watched.try_recv() {
+                    pending.push(hash);
+                }
+                let mut still_pending = Vec::with_capacity(pending.len());
+                for hash in pending {
+                    match provider.receipt(hash).await {
+                        Ok(Some(receipt)) => {
+                            if found.send((hash, receipt)).is_err() {
+                                return;
+                            }
+                        }
+                        _ => still_pending.push(hash),
+                    }
+                }
+                pending = still_pending;
+                if !pending.is_empty() {
+                    tokio::time::sleep(policy.backoff(0)).await;
+                }
+            }
+        });
+        Self { watch, receipts, task }
+    }
+
+    pub fn watch(&self, hash: B256) {
+        let _ = self.watch.send(hash);
+    }
+
+    /// Receipts found since the last call
+    pub fn confirmed(&mut self) -> Vec<(B256, TransactionReceipt)> {
+        let mut confirmed = Vec::new();
+        while let Ok(receipt) = self.receipts.try_recv() {
+            confirmed.push(receipt);
+        }
+        confirmed
+    }
+}
+
+impl Drop for ConfirmationTracker {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
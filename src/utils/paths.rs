@@ -0,0 +1,147 @@
+use anyhow::{Result, bail};
+use std::path::PathBuf;
+use std::sync::RwLock;
+
+/// Paths in use by this process, see [`AppPaths::install`]
+static CURRENT: RwLock<Option<AppPaths>> = RwLock::new(None);
+
+/// Where the app keeps its files.
+///
+/// By default data lives in the platform data directory and config in the
+/// platform config directory, both under `rsk-rust-cli`. A home directory
+/// (`--data-dir` or `RSK_CLI_HOME`) keeps both under one root instead, as
+/// `data/` and `config/`. A named profile gets its own `profiles/<name>`
+/// below each, so its wallets, config, token registry and contacts never
+/// mix with another profile's.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AppPaths {
+    home: Option<PathBuf>,
+    profile: Option<String>,
+}
+
+impl AppPaths {
+    /// Paths under `home` (or the platform directories) for `profile`;
+    /// `None` and "default" both mean the default profile
+    pub fn new(home: Option<PathBuf>, profile: Option<&str>) -> Result<Self> {
+        let profile = match profile.map(str::trim) {
+            None | Some("") | Some("default") => None,
+            Some(name) => {
+                validate_profile_name(name)?;
+                Some(name.to_string())
+            }
+        };
+        Ok(Self { home, profile })
+    }
+
+    /// Makes these the paths used by the rest of the process
+    pub fn install(paths: AppPaths) {
+        *CURRENT.write().unwrap() = Some(paths);
+    }
+
+    /// Paths installed for this process, or the defaults
+    pub fn current() -> AppPaths {
+        CURRENT.read().unwrap().clone().unwrap_or_default()
+    }
+
+    /// Name of the active profile, `None` for the default one
+    pub fn profile(&self) -> Option<&str> {
+        self.profile.as_deref()
+    }
+
+    pub fn profile_label(&self) -> &str {
+        self.profile().unwrap_or("default")
+    }
+
+    fn base_data_dir(&self) -> PathBuf {
+        match &self.home {
+            Some(home) => home.join("data"),
+            None => dirs::data_local_dir()
+                .expect("Failed to get data directory")
+                .join("rsk-rust-cli"),
+        }
+    }
+
+    fn base_config_dir(&self) -> PathBuf {
+        match &self.home {
+            Some(home) => home.join("config"),
+            None => dirs::config_dir()
+                .expect("Failed to get config directory")
+                .join("rsk-rust-cli"),
+        }
+    }
+
+    fn for_profile(&self, base: PathBuf) -> PathBuf {
+        match &self.profile {
+            Some(profile) => base.join(PROFILES_DIR).join(profile),
+            None => base,
+        }
+    }
+
+    /// Directory of the wallet file, history, token registry and contacts
+    pub fn data_dir(&self) -> PathBuf {
+        self.for_profile(self.base_data_dir())
+    }
+
+    /// Directory of the config file and API keys
+    pub fn config_dir(&self) -> PathBuf {
+        self.for_profile(self.base_config_dir())
+    }
+
+    /// Named profiles that have data or config, sorted
+    pub fn profiles(&self) -> Vec<String> {
+        let mut names: Vec<String> = [self.base_data_dir(), self.base_config_dir()]
+            .iter()
+            .filter_map(|base| std::fs::read_dir(base.join(PROFILES_DIR)).ok())
+            .flatten()
+            .flatten()
+            .filter(|entry| entry.path().is_dir())
+            .filter_map(|entry| entry.file_name().into_string().ok())
+            .filter(|name| validate_profile_name(name).is_ok())
+            .collect();
+        names.sort();
+        names.dedup();
+        names
+    }
+}
+
+/// Subdirectory holding named profiles; the default profile must not
+/// treat it as its own data
+pub const PROFILES_DIR: &str = "profiles";
+
+/// Profile names become directory names
+pub fn validate_profile_name(name: &str) -> Result<()> {
+    if name.is_empty() || name.len() > 32 {
+        bail!("Profile names must be 1 to 32 characters long");
+    }
+    if !name
+        .chars()
+        .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_')
+    {
+        bail!("Profile names may only use lowercase letters, digits, '-' and '_'");
+    }
+    if name == "default" {
+        bail!("'default' is the profile used when none is selected");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_profiles_are_kept_apart() {
+        let home = PathBuf::from("/tmp/rsk-home");
+        let default = AppPaths::new(Some(home.clone()), Some("default")).unwrap();
+        let ops = AppPaths::new(Some(home.clone()), Some("ops")).unwrap();
+
+        assert_eq!(default.data_dir(), home.join("data"));
+        assert_eq!(default.config_dir(), home.join("config"));
+        assert_eq!(ops.data_dir(), home.join("data/profiles/ops"));
+        assert_eq!(ops.config_dir(), home.join("config/profiles/ops"));
+        assert_eq!(default.profile_label(), "default");
+
+        assert!(AppPaths::new(None, Some("../prod")).is_err());
+        assert!(AppPaths::new(None, Some("Treasury")).is_err());
+    }
+}
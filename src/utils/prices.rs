@@ -0,0 +1,186 @@
+use crate::utils::constants;
+use anyhow::{Result, anyhow};
+use chrono::{DateTime, NaiveDate, NaiveTime, Utc};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Fiat prices for assets, keyed by symbol
+pub trait PriceSource {
+    /// Currency prices are quoted in, e.g. `USD`
+    fn currency(&self) -> &str;
+
+    /// Price of one whole unit of `asset` at `time`, if known
+    fn price_at(&self, asset: &str, time: DateTime<Utc>) -> Option<f64>;
+}
+
+/// Prices read from a local CSV file with `date,asset,price` rows and an
+/// optional `currency` column.
+///
+/// Dates may be `YYYY-MM-DD`, RFC 3339 or Unix seconds. The price for a
+/// moment is the latest one at or before it, so daily closes work as well
+/// as finer-grained data.
+#[derive(Debug, Clone, Default)]
+pub struct CsvPriceSource {
+    currency: String,
+    prices: HashMap<String, Vec<(DateTime<Utc>, f64)>>,
+}
+
+impl CsvPriceSource {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let mut reader = csv::Reader::from_path(path)
+            .map_err(|e| anyhow!("Failed to open price file {}: {}", path.display(), e))?;
+
+        let headers: Vec<String> = reader.headers()?.iter().map(|h| h.trim().to_lowercase()).collect();
+        let column = |name: &str| headers.iter().position(|h| h == name);
+        let (Some(date_col), Some(asset_col), Some(price_col)) =
+            (column("date"), column("asset"), column("price"))
+        else {
+            return Err(anyhow!(
+                "Price file {} needs date, asset and price columns",
+                path.display()
+            ));
+        };
+        let currency_col = column("currency");
+
+        let mut source = Self::default();
+        for (index, record) in reader.records().enumerate() {
+            let record = record?;
+            // Header is line 1
+            let line = index + 2;
+            let field = |col: usize| record.get(col).unwrap_or_default().trim();
+
+            let time = parse_time(field(date_col))
+                .ok_or_else(|| anyhow!("Invalid date '{}' on line {}", field(date_col), line))?;
+            let price: f64 = field(price_col)
+                .parse()
+                .map_err(|_| anyhow!("Invalid price '{}' on line {}", field(price_col), line))?;
+            if let Some(col) = currency_col {
+                let currency = field(col).to_uppercase();
+                if source.currency.is_empty() {
+                    source.currency = currency;
+                } else if !currency.is_empty() && currency != source.currency {
+                    return Err(anyhow!(
+                        "Price file mixes currencies ({} and {}) on line {}",
+                        source.currency,
+                        currency,
+                        line
+                    ));
+                }
+            }
+            source.add(field(asset_col), time, price);
+        }
+
+        if source.currency.is_empty() {
+            source.currency = "USD".to_string();
+        }
+        for series in source.prices.values_mut() {
+            series.sort_by_key(|(time, _)| *time);
+        }
+        Ok(source)
+    }
+
+    fn add(&mut self, asset: &str, time: DateTime<Utc>, price: f64) {
+        self.prices
+            .entry(asset.to_uppercase())
+            .or_default()
+            .push((time, price));
+    }
+}
+
+impl PriceSource for CsvPriceSource {
+    fn currency(&self) -> &str {
+        &self.currency
+    }
+
+    fn price_at(&self, asset: &str, time: DateTime<Utc>) -> Option<f64> {
+        let series = self.prices.get(&asset.to_uppercase())?;
+        let index = series.partition_point(|(t, _)| *t <= time);
+        index.checked_sub(1).map(|i| series[i].1)
+    }
+}
+
+/// Current prices from a local JSON file, used offline or when no price
+/// API is configured:
+///
+/// ```json
+/// { "currency": "USD", "prices": { "RBTC": 97000, "RIF": 0.08, "DOC": 1 } }
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+pub struct JsonPriceSource {
+    #[serde(default = "default_currency")]
+    currency: String,
+    prices: HashMap<String, f64>,
+}
+
+fn default_currency() -> String {
+    "USD".to_string()
+}
+
+impl JsonPriceSource {
+    /// Location of the default price file in the data directory
+    pub fn default_path() -> PathBuf {
+        constants::data_dir().join("prices.json")
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let data = fs::read_to_string(path)
+            .map_err(|e| anyhow!("Failed to read price file {}: {}", path.display(), e))?;
+        let mut source: Self = serde_json::from_str(&data)
+            .map_err(|e| anyhow!("Failed to parse price file {}: {}", path.display(), e))?;
+        source.currency = source.currency.to_uppercase();
+        source.prices = source
+            .prices
+            .into_iter()
+            .map(|(asset, price)| (asset.to_uppercase(), price))
+            .collect();
+        Ok(source)
+    }
+}
+
+impl PriceSource for JsonPriceSource {
+    fn currency(&self) -> &str {
+        &self.currency
+    }
+
+    /// The file holds a single price per asset, used for any time
+    fn price_at(&self, asset: &str, _time: DateTime<Utc>) -> Option<f64> {
+        self.prices.get(&asset.to_uppercase()).copied()
+    }
+}
+
+fn parse_time(value: &str) -> Option<DateTime<Utc>> {
+    if let Ok(datetime) = DateTime::parse_from_rfc3339(value) {
+        return Some(datetime.to_utc());
+    }
+    if let Ok(date) = NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+        return Some(date.and_time(NaiveTime::MIN).and_utc());
+    }
+    value.parse::<i64>().ok().and_then(|secs| DateTime::from_timestamp(secs, 0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_price_at_uses_latest_earlier_price() {
+        let file = tempfile::NamedTempFile::new().unwrap();
+        std::fs::write(
+            file.path(),
+            "date,asset,price,currency\n2024-03-02,RBTC,62000,usd\n2024-03-01,rbtc,60000,USD\n",
+        )
+        .unwrap();
+        let source = CsvPriceSource::load(file.path()).unwrap();
+
+        let at = |s: &str| DateTime::parse_from_rfc3339(s).unwrap().to_utc();
+        assert_eq!(source.currency(), "USD");
+        assert_eq!(source.price_at("RBTC", at("2024-03-01T12:00:00Z")), Some(60000.0));
+        assert_eq!(source.price_at("rbtc", at("2024-03-05T00:00:00Z")), Some(62000.0));
+        assert_eq!(source.price_at("RBTC", at("2024-02-28T00:00:00Z")), None);
+        assert_eq!(source.price_at("RIF", at("2024-03-05T00:00:00Z")), None);
+    }
+}
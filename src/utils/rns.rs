@@ -0,0 +1,98 @@
+use crate::utils::rpc::ChainRpc;
+use alloy::primitives::{Address, B256, address, keccak256};
+use alloy::sol;
+use alloy::sol_types::SolCall;
+use anyhow::{Result, anyhow, bail};
+
+sol! {
+    #[allow(missing_docs)]
+    interface IRnsRegistry {
+        function resolver(bytes32 node) external view returns (address);
+    }
+
+    #[allow(missing_docs)]
+    interface IRnsResolver {
+        function addr(bytes32 node) external view returns (address);
+    }
+}
+
+/// RNS registry on Rootstock mainnet
+pub const RNS_REGISTRY_MAINNET: Address = address!("cb868aeabd31e2b66f74e9a55cf064abb31a4ad5");
+
+/// RNS registry on Rootstock testnet
+pub const RNS_REGISTRY_TESTNET: Address = address!("7d284aaac6e925aad802a53c0c69efe3764597b8");
+
+/// Whether `name` looks like an RNS domain such as `alice.rsk`
+pub fn is_rns_name(name: &str) -> bool {
+    let name = name.trim().to_lowercase();
+    name.ends_with(".rsk") && name.len() > 4 && !name.contains(char::is_whitespace)
+}
+
+/// EIP-137 namehash of a domain
+pub fn namehash(name: &str) -> B256 {
+    let mut node = B256::ZERO;
+    for label in name.trim().to_lowercase().rsplit('.').filter(|l| !l.is_empty()) {
+        node = keccak256([node.as_slice(), keccak256(label.as_bytes()).as_slice()].concat());
+    }
+    node
+}
+
+/// Address an RNS name points to on the chain behind `rpc`
+pub async fn resolve(rpc: &dyn ChainRpc, name: &str) -> Result<Address> {
+    let registry = match rpc.chain_id().await? {
+        30 => RNS_REGISTRY_MAINNET,
+        31 => RNS_REGISTRY_TESTNET,
+        id => bail!("RNS is not available on chain {}", id),
+    };
+    let node = namehash(name);
+
+    let data = rpc
+        .eth_call(registry, IRnsRegistry::resolverCall { node }.abi_encode().into())
+        .await?;
+    let resolver = IRnsRegistry::resolverCall::abi_decode_returns(&data, true)
+        .map_err(|_| anyhow!("Invalid response from the RNS registry"))?
+        ._0;
+    if resolver == Address::ZERO {
+        bail!("{} is not registered", name);
+    }
+
+    let data = rpc
+        .eth_call(resolver, IRnsResolver::addrCall { node }.abi_encode().into())
+        .await?;
+    let resolved = IRnsResolver::addrCall::abi_decode_returns(&data, true)
+        .map_err(|_| anyhow!("Invalid response from the RNS resolver of {}", name))?
+        ._0;
+    if resolved == Address::ZERO {
+        bail!("{} has no address set", name);
+    }
+    Ok(resolved)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::rpc::MockRpc;
+    use alloy::sol_types::SolValue;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_names_resolve_through_registry_and_resolver() {
+        // Test vectors from EIP-137
+        assert_eq!(namehash(""), B256::ZERO);
+        assert_eq!(
+            namehash("foo.eth").to_string(),
+            "0xde9b09fd7c5f901e23a3f19fecc54828e9c848539801e86591bd9801b019f84f"
+        );
+        assert!(is_rns_name("Alice.rsk") && !is_rns_name(".rsk") && !is_rns_name("alice.eth"));
+
+        // The mock answers both calls with the same address
+        let target = Address::with_last_byte(0x42);
+        let rpc = MockRpc::new()
+            .with("eth_chainId", json!("0x1f"))
+            .with("eth_call", json!(alloy::primitives::Bytes::from(target.abi_encode())));
+        assert_eq!(resolve(&rpc, "alice.rsk").await.unwrap(), target);
+        let calls: Vec<_> = rpc.requests().into_iter().filter(|(m, _)| m == "eth_call").collect();
+        assert_eq!(calls[0].1[0]["to"], json!(RNS_REGISTRY_TESTNET));
+        assert_eq!(calls[1].1[0]["to"], json!(target));
+    }
+}
@@ -0,0 +1,215 @@
+use crate::config::Config;
+use crate::types::network::Network;
+use crate::utils::eth::{EthClient, IERC20};
+use crate::utils::rpc_pool::{PoolProvider, RpcPool};
+use alloy::primitives::{Address, B256, Bytes, U256};
+use alloy::providers::Provider;
+use alloy::rpc::types::{TransactionReceipt, TransactionRequest};
+use alloy::sol_types::SolCall;
+use anyhow::{Result, anyhow, bail};
+use async_trait::async_trait;
+use serde::de::DeserializeOwned;
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// Chain access used by commands. Requests go through a single method so
+/// endpoint selection, authentication and failover stay with the
+/// implementation, and the typed helpers behave the same on every backend:
+/// the endpoint pool in production and [`MockRpc`] in tests.
+#[async_trait]
+pub trait ChainRpc: Send + Sync {
+    /// Sends a JSON-RPC request and returns its `result`
+    async fn request(&self, method: &'static str, params: Value) -> Result<Value>;
+
+    async fn chain_id(&self) -> Result<u64> {
+        decode(self.request("eth_chainId", json!([])).await?)
+            .map(|id: alloy::primitives::U64| id.to::<u64>())
+    }
+
+    async fn block_number(&self) -> Result<u64> {
+        decode(self.request("eth_blockNumber", json!([])).await?)
+            .map(|number: alloy::primitives::U64| number.to::<u64>())
+    }
+
+    async fn gas_price(&self) -> Result<u128> {
+        let price: U256 = decode(self.request("eth_gasPrice", json!([])).await?)?;
+        u128::try_from(price).map_err(|_| anyhow!("Invalid RPC response: gas price {} is out of range", price))
+    }
+
+    /// RBTC balance at the latest block
+    async fn balance(&self, address: Address) -> Result<U256> {
+        decode(self.request("eth_getBalance", json!([address, "latest"])).await?)
+    }
+
+    /// ERC20 balance at the latest block
+    async fn token_balance(&self, token: Address, holder: Address) -> Result<U256> {
+        let data = IERC20::balanceOfCall { account: holder }.abi_encode();
+        let output = self.eth_call(token, data.into()).await?;
+        IERC20::balanceOfCall::abi_decode_returns(&output, true)
+            .map(|balance| balance._0)
+            .map_err(|e| anyhow!("Invalid balanceOf response from {}: {}", token, e))
+    }
+
+    /// Gas `tx` would use if it were sent now
+    async fn estimate(&self, tx: &TransactionRequest) -> Result<u64> {
+        decode(self.request("eth_estimateGas", json!([tx])).await?)
+            .map(|gas: alloy::primitives::U64| gas.to::<u64>())
+    }
+
+    /// Deployed bytecode at the latest block, empty for accounts without code
+    async fn code(&self, address: Address) -> Result<Bytes> {
+        decode(self.request("eth_getCode", json!([address, "latest"])).await?)
+    }
+
+    /// Transaction as the node returns it, `None` if it is unknown
+    async fn transaction(&self, hash: B256) -> Result<Option<Value>> {
+        let tx = self.request("eth_getTransactionByHash", json!([hash])).await?;
+        Ok((!tx.is_null()).then_some(tx))
+    }
+
+    /// Receipt of a mined transaction, `None` while it is pending or unknown
+    async fn receipt(&self, hash: B256) -> Result<Option<TransactionReceipt>> {
+        decode(self.request("eth_getTransactionReceipt", json!([hash])).await?)
+    }
+
+    /// Block header with transaction hashes only, `None` if not mined yet
+    async fn block(&self, number: u64) -> Result<Option<Value>> {
+        let block = self
+            .request("eth_getBlockByNumber", json!([format!("0x{:x}", number), false]))
+            .await?;
+        Ok((!block.is_null()).then_some(block))
+    }
+
+    /// Read-only contract call at the latest block, such as a Bridge method
+    async fn eth_call(&self, to: Address, data: Bytes) -> Result<Bytes> {
+        decode(self.request("eth_call", json!([{ "to": to, "data": data }, "latest"])).await?)
+    }
+}
+
+fn decode<T: DeserializeOwned>(value: Value) -> Result<T> {
+    serde_json::from_value(value).map_err(|e| anyhow!("Invalid RPC response: {}", e))
+}
+
+#[async_trait]
+impl ChainRpc for PoolProvider {
+    async fn request(&self, method: &'static str, params: Value) -> Result<Value> {
+        self.raw_request(method.into(), params)
+            .await
+            .map_err(|e| anyhow!("{} failed: {}", method, e))
+    }
+}
+
+#[async_trait]
+impl ChainRpc for EthClient {
+    async fn request(&self, method: &'static str, params: Value) -> Result<Value> {
+        self.provider().request(method, params).await
+    }
+}
+
+/// Connects to a network through the endpoint pool; which endpoints are
+/// used and how they authenticate is decided by [`Config::rpc_urls_for`]
+pub fn connect(config: &Config, network: &Network, alchemy_key: Option<&str>) -> Result<PoolProvider> {
+    let urls = config.rpc_urls_for(network, alchemy_key);
+    if urls.is_empty() {
+        bail!("No RPC endpoint configured for {}", network);
+    }
+    Ok(RpcPool::shared(&urls).provider())
+}
+
+/// Like [`connect`], for a network given by name: a built-in chain name or
+/// a configured custom network
+pub fn connect_to(config: &Config, network: &str, alchemy_key: Option<&str>) -> Result<PoolProvider> {
+    if let Some(custom) = config.custom_network(network) {
+        return Ok(RpcPool::shared(&custom.get_config().rpc_urls()).provider());
+    }
+    let network = Network::from_str(network).ok_or_else(|| anyhow!("Unknown network '{}'", network))?;
+    connect(config, &network, alchemy_key)
+}
+
+/// Canned responses by method, for exercising commands without a node.
+/// Unknown methods fail like a node without that RPC module.
+#[derive(Debug, Default)]
+pub struct MockRpc {
+    responses: Mutex<HashMap<String, Value>>,
+    requests: Mutex<Vec<(String, Value)>>,
+}
+
+impl MockRpc {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Answers every `method` request with `result`
+    pub fn with(self, method: &str, result: Value) -> Self {
+        self.responses.lock().unwrap().insert(method.to_string(), result);
+        self
+    }
+
+    /// Requests received so far, as method and params
+    pub fn requests(&self) -> Vec<(String, Value)> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+#[async_trait]
+impl ChainRpc for MockRpc {
+    async fn request(&self, method: &'static str, params: Value) -> Result<Value> {
+        self.requests.lock().unwrap().push((method.to_string(), params));
+        self.responses
+            .lock()
+            .unwrap()
+            .get(method)
+            .cloned()
+            .ok_or_else(|| anyhow!("{} failed: error code -32601: method not found", method))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_typed_helpers_decode_node_responses() {
+        let hash = B256::with_last_byte(7);
+        let rpc = MockRpc::new()
+            .with("eth_chainId", json!("0x1f"))
+            .with("eth_gasPrice", json!("0x3938700"))
+            .with("eth_getTransactionByHash", Value::Null)
+            .with(
+                "eth_getTransactionReceipt",
+                json!({
+                    "transactionHash": hash,
+                    "transactionIndex": "0x0",
+                    "blockHash": B256::with_last_byte(1),
+                    "blockNumber": "0x10",
+                    "from": Address::with_last_byte(1),
+                    "to": Address::with_last_byte(2),
+                    "contractAddress": null,
+                    "gasUsed": "0x5208",
+                    "cumulativeGasUsed": "0x5208",
+                    "effectiveGasPrice": "0x3938700",
+                    "logs": [],
+                    "logsBloom": format!("0x{}", "0".repeat(512)),
+                    "status": "0x1",
+                    "type": "0x0"
+                }),
+            );
+
+        assert_eq!(rpc.chain_id().await.unwrap(), 31);
+        assert_eq!(rpc.gas_price().await.unwrap(), 60_000_000);
+        assert_eq!(rpc.transaction(hash).await.unwrap(), None);
+        let receipt = rpc.receipt(hash).await.unwrap().unwrap();
+        assert!(receipt.status());
+        assert_eq!(receipt.block_number, Some(16));
+        assert_eq!(receipt.gas_used, 21_000);
+        assert_eq!(rpc.requests()[3], ("eth_getTransactionReceipt".to_string(), json!([hash])));
+
+        let missing = rpc.block_number().await.unwrap_err().to_string();
+        assert!(missing.contains("-32601"));
+
+        let huge = MockRpc::new().with("eth_gasPrice", json!(format!("0x1{}", "0".repeat(40))));
+        let error = huge.gas_price().await.unwrap_err().to_string();
+        assert!(error.contains("out of range"), "{}", error);
+    }
+}
@@ -0,0 +1,515 @@
+use crate::utils::http::{self, RATE_LIMIT_CODES, Retry};
+use alloy::primitives::{B256, Bytes, keccak256};
+use alloy::providers::RootProvider;
+use alloy::rpc::client::RpcClient;
+use alloy::rpc::json_rpc::{Id, Request, RequestPacket, Response, ResponsePacket, ResponsePayload};
+use alloy::transports::utils::guess_local_url;
+use alloy::transports::{TransportError, TransportErrorKind, TransportFut};
+use serde::Serialize;
+use serde_json::Value;
+use serde_json::value::RawValue;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+use tokio::task::JoinSet;
+use tower::Service;
+
+/// Provider backed by an [`RpcPool`]
+pub type PoolProvider = RootProvider<PoolTransport>;
+
+/// Longest time a failing endpoint is skipped
+const MAX_COOLDOWN: Duration = Duration::from_secs(60);
+
+/// Blocks an endpoint may trail the best one before it is ranked down
+const MAX_LAG_BLOCKS: u64 = 3;
+
+/// Block heights older than this are not compared
+const BLOCK_OBSERVATION_TTL: Duration = Duration::from_secs(60);
+
+/// Score added per position in the configured order, so the preferred
+/// endpoint wins unless another is clearly faster
+const PREFERENCE_MS: f64 = 150.0;
+const FAILURE_PENALTY_MS: f64 = 2_000.0;
+const LAG_PENALTY_MS: f64 = 500.0;
+
+/// Weight of the newest sample in the latency moving average
+const LATENCY_SMOOTHING: f64 = 0.3;
+
+/// Methods that must reach the network at most once. A failed send may
+/// still have been accepted by the node, so it is never retried elsewhere.
+const NON_IDEMPOTENT: &[&str] = &["eth_sendRawTransaction", "eth_sendTransaction"];
+
+#[derive(Debug, Clone, Default)]
+struct Health {
+    latency_ms: Option<f64>,
+    successes: u64,
+    failures: u64,
+    consecutive_failures: u32,
+    last_error: Option<String>,
+    block: Option<(u64, Instant)>,
+    cooling_until: Option<Instant>,
+}
+
+impl Health {
+    fn block_at(&self, now: Instant) -> Option<u64> {
+        self.block
+            .filter(|(_, seen)| now.duration_since(*seen) < BLOCK_OBSERVATION_TTL)
+            .map(|(block, _)| block)
+    }
+
+    fn is_cooling(&self, now: Instant) -> bool {
+        self.cooling_until.is_some_and(|until| until > now)
+    }
+}
+
+/// Health of one endpoint, as shown in the System menu
+#[derive(Debug, Clone)]
+pub struct EndpointStatus {
+    /// URL with any API key removed
+    pub url: String,
+    pub latency_ms: Option<f64>,
+    pub successes: u64,
+    pub failures: u64,
+    pub last_error: Option<String>,
+    pub block: Option<u64>,
+    /// Blocks behind the most advanced endpoint
+    pub lag: Option<u64>,
+    /// `false` while the endpoint is skipped after failures
+    pub available: bool,
+    /// Lower is better
+    pub score: f64,
+}
+
+/// A set of RPC endpoints for one network, used as a single transport.
+///
+/// Requests go to the best-scoring endpoint. Scores combine the configured
+/// preference, measured latency, recent failures and how far the endpoint
+/// trails the others in block height. Idempotent calls that fail at the
+/// transport level are retried on the next endpoint; transaction sends are
+/// not.
+#[derive(Debug)]
+pub struct RpcPool {
+    urls: Vec<String>,
+    health: Mutex<Vec<Health>>,
+}
+
+impl RpcPool {
+    /// Creates a pool from endpoints in order of preference; duplicates and
+    /// empty URLs are dropped
+    pub fn new(urls: &[String]) -> Self {
+        let mut unique: Vec<String> = Vec::new();
+        for url in urls {
+            let url = url.trim();
+            if !url.is_empty() && !unique.iter().any(|u| u == url) {
+                unique.push(url.to_string());
+            }
+        }
+        Self {
+            health: Mutex::new(vec![Health::default(); unique.len()]),
+            urls: unique,
+        }
+    }
+
+    /// The pool for a list of endpoints, shared for the life of the process
+    /// so health carries over between commands in the interactive session
+    pub fn shared(urls: &[String]) -> Arc<Self> {
+        static POOLS: OnceLock<Mutex<HashMap<Vec<String>, Arc<RpcPool>>>> = OnceLock::new();
+        let mut pools = POOLS
+            .get_or_init(Default::default)
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        pools
+            .entry(urls.to_vec())
+            .or_insert_with(|| Arc::new(Self::new(urls)))
+            .clone()
+    }
+
+    pub fn provider(self: &Arc<Self>) -> PoolProvider {
+        let is_local = !self.urls.is_empty() && self.urls.iter().all(guess_local_url);
+        RootProvider::new(RpcClient::new(PoolTransport { pool: self.clone() }, is_local))
+    }
+
+    pub fn urls(&self) -> &[String] {
+        &self.urls
+    }
+
+    /// Endpoint indexes, best first. Endpoints cooling down after failures
+    /// come last so they are still tried when nothing else works.
+    fn ranked(&self) -> Vec<usize> {
+        let now = Instant::now();
+        let health = self.health.lock().unwrap_or_else(|e| e.into_inner());
+        let scores = scores(&health, now);
+        let mut order: Vec<usize> = (0..health.len()).collect();
+        order.sort_by(|&a, &b| {
+            health[a]
+                .is_cooling(now)
+                .cmp(&health[b].is_cooling(now))
+                .then(scores[a].total_cmp(&scores[b]))
+        });
+        order
+    }
+
+    fn record_success(&self, index: usize, elapsed: Duration, block: Option<u64>) {
+        let mut health = self.health.lock().unwrap_or_else(|e| e.into_inner());
+        let entry = &mut health[index];
+        let sample = elapsed.as_secs_f64() * 1000.0;
+        entry.latency_ms = Some(match entry.latency_ms {
+            Some(avg) => avg + LATENCY_SMOOTHING * (sample - avg),
+            None => sample,
+        });
+        entry.successes += 1;
+        entry.consecutive_failures = 0;
+        entry.cooling_until = None;
+        if let Some(block) = block {
+            entry.block = Some((block, Instant::now()));
+        }
+    }
+
+    fn record_failure(&self, index: usize, error: &TransportError) {
+        let mut health = self.health.lock().unwrap_or_else(|e| e.into_inner());
+        let entry = &mut health[index];
+        entry.failures += 1;
+        entry.consecutive_failures += 1;
+        entry.last_error = Some(error.to_string());
+        let cooldown = Duration::from_secs(1 << entry.consecutive_failures.min(6).saturating_sub(1));
+        entry.cooling_until = Some(Instant::now() + cooldown.min(MAX_COOLDOWN));
+    }
+
+    /// Posts a JSON body to one endpoint and returns the response body.
+    /// Only throttled requests are retried on the same endpoint; anything
+    /// else fails over to the next one.
+    async fn post(&self, index: usize, body: &impl Serialize) -> Result<Vec<u8>, TransportError> {
+        let url = &self.urls[index];
+        let response = http::client()
+            .send(url, Retry::RateLimited, |client| client.post(url).json(body))
+            .await
+            .map_err(|e| TransportErrorKind::custom_str(&e.to_string()))?;
+        let status = response.status();
+        let body = response.bytes().await.map_err(TransportErrorKind::custom)?;
+        if !status.is_success() {
+            return Err(TransportErrorKind::http_error(
+                status.as_u16(),
+                String::from_utf8_lossy(&body).into_owned(),
+            ));
+        }
+        Ok(body.to_vec())
+    }
+
+    /// Sends a packet to one endpoint and records the outcome
+    async fn attempt(&self, index: usize, packet: &RequestPacket) -> Result<ResponsePacket, TransportError> {
+        let started = Instant::now();
+        let result = self.post(index, packet).await.and_then(|body| {
+            let response: ResponsePacket = serde_json::from_slice(&body)
+                .map_err(|e| TransportError::deser_err(e, String::from_utf8_lossy(&body)))?;
+            if let Some(error) = response.iter_errors().find(|e| RATE_LIMIT_CODES.contains(&e.code)) {
+                return Err(TransportErrorKind::custom_str(&format!(
+                    "rate limited: {}",
+                    error.message
+                )));
+            }
+            Ok(response)
+        });
+
+        match &result {
+            Ok(response) => self.record_success(index, started.elapsed(), block_number(packet, response)),
+            Err(error) => self.record_failure(index, error),
+        }
+        result
+    }
+
+    async fn dispatch(&self, packet: RequestPacket) -> Result<ResponsePacket, TransportError> {
+        let order = self.ranked();
+        let Some(&first) = order.first() else {
+            return Err(TransportErrorKind::custom_str("No RPC endpoints configured"));
+        };
+
+        if packet_methods(&packet).any(|m| NON_IDEMPOTENT.contains(&m)) {
+            return match self.attempt(first, &packet).await {
+                Ok(response) => Ok(response),
+                Err(error) => self.recover_send(&packet, error).await,
+            };
+        }
+
+        let mut last_error = None;
+        for index in order {
+            match self.attempt(index, &packet).await {
+                Ok(response) => return Ok(response),
+                Err(error) => {
+                    log::warn!("RPC endpoint {} failed: {}", redact_url(&self.urls[index]), error);
+                    last_error = Some(error);
+                }
+            }
+        }
+        Err(last_error.unwrap_or_else(|| TransportErrorKind::custom_str("All RPC endpoints failed")))
+    }
+
+    /// After a send fails at the transport level the node may still have
+    /// accepted the transaction. Ask every endpoint whether it knows the
+    /// transaction; if one does, report the hash as sent. Otherwise fail
+    /// without resending, naming the hash so it can be checked.
+    async fn recover_send(&self, packet: &RequestPacket, error: TransportError) -> Result<ResponsePacket, TransportError> {
+        let RequestPacket::Single(request) = packet else {
+            return Err(error);
+        };
+        let Some(hash) = request
+            .params()
+            .and_then(|params| serde_json::from_str::<(Bytes,)>(params.get()).ok())
+            .map(|(raw,)| keccak256(raw))
+        else {
+            return Err(error);
+        };
+
+        if self.transaction_known(hash).await {
+            let result = RawValue::from_string(format!("\"{:#x}\"", hash))
+                .map_err(TransportError::ser_err)?;
+            return Ok(ResponsePacket::Single(Response {
+                id: request.id().clone(),
+                payload: ResponsePayload::Success(result),
+            }));
+        }
+
+        Err(TransportErrorKind::custom_str(&format!(
+            "{}. Transaction {:#x} was not resent; check whether it reached the network before sending again",
+            error, hash
+        )))
+    }
+
+    async fn transaction_known(&self, hash: B256) -> bool {
+        let body = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "eth_getTransactionByHash",
+            "params": [format!("{:#x}", hash)],
+        });
+        for index in self.ranked() {
+            if let Ok(body) = self.post(index, &body).await
+                && let Ok(response) = serde_json::from_slice::<Value>(&body)
+                && response.get("result").is_some_and(|r| !r.is_null())
+            {
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Measures every endpoint with `eth_blockNumber`, so latency and block
+    /// height are current for all of them
+    pub async fn probe(self: &Arc<Self>) {
+        let mut tasks = JoinSet::new();
+        for index in 0..self.urls.len() {
+            let pool = self.clone();
+            tasks.spawn(async move {
+                let request = Request::new("eth_blockNumber", Id::Number(1), ()).serialize();
+                if let Ok(packet) = request.map(RequestPacket::Single) {
+                    let _ = pool.attempt(index, &packet).await;
+                }
+            });
+        }
+        while tasks.join_next().await.is_some() {}
+    }
+
+    /// Current health of every endpoint, in configured order
+    pub fn status(&self) -> Vec<EndpointStatus> {
+        let now = Instant::now();
+        let health = self.health.lock().unwrap_or_else(|e| e.into_inner());
+        let scores = scores(&health, now);
+        let best = health.iter().filter_map(|h| h.block_at(now)).max();
+        health
+            .iter()
+            .zip(&self.urls)
+            .zip(scores)
+            .map(|((h, url), score)| EndpointStatus {
+                url: redact_url(url),
+                latency_ms: h.latency_ms,
+                successes: h.successes,
+                failures: h.failures,
+                last_error: h.last_error.clone(),
+                block: h.block.map(|(block, _)| block),
+                lag: best.zip(h.block_at(now)).map(|(best, block)| best - block),
+                available: !h.is_cooling(now),
+                score,
+            })
+            .collect()
+    }
+}
+
+fn scores(health: &[Health], now: Instant) -> Vec<f64> {
+    let best_block = health.iter().filter_map(|h| h.block_at(now)).max();
+    // Unmeasured endpoints count as the slowest measured one, so the
+    // preferred endpoint is not abandoned just because it was measured
+    let slowest = health
+        .iter()
+        .filter_map(|h| h.latency_ms)
+        .fold(0.0, f64::max);
+
+    health
+        .iter()
+        .enumerate()
+        .map(|(index, h)| {
+            let mut score = h.latency_ms.unwrap_or(slowest) + index as f64 * PREFERENCE_MS;
+            score += h.consecutive_failures as f64 * FAILURE_PENALTY_MS;
+            let total = h.successes + h.failures;
+            if total > 0 {
+                score += h.failures as f64 / total as f64 * FAILURE_PENALTY_MS / 2.0;
+            }
+            if let (Some(best), Some(block)) = (best_block, h.block_at(now)) {
+                let lag = best - block;
+                if lag > MAX_LAG_BLOCKS {
+                    score += lag as f64 * LAG_PENALTY_MS;
+                }
+            }
+            score
+        })
+        .collect()
+}
+
+fn packet_methods(packet: &RequestPacket) -> impl Iterator<Item = &str> {
+    let requests = match packet {
+        RequestPacket::Single(request) => std::slice::from_ref(request),
+        RequestPacket::Batch(requests) => requests.as_slice(),
+    };
+    requests.iter().map(|r| r.method())
+}
+
+/// Block height in the response to a single `eth_blockNumber` request
+fn block_number(packet: &RequestPacket, response: &ResponsePacket) -> Option<u64> {
+    let (RequestPacket::Single(request), ResponsePacket::Single(response)) = (packet, response) else {
+        return None;
+    };
+    if request.method() != "eth_blockNumber" {
+        return None;
+    }
+    let ResponsePayload::Success(result) = &response.payload else {
+        return None;
+    };
+    let hex: String = serde_json::from_str(result.get()).ok()?;
+    u64::from_str_radix(hex.trim_start_matches("0x"), 16).ok()
+}
+
+/// Scheme, host and port of a URL. Hosted endpoints carry API keys in the
+/// path, which must not be printed.
+pub fn redact_url(url: &str) -> String {
+    match url::Url::parse(url) {
+        Ok(parsed) => {
+            let mut shown = format!("{}://{}", parsed.scheme(), parsed.host_str().unwrap_or_default());
+            if let Some(port) = parsed.port() {
+                shown.push_str(&format!(":{}", port));
+            }
+            if parsed.path().len() > 1 || parsed.query().is_some() {
+                shown.push_str("/***");
+            }
+            shown
+        }
+        Err(_) => "<invalid url>".to_string(),
+    }
+}
+
+/// Transport that sends each request through an [`RpcPool`]
+#[derive(Debug, Clone)]
+pub struct PoolTransport {
+    pool: Arc<RpcPool>,
+}
+
+impl Service<RequestPacket> for PoolTransport {
+    type Response = ResponsePacket;
+    type Error = TransportError;
+    type Future = TransportFut<'static>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, packet: RequestPacket) -> Self::Future {
+        let pool = self.pool.clone();
+        Box::pin(async move { pool.dispatch(packet).await })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn measured(latency_ms: f64) -> Health {
+        Health {
+            latency_ms: Some(latency_ms),
+            successes: 10,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_ranking_prefers_healthy_endpoints() {
+        let urls: Vec<String> = ["http://a", "http://b", "http://c"].map(String::from).to_vec();
+        let pool = RpcPool::new(&urls);
+        let now = Instant::now();
+
+        // Measured preferred endpoint stays ahead of unmeasured ones
+        *pool.health.lock().unwrap() = vec![measured(400.0), Health::default(), Health::default()];
+        assert_eq!(pool.ranked(), vec![0, 1, 2]);
+
+        // A clearly faster endpoint wins over the preferred one
+        *pool.health.lock().unwrap() = vec![measured(900.0), measured(100.0), Health::default()];
+        assert_eq!(pool.ranked()[0], 1);
+
+        // Failing endpoints cool down and move to the back
+        let mut failing = measured(50.0);
+        failing.consecutive_failures = 1;
+        failing.cooling_until = Some(now + Duration::from_secs(5));
+        *pool.health.lock().unwrap() = vec![failing, measured(300.0), measured(300.0)];
+        assert_eq!(pool.ranked(), vec![1, 2, 0]);
+
+        // An endpoint trailing the others in block height is ranked down
+        let mut lagging = measured(50.0);
+        lagging.block = Some((100, now));
+        let mut synced = measured(300.0);
+        synced.block = Some((110, now));
+        *pool.health.lock().unwrap() = vec![lagging, synced, Health::default()];
+        assert_eq!(pool.ranked()[0], 1);
+        assert_eq!(pool.status()[0].lag, Some(10));
+    }
+
+    #[test]
+    fn test_redact_url_hides_api_keys() {
+        assert_eq!(
+            redact_url("https://rpc.rootstock.io/secret-key"),
+            "https://rpc.rootstock.io/***"
+        );
+        assert_eq!(redact_url("http://localhost:4444"), "http://localhost:4444");
+        assert_eq!(redact_url("https://public-node.rsk.co/"), "https://public-node.rsk.co");
+    }
+
+    async fn dead_endpoint() -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        format!("http://{}", listener.local_addr().unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_reads_fail_over_but_sends_are_not_resent() {
+        use crate::utils::mock_node::{Fixtures, TestEnv};
+        use alloy::providers::Provider;
+
+        let env = TestEnv::start(Fixtures {
+            block_number: 16,
+            ..Fixtures::default()
+        })
+        .await;
+        let pool = Arc::new(RpcPool::new(&[dead_endpoint().await, env.node.url().to_string()]));
+        let provider = pool.provider();
+
+        assert_eq!(provider.get_block_number().await.unwrap(), 16);
+        let status = pool.status();
+        assert_eq!((status[0].failures, status[1].successes), (1, 1));
+        assert_eq!(status[1].block, Some(16));
+
+        // Forget the failure so the dead endpoint is tried first again
+        pool.health.lock().unwrap()[0] = Health::default();
+        let error = provider
+            .send_raw_transaction(&[0x02, 0xc0])
+            .await
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("was not resent"), "{}", error);
+        assert!(!env.node.methods().iter().any(|m| m == "eth_sendRawTransaction"));
+    }
+}
@@ -0,0 +1,368 @@
+use crate::commands::tokens::{TokenInfo, TokenRegistry};
+use crate::utils::http::{self, Retry};
+use alloy::primitives::Address;
+use anyhow::{Result, anyhow};
+use console::style;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+
+/// Token list in the tokenlists.org schema, as published by wallets and DEXes
+#[derive(Debug, Clone, Deserialize)]
+pub struct TokenList {
+    pub name: String,
+    pub version: ListVersion,
+    pub tokens: Vec<ListToken>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListToken {
+    pub chain_id: u64,
+    pub address: String,
+    pub name: String,
+    pub symbol: String,
+    pub decimals: u8,
+    #[serde(rename = "logoURI")]
+    pub logo_uri: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+/// Semantic version of a list; a major bump means tokens were removed or
+/// changed in a way that breaks earlier versions
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct ListVersion {
+    pub major: u32,
+    pub minor: u32,
+    pub patch: u32,
+}
+
+impl fmt::Display for ListVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+/// A list the registry was populated from, kept so it can be updated
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ListSubscription {
+    pub name: String,
+    pub version: ListVersion,
+}
+
+/// Registry network for an RSK chain id; tokens on other chains are ignored
+pub fn network_for_chain(chain_id: u64) -> Option<&'static str> {
+    match chain_id {
+        30 => Some("mainnet"),
+        31 => Some("testnet"),
+        _ => None,
+    }
+}
+
+/// Key a list is recorded under: the URL, or the absolute path of a file so
+/// it can be updated from any directory
+pub fn source_key(source: &str) -> Result<String> {
+    if is_url(source) {
+        return Ok(source.to_string());
+    }
+    let path = Path::new(source)
+        .canonicalize()
+        .map_err(|e| anyhow!("Cannot read token list {}: {}", source, e))?;
+    Ok(path.to_string_lossy().into_owned())
+}
+
+fn is_url(source: &str) -> bool {
+    source.starts_with("https://") || source.starts_with("http://")
+}
+
+/// Reads a token list from a URL or a file
+pub async fn fetch(source: &str) -> Result<TokenList> {
+    let content = if is_url(source) {
+        let response = http::client()
+            .send(source, Retry::Transient, |client| client.get(source))
+            .await?;
+        if !response.status().is_success() {
+            return Err(anyhow!("Fetching {} failed with HTTP {}", source, response.status()));
+        }
+        response
+            .text()
+            .await
+            .map_err(|e| anyhow!("Failed to read token list from {}: {}", source, e))?
+    } else {
+        std::fs::read_to_string(source)
+            .map_err(|e| anyhow!("Cannot read token list {}: {}", source, e))?
+    };
+    serde_json::from_str(&content).map_err(|e| anyhow!("{} is not a valid token list: {}", source, e))
+}
+
+/// Changes an import or update would make to the registry
+#[derive(Debug, Default)]
+pub struct ListDiff {
+    /// Network, symbol and the new entry
+    pub added: Vec<(String, String, TokenInfo)>,
+    /// Network, symbol and the entry going away
+    pub removed: Vec<(String, String, TokenInfo)>,
+    /// Network, symbol, current and new entry
+    pub changed: Vec<(String, String, TokenInfo, TokenInfo)>,
+    /// Network, symbol and why the list entry is not imported
+    pub skipped: Vec<(String, String, String)>,
+}
+
+impl ListDiff {
+    /// Compares `list`, recorded under `source`, with what the registry
+    /// holds from it. Tokens whose symbol or address is already taken by a
+    /// bundled token, the user or another list are skipped, never replaced.
+    pub fn new(registry: &TokenRegistry, list: &TokenList, source: &str) -> Self {
+        let mut diff = ListDiff::default();
+        let mut incoming: BTreeMap<(String, String), TokenInfo> = BTreeMap::new();
+
+        for token in &list.tokens {
+            let Some(network) = network_for_chain(token.chain_id) else {
+                continue;
+            };
+            let symbol = token.symbol.trim().to_uppercase();
+            let Ok(address) = Address::from_str(&token.address) else {
+                diff.skipped
+                    .push((network.to_string(), symbol, format!("invalid address {}", token.address)));
+                continue;
+            };
+            let key = (network.to_string(), symbol);
+            if incoming.contains_key(&key) {
+                diff.skipped.push((key.0, key.1, "listed more than once".to_string()));
+                continue;
+            }
+            incoming.insert(
+                key,
+                TokenInfo {
+                    address: address.to_checksum(None),
+                    decimals: token.decimals,
+                    name: Some(token.name.clone()),
+                    logo_uri: token.logo_uri.clone(),
+                    tags: token.tags.clone(),
+                    list: Some(source.to_string()),
+                },
+            );
+        }
+
+        for ((network, symbol), info) in &incoming {
+            let tokens = registry.tokens(network);
+            match tokens.get(symbol) {
+                Some(current) if current.list.as_deref() == Some(source) => {
+                    if current != info {
+                        diff.changed
+                            .push((network.clone(), symbol.clone(), current.clone(), info.clone()));
+                    }
+                }
+                // Already registered by hand or bundled; nothing to report
+                Some(current) if current.address.eq_ignore_ascii_case(&info.address) => {}
+                Some(current) => diff.skipped.push((
+                    network.clone(),
+                    symbol.clone(),
+                    format!("symbol already used by {}", current.address),
+                )),
+                None => match tokens
+                    .iter()
+                    .find(|(_, t)| t.address.eq_ignore_ascii_case(&info.address))
+                {
+                    Some((other, _)) => diff.skipped.push((
+                        network.clone(),
+                        symbol.clone(),
+                        format!("address already registered as {}", other),
+                    )),
+                    None => diff.added.push((network.clone(), symbol.clone(), info.clone())),
+                },
+            }
+        }
+
+        for network in ["mainnet", "testnet"] {
+            for (symbol, info) in registry.tokens(network) {
+                if info.list.as_deref() == Some(source)
+                    && !incoming.contains_key(&(network.to_string(), symbol.clone()))
+                {
+                    diff.removed.push((network.to_string(), symbol.clone(), info.clone()));
+                }
+            }
+        }
+        diff.removed.sort_by(|a, b| (&a.0, &a.1).cmp(&(&b.0, &b.1)));
+        diff
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+
+    pub fn print(&self) {
+        for (network, symbol, info) in &self.added {
+            println!(
+                "  {} {:<10} {} {}",
+                style("+").green(),
+                symbol,
+                info.address,
+                style(format!("{} · {} decimals", network, info.decimals)).dim()
+            );
+        }
+        for (network, symbol, current, new) in &self.changed {
+            println!(
+                "  {} {:<10} {}",
+                style("~").yellow(),
+                symbol,
+                style(network).dim()
+            );
+            for (field, before, after) in changed_fields(current, new) {
+                println!("      {}: {} → {}", field, before, after);
+            }
+        }
+        for (network, symbol, info) in &self.removed {
+            println!(
+                "  {} {:<10} {} {}",
+                style("-").red(),
+                symbol,
+                info.address,
+                style(network).dim()
+            );
+        }
+        for (network, symbol, reason) in &self.skipped {
+            println!(
+                "  {} {}",
+                style("skipped").dim(),
+                style(format!("{} on {}: {}", symbol, network, reason)).dim()
+            );
+        }
+        println!(
+            "\n{} added, {} changed, {} removed, {} skipped",
+            self.added.len(),
+            self.changed.len(),
+            self.removed.len(),
+            self.skipped.len()
+        );
+    }
+
+    /// Applies the changes and records the list as a subscription
+    pub fn apply(&self, registry: &mut TokenRegistry, list: &TokenList, source: &str) {
+        for (network, symbol, info) in &self.added {
+            registry.tokens_mut(network).insert(symbol.clone(), info.clone());
+        }
+        for (network, symbol, _, new) in &self.changed {
+            registry.tokens_mut(network).insert(symbol.clone(), new.clone());
+        }
+        for (network, symbol, _) in &self.removed {
+            registry.tokens_mut(network).remove(symbol);
+        }
+        registry.lists.insert(
+            source.to_string(),
+            ListSubscription {
+                name: list.name.clone(),
+                version: list.version,
+            },
+        );
+    }
+}
+
+fn changed_fields(current: &TokenInfo, new: &TokenInfo) -> Vec<(&'static str, String, String)> {
+    let show = |value: &Option<String>| value.clone().unwrap_or_else(|| "-".to_string());
+    let mut fields = Vec::new();
+    if !current.address.eq_ignore_ascii_case(&new.address) {
+        fields.push(("address", current.address.clone(), new.address.clone()));
+    }
+    if current.decimals != new.decimals {
+        fields.push(("decimals", current.decimals.to_string(), new.decimals.to_string()));
+    }
+    if current.name != new.name {
+        fields.push(("name", show(&current.name), show(&new.name)));
+    }
+    if current.logo_uri != new.logo_uri {
+        fields.push(("logo", show(&current.logo_uri), show(&new.logo_uri)));
+    }
+    if current.tags != new.tags {
+        fields.push(("tags", current.tags.join(", "), new.tags.join(", ")));
+    }
+    fields
+}
+
+/// How a fetched list relates to the version already imported
+pub fn compare_versions(registry: &TokenRegistry, list: &TokenList, source: &str) -> Option<Ordering> {
+    registry
+        .lists
+        .get(source)
+        .map(|subscription| list.version.cmp(&subscription.version))
+}
+
+/// Names of the subscribed lists by source, for display
+pub fn list_names(registry: &TokenRegistry) -> HashMap<String, String> {
+    registry
+        .lists
+        .iter()
+        .map(|(source, subscription)| (source.clone(), subscription.name.clone()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn list(version: (u32, u32, u32), tokens: &[(u64, &str, &str, u8)]) -> TokenList {
+        TokenList {
+            name: "RSK Tokens".to_string(),
+            version: ListVersion {
+                major: version.0,
+                minor: version.1,
+                patch: version.2,
+            },
+            tokens: tokens
+                .iter()
+                .map(|(chain_id, symbol, address, decimals)| ListToken {
+                    chain_id: *chain_id,
+                    address: address.to_string(),
+                    name: format!("{} Token", symbol),
+                    symbol: symbol.to_string(),
+                    decimals: *decimals,
+                    logo_uri: None,
+                    tags: vec!["stablecoin".to_string()],
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_list_updates_are_diffed_against_their_own_entries() {
+        let doc = "0xe700691da7b9851f2f35f8b8182c69c53ccad9db";
+        let dllr = "0xc1411567d2670e24d9c4daaa7cda95686e1250aa";
+        let source = "https://example.com/rsk.tokenlist.json";
+        let mut registry = TokenRegistry::bundled();
+
+        let v1 = list(
+            (1, 0, 0),
+            &[
+                (30, "DOC", doc, 18),
+                (30, "DLLR", dllr, 18),
+                (1, "USDC", "0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48", 6),
+                // Bundled already, same address
+                (30, "RIF", "0x2acc95758f8b5f583470ba265eb685a8f45fc9d5", 18),
+                (31, "RIF", "0x0000000000000000000000000000000000000001", 18),
+            ],
+        );
+        let diff = ListDiff::new(&registry, &v1, source);
+        assert_eq!(diff.added.len(), 2);
+        assert_eq!(diff.skipped.len(), 1);
+        assert_eq!(diff.added[0].2.list.as_deref(), Some(source));
+        assert_eq!(diff.added[0].2.tags, vec!["stablecoin".to_string()]);
+        diff.apply(&mut registry, &v1, source);
+        assert_eq!(compare_versions(&registry, &v1, source), Some(Ordering::Equal));
+
+        let v2 = list((2, 0, 0), &[(30, "DOC", doc, 8)]);
+        let diff = ListDiff::new(&registry, &v2, source);
+        assert_eq!(diff.changed.len(), 1);
+        assert_eq!(diff.removed.len(), 1);
+        assert_eq!(diff.removed[0].1, "DLLR");
+        assert!(diff.added.is_empty());
+        diff.apply(&mut registry, &v2, source);
+        assert_eq!(registry.mainnet["DOC"].decimals, 8);
+        assert!(!registry.mainnet.contains_key("DLLR"));
+        assert!(registry.mainnet.contains_key("RIF"));
+        assert_eq!(registry.lists[source].version.major, 2);
+    }
+}
@@ -0,0 +1,148 @@
+use crate::types::transaction::{RskTransaction, TransactionStatus};
+use crate::utils::alchemy::AlchemyClient;
+use crate::utils::constants;
+use crate::utils::secure_fs;
+use alloy::primitives::{Address, U256};
+use crate::utils::rpc::ChainRpc;
+use anyhow::{Result, anyhow};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+/// Whether two entries describe the same transfer. A transaction can move
+/// several assets, or RBTC both externally and internally, so the hash
+/// alone is not enough.
+fn same_transfer(a: &RskTransaction, b: &RskTransaction) -> bool {
+    a.hash == b.hash && a.token_address == b.token_address && a.from == b.from && a.to == b.to
+}
+
+/// Local transaction database for one address on one network.
+///
+/// Every history backend syncs into this store, and history views, filters
+/// and exports are served from it, so they also work offline.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransactionStore {
+    pub address: Address,
+    #[serde(default)]
+    pub network: String,
+    /// Lowest block covered by syncing
+    #[serde(default)]
+    pub first_synced_block: Option<u64>,
+    /// Highest block covered by the last sync
+    #[serde(alias = "last_scanned_block")]
+    pub last_synced_block: Option<u64>,
+    pub transactions: Vec<RskTransaction>,
+}
+
+impl TransactionStore {
+    fn path(network: &str, address: &Address) -> PathBuf {
+        constants::history_dir(network).join(format!("0x{:x}.json", address))
+    }
+
+    /// Loads the store for an address, or an empty one if none exists
+    pub fn load(network: &str, address: &Address) -> Result<Self> {
+        let network = network.to_lowercase();
+        let path = Self::path(&network, address);
+        if !path.exists() {
+            return Ok(Self {
+                address: *address,
+                network,
+                first_synced_block: None,
+                last_synced_block: None,
+                transactions: Vec::new(),
+            });
+        }
+        let data = fs::read_to_string(&path)?;
+        let mut store: Self = serde_json::from_str(&data)
+            .map_err(|e| anyhow!("Failed to parse history file {}: {}", path.display(), e))?;
+        store.network = network;
+        Ok(store)
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let path = Self::path(&self.network, &self.address);
+        secure_fs::write_secure(&path, &serde_json::to_string_pretty(self)?)
+    }
+
+    /// Adds a transfer, replacing an earlier entry for the same transfer
+    pub fn upsert(&mut self, tx: RskTransaction) {
+        match self.transactions.iter_mut().find(|t| same_transfer(t, &tx)) {
+            Some(existing) => *existing = tx,
+            None => self.transactions.push(tx),
+        }
+    }
+
+    /// Records a transfer sent from this CLI and saves the store
+    pub fn record_sent(network: &str, tx: RskTransaction) -> Result<()> {
+        let mut store = Self::load(network, &tx.from)?;
+        store.upsert(tx);
+        store.save()
+    }
+
+    /// Fetches transfers newer than the last sync from Alchemy. Receipts and
+    /// timestamps are only looked up for transfers not already stored.
+    pub async fn sync_alchemy(&mut self, client: &AlchemyClient, rpc: &dyn ChainRpc) -> Result<usize> {
+        let from_block = self.last_synced_block.map(|b| format!("0x{:x}", b));
+        let transfers = client
+            .get_all_transfers(&format!("{:#x}", self.address), None, from_block.as_deref(), None)
+            .await?;
+
+        // Alchemy always covers the full history from genesis
+        self.first_synced_block = Some(0);
+
+        let mut added = 0;
+        for transfer in &transfers {
+            let parse = |field: &Value| field.as_str().and_then(|s| Address::from_str(s).ok());
+            let token = match transfer["category"].as_str() {
+                Some("erc20") => parse(&transfer["rawContract"]["address"]),
+                _ => None,
+            };
+            let (from, to) = (parse(&transfer["from"]), parse(&transfer["to"]));
+            let known = self.transactions.iter().any(|t| {
+                format!("0x{:x}", t.hash).eq_ignore_ascii_case(transfer["hash"].as_str().unwrap_or_default())
+                    && t.token_address == token
+                    && Some(t.from) == from
+                    && t.to == to
+                    && t.status != TransactionStatus::Pending
+            });
+            if known {
+                continue;
+            }
+
+            let tx = RskTransaction::from_alchemy_transfer(transfer, &self.address, rpc).await?;
+            if let Some(block) = tx.block_number {
+                let block = block.to::<u64>();
+                self.last_synced_block = Some(self.last_synced_block.map_or(block, |b| b.max(block)));
+            }
+            self.upsert(tx);
+            added += 1;
+        }
+
+        self.save()?;
+        Ok(added)
+    }
+
+    /// Updates transfers still marked pending with their receipts
+    pub async fn refresh_pending(&mut self, rpc: &dyn ChainRpc) -> Result<()> {
+        for tx in self
+            .transactions
+            .iter_mut()
+            .filter(|t| t.status == TransactionStatus::Pending)
+        {
+            let Some(receipt) = rpc.receipt(tx.hash).await? else {
+                continue;
+            };
+            tx.status = if receipt.status() {
+                TransactionStatus::Success
+            } else {
+                TransactionStatus::Failed
+            };
+            tx.gas = U256::from(receipt.gas_used);
+            tx.block_number = receipt.block_number.map(alloy::primitives::U64::from);
+            tx.logs = Some(receipt.inner.logs().to_vec());
+        }
+        self.save()
+    }
+}